@@ -0,0 +1,959 @@
+//! `party-cli`: import, export, stats and door actions against a Party Sign-In
+//! database without opening the app.
+
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use party_core::{
+  banned::{self, BanInput},
+  batches,
+  checkins::{self, CheckAction, Overrides, RedoStatus, ToggleStatus, UndoStatus},
+  db,
+  events::{self, Event, EventInput},
+  export,
+  guests::{self, GuestEdit, WalkInInput},
+  import::{self, GuestOutcome, ImportMode, ImportPreview, SyncCounts},
+  name_list::AmbiguityKind,
+  profiles::{self, ColumnMapping, ProfileInput},
+  quotas::{self, MemberQuota},
+  search::{self, SearchHit},
+  stats,
+  time::{format_local_timestamp, parse_timezone},
+};
+use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(name = "party-cli", version, about = "Manage a Party Sign-In database from the terminal")]
+struct Cli {
+  /// Path to the SQLite database (the app keeps it in its data directory).
+  #[arg(long, global = true, default_value = "party.db")]
+  db: PathBuf,
+
+  /// Event to work on. May be omitted when the database has only one.
+  #[arg(long, short, global = true)]
+  event: Option<i64>,
+
+  /// Print results as JSON instead of text.
+  #[arg(long, global = true)]
+  json: bool,
+
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// List events in the database, or create one.
+  #[command(alias = "event")]
+  Events {
+    #[command(subcommand)]
+    action: Option<EventAction>,
+  },
+  /// Import a guest list CSV or TSV.
+  Import {
+    file: PathBuf,
+    /// Saved column mapping to read the file with (see `party-cli profile`).
+    #[arg(long)]
+    profile: Option<String>,
+    #[arg(long, value_enum, default_value_t = Mode::Append)]
+    mode: Mode,
+    #[arg(long)]
+    operator: Option<String>,
+    /// Add guests even past their member's guest quota.
+    #[arg(long)]
+    override_quota: bool,
+    /// Let `--mode replace` clear the list while guests are checked in.
+    #[arg(long)]
+    replace_checked_in: bool,
+    /// Show what each row would do without importing anything.
+    #[arg(long)]
+    dry_run: bool,
+  },
+  /// List the event's imports, or roll one back.
+  Batches {
+    /// Remove the guests and check-ins this batch added, restoring the list
+    /// a replace batch cleared.
+    #[arg(long)]
+    rollback: Option<i64>,
+    /// Roll back even if that drops check-ins made at the door or undoes a
+    /// later replace or sync.
+    #[arg(long)]
+    confirm: bool,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Export the guest list as CSV.
+  Export {
+    /// Directory to write into (defaults to the desktop). Use `-` for stdout.
+    #[arg(long)]
+    out: Option<String>,
+  },
+  /// Print attendance totals and who is currently inside.
+  Stats,
+  /// Search guests and members. Accepts `host:NAME`, `in:`, `out:` and `never:`.
+  Search {
+    query: String,
+    #[arg(long, default_value_t = 25)]
+    limit: usize,
+  },
+  /// Check a guest in.
+  CheckIn {
+    guest_id: i64,
+    #[arg(long)]
+    operator: Option<String>,
+    /// Admit the guest even if the venue is at capacity.
+    #[arg(long)]
+    override_capacity: bool,
+    /// Admit the guest even past their member's quota.
+    #[arg(long)]
+    override_quota: bool,
+    /// Admit the guest even though they match the banned list.
+    #[arg(long)]
+    override_ban: bool,
+  },
+  /// Add someone who is not on the list and check them in.
+  WalkIn {
+    name: String,
+    /// Member bringing the guest.
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
+    operator: Option<String>,
+    /// Admit the guest even if the venue is at capacity.
+    #[arg(long)]
+    override_capacity: bool,
+    /// Admit the guest even past their member's quota.
+    #[arg(long)]
+    override_quota: bool,
+    /// Admit the guest even though they match the banned list.
+    #[arg(long)]
+    override_ban: bool,
+  },
+  /// Correct a guest's name and host. Leaving out `--host` clears it.
+  EditGuest {
+    guest_id: i64,
+    name: String,
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Remove a guest and their check-ins.
+  DeleteGuest {
+    guest_id: i64,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Merge a duplicate guest into another, moving over their check-ins.
+  MergeGuests {
+    survivor_id: i64,
+    duplicate_id: i64,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Check a guest out.
+  CheckOut {
+    guest_id: i64,
+    #[arg(long)]
+    operator: Option<String>,
+    /// Record a check-out even if the guest never checked in.
+    #[arg(long)]
+    force: bool,
+  },
+  /// Undo the most recent check-in, check-out, or guest edit, delete or merge.
+  Undo {
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Re-apply the most recently undone action.
+  Redo {
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Show member quotas, or set one member's limits.
+  Quota {
+    /// Member to set limits for; omit to list every member exception.
+    member: Option<String>,
+    /// Guests the member may put on the list.
+    #[arg(long)]
+    guests: Option<i64>,
+    /// Guests of the member allowed inside at once.
+    #[arg(long)]
+    present: Option<i64>,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// List, save or delete import column profiles.
+  Profile {
+    #[command(subcommand)]
+    action: Option<ProfileAction>,
+  },
+  /// Show the banned list, ban someone, or lift a ban.
+  Ban {
+    /// Name to ban; omit to list current bans.
+    name: Option<String>,
+    #[arg(long)]
+    reason: Option<String>,
+    /// Lift the ban with this id instead.
+    #[arg(long, conflicts_with_all = ["name", "reason"])]
+    remove: Option<i64>,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+}
+
+#[derive(Debug, Subcommand)]
+enum EventAction {
+  /// Create an event, e.g. to import into a new database.
+  Create {
+    name: String,
+    /// Date of the event night, `YYYY-MM-DD`.
+    #[arg(long)]
+    date: Option<String>,
+    #[arg(long)]
+    venue: Option<String>,
+    /// Time doors open, `HH:MM`.
+    #[arg(long)]
+    doors_open: Option<String>,
+    /// Time doors close, `HH:MM`.
+    #[arg(long)]
+    doors_close: Option<String>,
+    /// IANA timezone, e.g. `America/Chicago` (the default).
+    #[arg(long)]
+    timezone: Option<String>,
+    /// Most guests allowed inside at once.
+    #[arg(long)]
+    capacity: Option<i64>,
+  },
+}
+
+#[derive(Debug, Subcommand)]
+enum ProfileAction {
+  /// Save a profile, replacing any with the same name. Columns are the
+  /// sheet's header names.
+  Save {
+    name: String,
+    #[arg(long)]
+    guests: String,
+    #[arg(long)]
+    member: Option<String>,
+    #[arg(long)]
+    check_in: Option<String>,
+    #[arg(long)]
+    check_in_time: Option<String>,
+    #[arg(long)]
+    check_out: Option<String>,
+    #[arg(long)]
+    check_out_time: Option<String>,
+    #[arg(long)]
+    walk_in: Option<String>,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Delete a profile.
+  Delete {
+    name: String,
+    #[arg(long)]
+    operator: Option<String>,
+  },
+  /// Print a file's header row, to pick columns for a profile.
+  Headers { file: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mode {
+  Append,
+  Replace,
+  Sync,
+}
+
+impl From<Mode> for ImportMode {
+  fn from(mode: Mode) -> Self {
+    match mode {
+      Mode::Append => ImportMode::Append,
+      Mode::Replace => ImportMode::Replace,
+      Mode::Sync => ImportMode::Sync,
+    }
+  }
+}
+
+fn main() {
+  if let Err(err) = run(Cli::parse()) {
+    eprintln!("error: {:#}", err);
+    std::process::exit(1);
+  }
+}
+
+fn run(cli: Cli) -> Result<()> {
+  let db_path = cli
+    .db
+    .to_str()
+    .ok_or_else(|| anyhow!("database path must be valid UTF-8"))?;
+  let mut conn = db::open(db_path)?;
+
+  if let Command::Ban {
+    name,
+    reason,
+    remove,
+    operator,
+  } = cli.command
+  {
+    return ban(&mut conn, cli.json, name, reason, remove, operator.as_deref());
+  }
+
+  if let Command::Profile { action } = cli.command {
+    return profile(&mut conn, cli.json, action);
+  }
+
+  if let Command::Events { action } = cli.command {
+    return manage_events(&conn, cli.json, action);
+  }
+
+  let event = resolve_event(&conn, cli.event)?;
+
+  match cli.command {
+    Command::Events { .. } | Command::Ban { .. } | Command::Profile { .. } => unreachable!("handled above"),
+    Command::Import {
+      file,
+      profile,
+      mode,
+      operator,
+      override_quota,
+      replace_checked_in,
+      dry_run,
+    } => {
+      let path = file.to_str().ok_or_else(|| anyhow!("file path must be valid UTF-8"))?;
+      let file = import::read_import_file(&conn, path, profile.as_deref())?;
+      let overrides = Overrides {
+        quota: override_quota,
+        checked_in: replace_checked_in,
+        ..Overrides::default()
+      };
+      if dry_run {
+        let preview = import::preview_import(&mut conn, event.id, &file.rows, mode.into(), overrides)?;
+        return print(cli.json, &preview, || print_preview(&preview));
+      }
+      let summary =
+        import::import_file(&mut conn, event.id, &file, mode.into(), operator.as_deref(), overrides)?;
+      print(cli.json, &summary, || {
+        println!(
+          "Imported {} rows into {}: {} new guests (batch {})",
+          summary.total_rows,
+          event.name,
+          summary.inserted,
+          summary.batch_id.map(|id| id.to_string()).unwrap_or_default()
+        );
+        if let Some(synced) = &summary.synced {
+          println!("Synced: {}", sync_counts(synced));
+        }
+        if let Some(replaced) = &summary.replaced {
+          println!(
+            "Replaced {} guests and {} check-ins ({} checked in); rolling back batch {} restores them",
+            replaced.guests,
+            replaced.checkins,
+            replaced.checked_in,
+            summary.batch_id.map(|id| id.to_string()).unwrap_or_default()
+          );
+        }
+        if !summary.banned.is_empty() {
+          println!();
+          println!("On the banned list (added, but refused at the door):");
+          for matched in &summary.banned {
+            println!(
+              "  row {:>4}  {:<30} matches {}{}",
+              matched.source_row.map(|row| row.to_string()).unwrap_or_default(),
+              matched.guest_name,
+              matched.banned_name,
+              matched.reason.as_deref().map(|r| format!(" ({})", r)).unwrap_or_default()
+            );
+          }
+        }
+        if !summary.over_quota.is_empty() {
+          println!();
+          println!(
+            "Over quota ({}):",
+            if override_quota { "added anyway" } else { "skipped; pass --override-quota to add" }
+          );
+          for over in &summary.over_quota {
+            println!(
+              "  row {:>4}  {:<30} {} (quota {})",
+              over.source_row.map(|row| row.to_string()).unwrap_or_default(),
+              over.guest_name,
+              over.member_name,
+              over.quota
+            );
+          }
+        }
+      })
+    }
+    Command::Batches {
+      rollback: Some(batch_id),
+      confirm,
+      operator,
+    } => {
+      let summary = batches::rollback_batch(&mut conn, event.id, batch_id, operator.as_deref(), confirm)?;
+      print(cli.json, &summary, || {
+        println!(
+          "Rolled back batch {}: removed {} guests and {} check-ins ({} made at the door)",
+          batch_id, summary.guests_removed, summary.checkins_removed, summary.door_checkins_removed
+        );
+        if summary.later_imports_overridden > 0 {
+          println!("Overrode {} later imports", summary.later_imports_overridden);
+        }
+        if summary.guests_restored > 0 {
+          println!("Restored {} guests from the list it replaced", summary.guests_restored);
+        }
+      })
+    }
+    Command::Batches { rollback: None, .. } => {
+      let batches = batches::list_batches(&conn, event.id)?;
+      let tz = parse_timezone(&event.timezone)?;
+      print(cli.json, &batches, || {
+        for batch in &batches {
+          println!(
+            "{:>4}  {}  {:<8} {:<30} {:>4} rows  {:>4} added  {:>4} listed{}",
+            batch.id,
+            format_local_timestamp(&batch.created_at, tz),
+            batch.mode,
+            batch.file_name.as_deref().unwrap_or("-"),
+            batch.total_rows,
+            batch.inserted,
+            batch.guests,
+            if batch.rolled_back_at.is_some() { "  (rolled back)" } else { "" }
+          );
+        }
+      })
+    }
+    Command::Export { out } => {
+      if out.as_deref() == Some("-") {
+        std::io::stdout().write_all(&export::render_csv(&conn, event.id)?)?;
+        return Ok(());
+      }
+      let path = export::export_csv(&conn, event.id, out.as_deref())?;
+      print(cli.json, &path, || println!("Exported to {}", path))
+    }
+    Command::Stats => {
+      let summary = stats::stats_summary(&conn, event.id)?;
+      let tz = parse_timezone(&event.timezone)?;
+      print(cli.json, &summary, || {
+        println!("{}", event.name);
+        println!("  Guests:      {}", summary.total_guests);
+        println!("  Check-ins:   {}", summary.total_check_ins);
+        println!("  Check-outs:  {}", summary.total_check_outs);
+        println!("  Walk-ins:    {}", summary.walk_ins);
+        match summary.capacity {
+          Some(capacity) => println!(
+            "  Present:     {} / {}{}",
+            summary.currently_present,
+            capacity,
+            if summary.near_capacity { "  (near capacity)" } else { "" }
+          ),
+          None => println!("  Present:     {}", summary.currently_present),
+        }
+        if !summary.top_hosts.is_empty() {
+          println!();
+          println!("Top hosts");
+          for host in &summary.top_hosts {
+            println!(
+              "  {:<30} {} / {} present",
+              host.member_host, host.present_guests, host.total_guests
+            );
+          }
+        }
+        if !summary.present_guests.is_empty() {
+          println!();
+          println!("Currently present");
+          for guest in &summary.present_guests {
+            println!(
+              "  {:<30} {}  {}{}",
+              guest.display_name,
+              guest
+                .in_ts
+                .as_deref()
+                .map(|ts| format_local_timestamp(ts, tz))
+                .unwrap_or_default(),
+              guest.member_host.as_deref().unwrap_or(""),
+              if guest.is_walk_in { "  (walk-in)" } else { "" }
+            );
+          }
+        }
+      })
+    }
+    Command::Search { query, limit } => {
+      let results = search::search(&conn, event.id, &query, Some(limit))?;
+      print(cli.json, &results, || {
+        for hit in &results {
+          let guest = match hit {
+            SearchHit::Guest(guest) => guest,
+            SearchHit::Member(member) => {
+              println!(
+                "{:>6}  MEM  {:<30} {} present / {} total",
+                "",
+                member.member_host,
+                member.present_guests,
+                member.total_guests
+              );
+              continue;
+            }
+          };
+          println!(
+            "{:>6}  {}  {:<30} {:<24} {}",
+            guest.id,
+            if guest.is_banned {
+              "BAN"
+            } else if guest.is_checked_in {
+              "IN "
+            } else if guest.is_removed {
+              "DEL"
+            } else {
+              "   "
+            },
+            guest.display_name,
+            guest.member_host.as_deref().unwrap_or(""),
+            match guest.score {
+              Some(score) if score < 1.0 => format!("~{:.0}% match", score * 100.0),
+              _ => String::new(),
+            }
+          );
+        }
+      })
+    }
+    Command::CheckIn {
+      guest_id,
+      operator,
+      override_capacity,
+      override_quota,
+      override_ban,
+    } => {
+      let overrides = Overrides {
+        capacity: override_capacity,
+        quota: override_quota,
+        banned: override_ban,
+        ..Overrides::default()
+      };
+      let result = checkins::toggle_checkin(
+        &mut conn,
+        event.id,
+        guest_id,
+        CheckAction::In,
+        operator.as_deref(),
+        false,
+        overrides,
+      )?;
+      print(cli.json, &result, || println!("{}", toggle_message(&result.status)))
+    }
+    Command::WalkIn {
+      name,
+      host,
+      operator,
+      override_capacity,
+      override_quota,
+      override_ban,
+    } => {
+      let overrides = Overrides {
+        capacity: override_capacity,
+        quota: override_quota,
+        banned: override_ban,
+        ..Overrides::default()
+      };
+      let input = WalkInInput {
+        display_name: name,
+        member_host: host,
+      };
+      let result = guests::add_guest(&mut conn, event.id, input, operator.as_deref(), overrides)?;
+      print(cli.json, &result, || match result.guest_id {
+        Some(id) => println!("Added walk-in {} and checked them in", id),
+        None => println!("{} (walk-in not added)", toggle_message(&result.status)),
+      })
+    }
+    Command::EditGuest {
+      guest_id,
+      name,
+      host,
+      operator,
+    } => {
+      let edit = GuestEdit {
+        display_name: name,
+        member_host: host,
+      };
+      guests::edit_guest(&mut conn, event.id, guest_id, edit, operator.as_deref())?;
+      print(cli.json, &guest_id, || println!("Updated guest {}", guest_id))
+    }
+    Command::DeleteGuest { guest_id, operator } => {
+      guests::delete_guest(&mut conn, event.id, guest_id, operator.as_deref())?;
+      print(cli.json, &guest_id, || println!("Deleted guest {}", guest_id))
+    }
+    Command::MergeGuests {
+      survivor_id,
+      duplicate_id,
+      operator,
+    } => {
+      guests::merge_guests(&mut conn, event.id, survivor_id, duplicate_id, operator.as_deref())?;
+      print(cli.json, &survivor_id, || println!("Merged guest {} into {}", duplicate_id, survivor_id))
+    }
+    Command::CheckOut {
+      guest_id,
+      operator,
+      force,
+    } => {
+      let result = checkins::toggle_checkin(
+        &mut conn,
+        event.id,
+        guest_id,
+        CheckAction::Out,
+        operator.as_deref(),
+        force,
+        Overrides::default(),
+      )?;
+      print(cli.json, &result, || println!("{}", toggle_message(&result.status)))
+    }
+    Command::Undo { operator } => {
+      let result = checkins::undo_last(&mut conn, event.id, operator.as_deref())?;
+      print(cli.json, &result, || {
+        println!(
+          "{}",
+          match result.status {
+            UndoStatus::RevertedCheckIn => "Undid check-in",
+            UndoStatus::RevertedCheckOut => "Undid check-out",
+            UndoStatus::RevertedEdit => "Undid guest edit",
+            UndoStatus::RevertedDelete => "Restored deleted guest",
+            UndoStatus::RevertedMerge => "Undid guest merge",
+            UndoStatus::Empty => "Nothing to undo",
+          }
+        )
+      })
+    }
+    Command::Redo { operator } => {
+      let result = checkins::redo_last(&mut conn, event.id, operator.as_deref())?;
+      print(cli.json, &result, || {
+        println!(
+          "{}",
+          match result.status {
+            RedoStatus::ReappliedCheckIn => "Redid check-in",
+            RedoStatus::ReappliedCheckOut => "Redid check-out",
+            RedoStatus::ReappliedEdit => "Redid guest edit",
+            RedoStatus::ReappliedDelete => "Deleted guest again",
+            RedoStatus::ReappliedMerge => "Redid guest merge",
+            RedoStatus::Empty => "Nothing to redo",
+          }
+        )
+      })
+    }
+    Command::Quota {
+      member: None, ..
+    } => {
+      let quotas = quotas::list_member_quotas(&conn, event.id)?;
+      print(cli.json, &quotas, || {
+        let limit = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_else(|| "-".into());
+        println!(
+          "Event defaults: {} guests, {} present",
+          limit(event.guest_quota),
+          limit(event.present_quota)
+        );
+        for quota in &quotas {
+          println!(
+            "  {:<30} {} guests, {} present",
+            quota.member_host,
+            limit(quota.guest_quota),
+            limit(quota.present_quota)
+          );
+        }
+      })
+    }
+    Command::Quota {
+      member: Some(member),
+      guests,
+      present,
+      operator,
+    } => {
+      let quota = MemberQuota {
+        member_host: member,
+        guest_quota: guests,
+        present_quota: present,
+      };
+      quotas::set_member_quota(&conn, event.id, quota.clone(), operator.as_deref())?;
+      print(cli.json, &quota, || println!("Updated quota for {}", quota.member_host))
+    }
+  }
+}
+
+/// Picks the event named by `--event`, or the only event in the database.
+fn resolve_event(conn: &Connection, event_id: Option<i64>) -> Result<Event> {
+  if let Some(id) = event_id {
+    return Ok(events::fetch_event(conn, id)?);
+  }
+  let mut all = events::list_events(conn)?;
+  match all.len() {
+    0 => Err(anyhow!("database has no events; create one with `party-cli event create`")),
+    1 => Ok(all.remove(0)),
+    _ => Err(anyhow!(
+      "database has {} events; pass --event (see `party-cli events`)",
+      all.len()
+    )),
+  }
+}
+
+/// Lists events or creates one, so this runs without `--event`.
+fn manage_events(conn: &Connection, json: bool, action: Option<EventAction>) -> Result<()> {
+  match action {
+    None => {
+      let events = events::list_events(conn)?;
+      print(json, &events, || {
+        for event in &events {
+          println!(
+            "{:>4}  {}  {}  {}",
+            event.id,
+            event.event_date.as_deref().unwrap_or("----------"),
+            event.timezone,
+            event.name
+          );
+        }
+      })
+    }
+    Some(EventAction::Create {
+      name,
+      date,
+      venue,
+      doors_open,
+      doors_close,
+      timezone,
+      capacity,
+    }) => {
+      let input = EventInput {
+        name,
+        event_date: date,
+        venue,
+        doors_open,
+        doors_close,
+        timezone,
+        capacity,
+        ..EventInput::default()
+      };
+      let created = events::create_event(conn, input)?;
+      print(json, &created, || println!("Created event {} ({})", created.name, created.id))
+    }
+  }
+}
+
+/// The banned list is shared by every event, so this runs without `--event`.
+fn ban(
+  conn: &mut Connection,
+  json: bool,
+  name: Option<String>,
+  reason: Option<String>,
+  remove: Option<i64>,
+  operator: Option<&str>,
+) -> Result<()> {
+  if let Some(ban_id) = remove {
+    banned::remove_banned(conn, ban_id, operator)?;
+    return print(json, &ban_id, || println!("Lifted ban {}", ban_id));
+  }
+  if let Some(name) = name {
+    let added = banned::add_banned(conn, BanInput { name, reason }, operator)?;
+    return print(json, &added, || println!("Banned {} (id {})", added.name, added.id));
+  }
+  let bans = banned::list_banned(conn)?;
+  print(json, &bans, || {
+    for ban in &bans {
+      println!(
+        "{:>4}  {:<30} {}",
+        ban.id,
+        ban.name,
+        ban.reason.as_deref().unwrap_or("")
+      );
+    }
+  })
+}
+
+/// Profiles are shared by every event, so this runs without `--event`.
+fn profile(conn: &mut Connection, json: bool, action: Option<ProfileAction>) -> Result<()> {
+  match action {
+    None => {
+      let profiles = profiles::list_profiles(conn)?;
+      print(json, &profiles, || {
+        for profile in &profiles {
+          let columns = &profile.columns;
+          let fields = [
+            ("guests", &columns.guest_names),
+            ("member", &columns.member_name),
+            ("check-in", &columns.check_in),
+            ("check-in-time", &columns.check_in_time),
+            ("check-out", &columns.check_out),
+            ("check-out-time", &columns.check_out_time),
+            ("walk-in", &columns.walk_in),
+          ];
+          println!("{}", profile.name);
+          for (field, header) in fields {
+            if let Some(header) = header {
+              println!("  {:<15} {}", field, header);
+            }
+          }
+        }
+      })
+    }
+    Some(ProfileAction::Save {
+      name,
+      guests,
+      member,
+      check_in,
+      check_in_time,
+      check_out,
+      check_out_time,
+      walk_in,
+      operator,
+    }) => {
+      let input = ProfileInput {
+        name,
+        columns: ColumnMapping {
+          member_name: member,
+          guest_names: Some(guests),
+          check_in,
+          check_in_time,
+          check_out,
+          check_out_time,
+          walk_in,
+        },
+      };
+      let saved = profiles::save_profile(conn, input, operator.as_deref())?;
+      print(json, &saved, || println!("Saved profile {}", saved.name))
+    }
+    Some(ProfileAction::Delete { name, operator }) => {
+      profiles::delete_profile(conn, &name, operator.as_deref())?;
+      print(json, &name, || println!("Deleted profile {}", name))
+    }
+    Some(ProfileAction::Headers { file }) => {
+      let path = file.to_str().ok_or_else(|| anyhow!("file path must be valid UTF-8"))?;
+      let headers = import::read_headers(path)?;
+      print(json, &headers, || {
+        for header in &headers {
+          println!("{}", header);
+        }
+      })
+    }
+  }
+}
+
+fn print_preview(preview: &ImportPreview) {
+  for row in &preview.rows {
+    println!(
+      "row {:>4}  host: {}",
+      row.source_row.map(|row| row.to_string()).unwrap_or_default(),
+      row.member_host.as_deref().filter(|host| !host.is_empty()).unwrap_or("-")
+    );
+    if row.guests.is_empty() {
+      println!("    (no guest names)");
+    }
+    for guest in &row.guests {
+      let note = match (guest.outcome, &guest.duplicate_of) {
+        (GuestOutcome::Duplicate, Some(existing)) => format!(
+          "skip, already listed as {} (id {}{})",
+          existing.display_name,
+          existing.guest_id,
+          existing.source_row.map(|row| format!(", row {}", row)).unwrap_or_default()
+        ),
+        (GuestOutcome::Duplicate, None) => "skip, already listed".into(),
+        (GuestOutcome::OverQuota, _) => "skip, over member quota".into(),
+        (GuestOutcome::Unchanged, _) => "keep".into(),
+        (GuestOutcome::Updated, existing) => format!(
+          "move from {}",
+          existing.as_ref().and_then(|guest| guest.member_host.as_deref()).filter(|host| !host.is_empty()).unwrap_or("-")
+        ),
+        (GuestOutcome::Restored, _) => "add back".into(),
+        (GuestOutcome::Added, _) => match &guest.banned_name {
+          Some(name) => format!("add, matches banned {}", name),
+          None => "add".into(),
+        },
+      };
+      println!("    {:<30} {}", guest.name, note);
+    }
+    if let Some(history) = &row.history {
+      println!(
+        "    history: in {}{}",
+        history.in_ts,
+        history.out_ts.as_deref().map(|out| format!(", out {}", out)).unwrap_or_default()
+      );
+    }
+    for (column, value) in [
+      ("check-in", &row.unparsed_check_in_time),
+      ("check-out", &row.unparsed_check_out_time),
+    ] {
+      if let Some(value) = value {
+        println!("    unreadable {} time {:?}, treated as blank", column, value);
+      }
+    }
+    for guess in &row.ambiguities {
+      let what = match guess.kind {
+        AmbiguityKind::SharedSurname => "shared surname",
+        AmbiguityKind::PlusGuests => "unnamed extra guests",
+        AmbiguityKind::Note => "note left out",
+      };
+      println!("    check {:?}: {}, read as {}", guess.text, what, guess.names.join(", "));
+    }
+  }
+  let summary = &preview.summary;
+  println!();
+  println!(
+    "Dry run: {} rows would add {} guests ({} over quota, {} banned). Nothing was written.",
+    summary.total_rows,
+    summary.inserted,
+    summary.over_quota.len(),
+    summary.banned.len()
+  );
+  if let Some(synced) = &summary.synced {
+    println!("Sync: {}.", sync_counts(synced));
+  }
+  if !preview.removed.is_empty() {
+    println!("Not on the sheet, so they would be marked removed:");
+    for guest in &preview.removed {
+      println!(
+        "    {:<30} {}",
+        guest.display_name,
+        guest.member_host.as_deref().filter(|host| !host.is_empty()).unwrap_or("-")
+      );
+    }
+  }
+  if let Some(replaced) = &summary.replaced {
+    println!(
+      "It would first clear {} guests and {} check-ins{}.",
+      replaced.guests,
+      replaced.checkins,
+      match replaced.checked_in {
+        0 => String::new(),
+        inside => format!("; {} are checked in, so pass --replace-checked-in", inside),
+      }
+    );
+  }
+}
+
+fn sync_counts(synced: &SyncCounts) -> String {
+  format!(
+    "{} added, {} updated, {} removed, {} unchanged",
+    synced.added, synced.updated, synced.removed, synced.unchanged
+  )
+}
+
+fn toggle_message(status: &ToggleStatus) -> &'static str {
+  match status {
+    ToggleStatus::CheckedIn => "Checked in",
+    ToggleStatus::CheckedOut => "Checked out",
+    ToggleStatus::AlreadyIn => "Already checked in",
+    ToggleStatus::NotCheckedIn => "Not currently checked in",
+    ToggleStatus::NeverCheckedIn => "Never checked in; pass --force to record a check-out",
+    ToggleStatus::AtCapacity => "Venue is at capacity; pass --override-capacity to admit anyway",
+    ToggleStatus::OverGuestQuota => "Guest is past their member's guest quota; pass --override-quota to admit anyway",
+    ToggleStatus::Banned => "Guest is on the banned list; pass --override-ban to admit anyway",
+    ToggleStatus::MemberQuotaFull => "Member already has their quota of guests inside; pass --override-quota to admit anyway",
+    ToggleStatus::Removed => "Guest was taken off the list by a sync import",
+  }
+}
+
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce()) -> Result<()> {
+  if json {
+    println!("{}", serde_json::to_string_pretty(value)?);
+  } else {
+    text();
+  }
+  Ok(())
+}
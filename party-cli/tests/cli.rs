@@ -0,0 +1,42 @@
+use std::{fs, path::Path, process::Output};
+
+use serde_json::Value;
+
+fn party(db: &Path, args: &[&str]) -> Output {
+  std::process::Command::new(env!("CARGO_BIN_EXE_party-cli"))
+    .arg("--db")
+    .arg(db)
+    .args(args)
+    .output()
+    .expect("run party-cli")
+}
+
+fn json(output: Output) -> Value {
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+  serde_json::from_slice(&output.stdout).expect("JSON output")
+}
+
+#[test]
+fn a_new_database_takes_an_event_then_an_import() {
+  let dir = tempfile::tempdir().unwrap();
+  let db = dir.path().join("party.db");
+  let sheet = dir.path().join("guests.csv");
+  fs::write(&sheet, "Member Name,Guest Name\nJohn Smith,\"Jane Doe, Bob Roe\"\n").unwrap();
+  let sheet = sheet.to_str().unwrap();
+
+  let refused = party(&db, &["import", sheet]);
+  assert!(!refused.status.success());
+  assert!(String::from_utf8_lossy(&refused.stderr).contains("event create"));
+
+  let created = json(party(
+    &db,
+    &["--json", "event", "create", "Spring Formal", "--date", "2026-04-10", "--timezone", "America/New_York"],
+  ));
+  assert_eq!(created["name"], "Spring Formal");
+  assert_eq!(created["timezone"], "America/New_York");
+
+  let imported = json(party(&db, &["--json", "import", sheet]));
+  assert_eq!(imported["inserted"], 2);
+  let events = json(party(&db, &["--json", "events"]));
+  assert_eq!(events.as_array().unwrap().len(), 1);
+}
@@ -1,5 +1,3 @@
-BEGIN;
-
 CREATE TABLE IF NOT EXISTS guests (
   id INTEGER PRIMARY KEY,
   display_name TEXT NOT NULL,
@@ -40,5 +38,3 @@ CREATE TRIGGER IF NOT EXISTS guests_au AFTER UPDATE ON guests BEGIN
   INSERT INTO guest_fts(rowid, display_name, member_host)
   VALUES (new.id, new.display_name, new.member_host);
 END;
-
-COMMIT;
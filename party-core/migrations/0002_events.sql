@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS events (
+  id INTEGER PRIMARY KEY,
+  name TEXT NOT NULL,
+  event_date TEXT,
+  venue TEXT,
+  doors_open TEXT,
+  doors_close TEXT,
+  timezone TEXT NOT NULL DEFAULT 'America/Chicago',
+  created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS guests_event_idx ON guests(event_id);
+CREATE INDEX IF NOT EXISTS checkins_event_guest_idx ON checkins(event_id, guest_id);
@@ -0,0 +1,17 @@
+CREATE TABLE undo_history (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  action TEXT NOT NULL,
+  checkin_id INTEGER NOT NULL,
+  guest_id INTEGER NOT NULL REFERENCES guests(id) ON DELETE CASCADE,
+  in_ts TEXT NOT NULL,
+  out_ts TEXT,
+  in_by TEXT,
+  out_by TEXT,
+  operator TEXT,
+  created_at TEXT NOT NULL,
+  undone_at TEXT,
+  undone_by TEXT
+);
+
+CREATE INDEX undo_history_event_idx ON undo_history(event_id, id);
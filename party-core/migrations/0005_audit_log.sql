@@ -0,0 +1,27 @@
+-- Rows outlive the guests and events they mention, so there are no foreign
+-- keys here; names are copied in at write time.
+CREATE TABLE audit_log (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER,
+  guest_id INTEGER,
+  guest_name TEXT,
+  action TEXT NOT NULL,
+  operator TEXT,
+  created_at TEXT NOT NULL,
+  before_state TEXT,
+  after_state TEXT
+);
+
+CREATE INDEX audit_log_event_idx ON audit_log(event_id, created_at);
+CREATE INDEX audit_log_guest_idx ON audit_log(guest_id, created_at);
+CREATE INDEX audit_log_operator_idx ON audit_log(operator, created_at);
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+  SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
+
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+  SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
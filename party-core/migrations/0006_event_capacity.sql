@@ -0,0 +1,3 @@
+-- NULL capacity means no limit.
+ALTER TABLE events ADD COLUMN capacity INTEGER;
+ALTER TABLE events ADD COLUMN capacity_warning_percent INTEGER NOT NULL DEFAULT 90;
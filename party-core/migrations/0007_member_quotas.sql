@@ -0,0 +1,13 @@
+-- Event-wide defaults; NULL means no limit.
+ALTER TABLE events ADD COLUMN guest_quota INTEGER;
+ALTER TABLE events ADD COLUMN present_quota INTEGER;
+
+-- Per-member exceptions to the event defaults. A NULL column falls back to
+-- the event's value.
+CREATE TABLE IF NOT EXISTS member_quotas (
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  member_host TEXT NOT NULL COLLATE NOCASE,
+  guest_quota INTEGER,
+  present_quota INTEGER,
+  PRIMARY KEY (event_id, member_host)
+);
@@ -0,0 +1,14 @@
+-- People who may not be admitted to any event. `name_key` is the normalized
+-- form used for matching.
+CREATE TABLE IF NOT EXISTS banned_guests (
+  id INTEGER PRIMARY KEY,
+  name TEXT NOT NULL,
+  name_key TEXT NOT NULL,
+  reason TEXT,
+  added_by TEXT,
+  created_at TEXT NOT NULL
+);
+
+-- The ban a guest matched, if any.
+ALTER TABLE guests ADD COLUMN banned_id INTEGER REFERENCES banned_guests(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS guests_banned_idx ON guests(banned_id);
@@ -0,0 +1,24 @@
+-- Sound-alike keys for guest and member names, one FTS row per guest (rowid =
+-- guests.id). `sound_key` is registered by the app on every connection.
+CREATE VIRTUAL TABLE IF NOT EXISTS guest_sounds USING fts5(
+  name_sound,
+  member_sound
+);
+
+INSERT INTO guest_sounds(rowid, name_sound, member_sound)
+SELECT id, sound_key(display_name), sound_key(member_host) FROM guests;
+
+CREATE TRIGGER IF NOT EXISTS guests_sounds_ai AFTER INSERT ON guests BEGIN
+  INSERT INTO guest_sounds(rowid, name_sound, member_sound)
+  VALUES (new.id, sound_key(new.display_name), sound_key(new.member_host));
+END;
+
+CREATE TRIGGER IF NOT EXISTS guests_sounds_ad AFTER DELETE ON guests BEGIN
+  DELETE FROM guest_sounds WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS guests_sounds_au AFTER UPDATE OF display_name, member_host ON guests BEGIN
+  UPDATE guest_sounds
+  SET name_sound = sound_key(new.display_name), member_sound = sound_key(new.member_host)
+  WHERE rowid = new.id;
+END;
@@ -0,0 +1,32 @@
+-- Rebuild the name index from folded text (`index_text`, registered by
+-- `db::open`) so accents, apostrophes and hyphens match the same way in the
+-- index as in a search. The index now stores its own text instead of reading
+-- `guests`, since the folded form is not a column there.
+DROP TRIGGER IF EXISTS guests_ai;
+DROP TRIGGER IF EXISTS guests_ad;
+DROP TRIGGER IF EXISTS guests_au;
+DROP TABLE IF EXISTS guest_fts;
+
+CREATE VIRTUAL TABLE guest_fts USING fts5(
+  display_name,
+  member_host,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO guest_fts(rowid, display_name, member_host)
+SELECT id, index_text(display_name), index_text(member_host) FROM guests;
+
+CREATE TRIGGER guests_ai AFTER INSERT ON guests BEGIN
+  INSERT INTO guest_fts(rowid, display_name, member_host)
+  VALUES (new.id, index_text(new.display_name), index_text(new.member_host));
+END;
+
+CREATE TRIGGER guests_ad AFTER DELETE ON guests BEGIN
+  DELETE FROM guest_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER guests_au AFTER UPDATE OF display_name, member_host ON guests BEGIN
+  UPDATE guest_fts
+  SET display_name = index_text(new.display_name), member_host = index_text(new.member_host)
+  WHERE rowid = new.id;
+END;
@@ -0,0 +1,60 @@
+-- One row per member (guest host) per event, kept in step with `guests` by
+-- triggers, so member search reads an index instead of grouping every guest.
+-- Names match without regard to case; the first spelling seen is kept.
+CREATE TABLE IF NOT EXISTS members (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  member_host TEXT NOT NULL COLLATE NOCASE,
+  UNIQUE (event_id, member_host)
+);
+
+CREATE INDEX IF NOT EXISTS guests_member_idx ON guests(event_id, member_host COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS checkins_open_idx ON checkins(guest_id) WHERE out_ts IS NULL;
+
+-- Folded member names and their sound keys (rowid = members.id).
+CREATE VIRTUAL TABLE IF NOT EXISTS member_fts USING fts5(
+  member_host,
+  member_sound,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS members_ai AFTER INSERT ON members BEGIN
+  INSERT INTO member_fts(rowid, member_host, member_sound)
+  VALUES (new.id, index_text(new.member_host), sound_key(new.member_host));
+END;
+
+CREATE TRIGGER IF NOT EXISTS members_ad AFTER DELETE ON members BEGIN
+  DELETE FROM member_fts WHERE rowid = old.id;
+END;
+
+INSERT OR IGNORE INTO members(event_id, member_host)
+SELECT event_id, member_host FROM guests
+WHERE event_id IS NOT NULL AND member_host IS NOT NULL AND member_host != ''
+ORDER BY id;
+
+CREATE TRIGGER IF NOT EXISTS guests_members_ai AFTER INSERT ON guests
+WHEN new.member_host IS NOT NULL AND new.member_host != '' BEGIN
+  INSERT OR IGNORE INTO members(event_id, member_host) VALUES (new.event_id, new.member_host);
+END;
+
+CREATE TRIGGER IF NOT EXISTS guests_members_ad AFTER DELETE ON guests
+WHEN old.member_host IS NOT NULL BEGIN
+  DELETE FROM members
+  WHERE event_id = old.event_id AND member_host = old.member_host
+    AND NOT EXISTS (
+      SELECT 1 FROM guests g
+      WHERE g.event_id = old.event_id AND g.member_host = old.member_host COLLATE NOCASE
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS guests_members_au AFTER UPDATE OF event_id, member_host ON guests BEGIN
+  DELETE FROM members
+  WHERE event_id = old.event_id AND member_host = old.member_host
+    AND NOT EXISTS (
+      SELECT 1 FROM guests g
+      WHERE g.event_id = old.event_id AND g.member_host = old.member_host COLLATE NOCASE
+    );
+  INSERT OR IGNORE INTO members(event_id, member_host)
+  SELECT new.event_id, new.member_host
+  WHERE new.member_host IS NOT NULL AND new.member_host != '';
+END;
@@ -0,0 +1,3 @@
+-- Guests added at the door rather than from a guest list, and who added them.
+ALTER TABLE guests ADD COLUMN is_walk_in INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE guests ADD COLUMN added_by TEXT;
@@ -0,0 +1,33 @@
+-- Guest edits, deletions and merges are undoable too. Their history rows keep
+-- JSON snapshots of the guests instead of a check-in, and have to outlive a
+-- deleted guest, so `guest_id` no longer cascades and the name is copied in.
+CREATE TABLE undo_history_new (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  action TEXT NOT NULL,
+  checkin_id INTEGER,
+  guest_id INTEGER NOT NULL,
+  guest_name TEXT,
+  in_ts TEXT,
+  out_ts TEXT,
+  in_by TEXT,
+  out_by TEXT,
+  before_state TEXT,
+  after_state TEXT,
+  operator TEXT,
+  created_at TEXT NOT NULL,
+  undone_at TEXT,
+  undone_by TEXT
+);
+
+INSERT INTO undo_history_new
+  (id, event_id, action, checkin_id, guest_id, guest_name, in_ts, out_ts, in_by, out_by,
+   operator, created_at, undone_at, undone_by)
+SELECT h.id, h.event_id, h.action, h.checkin_id, h.guest_id, g.display_name, h.in_ts, h.out_ts,
+  h.in_by, h.out_by, h.operator, h.created_at, h.undone_at, h.undone_by
+FROM undo_history h
+LEFT JOIN guests g ON g.id = h.guest_id;
+
+DROP TABLE undo_history;
+ALTER TABLE undo_history_new RENAME TO undo_history;
+CREATE INDEX undo_history_event_idx ON undo_history(event_id, id);
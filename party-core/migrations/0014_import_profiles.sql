@@ -0,0 +1,10 @@
+-- Named column mappings for importing sheets laid out differently from the
+-- app's own export (a Google Form export, the chapter spreadsheet, ...).
+-- `columns` is a JSON object from import field to the sheet's header.
+CREATE TABLE IF NOT EXISTS import_profiles (
+  id INTEGER PRIMARY KEY,
+  name TEXT NOT NULL COLLATE NOCASE UNIQUE,
+  columns TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
@@ -0,0 +1,23 @@
+-- One row per import, so the guests and check-ins it added can be traced back
+-- to the file they came from and rolled back together.
+CREATE TABLE IF NOT EXISTS import_batches (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  file_name TEXT,
+  file_hash TEXT,
+  mode TEXT NOT NULL,
+  operator TEXT,
+  created_at TEXT NOT NULL,
+  total_rows INTEGER NOT NULL DEFAULT 0,
+  inserted INTEGER NOT NULL DEFAULT 0,
+  rolled_back_at TEXT,
+  rolled_back_by TEXT
+);
+CREATE INDEX IF NOT EXISTS import_batches_event_idx ON import_batches(event_id, id);
+
+-- The import that added the row; NULL for walk-ins, door check-ins and rows
+-- imported before batches were kept.
+ALTER TABLE guests ADD COLUMN batch_id INTEGER REFERENCES import_batches(id) ON DELETE SET NULL;
+ALTER TABLE checkins ADD COLUMN batch_id INTEGER REFERENCES import_batches(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS guests_batch_idx ON guests(batch_id);
+CREATE INDEX IF NOT EXISTS checkins_batch_idx ON checkins(batch_id);
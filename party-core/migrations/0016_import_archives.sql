@@ -0,0 +1,12 @@
+-- The guest list a Replace import removed, with its check-ins, as a
+-- snapshot. Rolling the import back puts it back.
+CREATE TABLE IF NOT EXISTS import_archives (
+  id INTEGER PRIMARY KEY,
+  event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+  batch_id INTEGER REFERENCES import_batches(id) ON DELETE SET NULL,
+  created_at TEXT NOT NULL,
+  guests INTEGER NOT NULL,
+  checkins INTEGER NOT NULL,
+  snapshot TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS import_archives_batch_idx ON import_archives(batch_id);
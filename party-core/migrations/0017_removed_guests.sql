@@ -0,0 +1,3 @@
+-- When a Sync import found the guest missing from the sheet. Removed guests
+-- keep their check-ins but no longer count as on the list.
+ALTER TABLE guests ADD COLUMN removed_at TEXT;
@@ -0,0 +1,130 @@
+-- Undo puts deleted guests and check-ins back under their old ids, so an id
+-- must never be handed to a new row. Without AUTOINCREMENT SQLite reuses the
+-- highest id once it is deleted. Both tables are rebuilt with foreign keys
+-- off (see `migrate`), so dropping `guests` does not cascade to `checkins`.
+CREATE TABLE guests_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  event_id INTEGER REFERENCES events(id) ON DELETE CASCADE,
+  display_name TEXT NOT NULL,
+  member_host TEXT,
+  source_row INTEGER,
+  created_at TEXT DEFAULT (datetime('now')),
+  banned_id INTEGER REFERENCES banned_guests(id) ON DELETE SET NULL,
+  is_walk_in INTEGER NOT NULL DEFAULT 0,
+  added_by TEXT,
+  batch_id INTEGER REFERENCES import_batches(id) ON DELETE SET NULL,
+  removed_at TEXT
+);
+
+INSERT INTO guests_new
+  (id, event_id, display_name, member_host, source_row, created_at, banned_id, is_walk_in, added_by,
+   batch_id, removed_at)
+SELECT id, event_id, display_name, member_host, source_row, created_at, banned_id, is_walk_in, added_by,
+  batch_id, removed_at
+FROM guests;
+
+DROP TABLE guests;
+ALTER TABLE guests_new RENAME TO guests;
+
+CREATE INDEX guests_event_idx ON guests(event_id);
+CREATE INDEX guests_banned_idx ON guests(banned_id);
+CREATE INDEX guests_member_idx ON guests(event_id, member_host COLLATE NOCASE);
+CREATE INDEX guests_batch_idx ON guests(batch_id);
+
+-- The search and member triggers went with the old table; these match
+-- 0009, 0010 and 0011. The indexes they maintain are keyed on guest id and
+-- are still current.
+CREATE TRIGGER guests_ai AFTER INSERT ON guests BEGIN
+  INSERT INTO guest_fts(rowid, display_name, member_host)
+  VALUES (new.id, index_text(new.display_name), index_text(new.member_host));
+END;
+
+CREATE TRIGGER guests_ad AFTER DELETE ON guests BEGIN
+  DELETE FROM guest_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER guests_au AFTER UPDATE OF display_name, member_host ON guests BEGIN
+  UPDATE guest_fts
+  SET display_name = index_text(new.display_name), member_host = index_text(new.member_host)
+  WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER guests_sounds_ai AFTER INSERT ON guests BEGIN
+  INSERT INTO guest_sounds(rowid, name_sound, member_sound)
+  VALUES (new.id, sound_key(new.display_name), sound_key(new.member_host));
+END;
+
+CREATE TRIGGER guests_sounds_ad AFTER DELETE ON guests BEGIN
+  DELETE FROM guest_sounds WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER guests_sounds_au AFTER UPDATE OF display_name, member_host ON guests BEGIN
+  UPDATE guest_sounds
+  SET name_sound = sound_key(new.display_name), member_sound = sound_key(new.member_host)
+  WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER guests_members_ai AFTER INSERT ON guests
+WHEN new.member_host IS NOT NULL AND new.member_host != '' BEGIN
+  INSERT OR IGNORE INTO members(event_id, member_host) VALUES (new.event_id, new.member_host);
+END;
+
+CREATE TRIGGER guests_members_ad AFTER DELETE ON guests
+WHEN old.member_host IS NOT NULL BEGIN
+  DELETE FROM members
+  WHERE event_id = old.event_id AND member_host = old.member_host
+    AND NOT EXISTS (
+      SELECT 1 FROM guests g
+      WHERE g.event_id = old.event_id AND g.member_host = old.member_host COLLATE NOCASE
+    );
+END;
+
+CREATE TRIGGER guests_members_au AFTER UPDATE OF event_id, member_host ON guests BEGIN
+  DELETE FROM members
+  WHERE event_id = old.event_id AND member_host = old.member_host
+    AND NOT EXISTS (
+      SELECT 1 FROM guests g
+      WHERE g.event_id = old.event_id AND g.member_host = old.member_host COLLATE NOCASE
+    );
+  INSERT OR IGNORE INTO members(event_id, member_host)
+  SELECT new.event_id, new.member_host
+  WHERE new.member_host IS NOT NULL AND new.member_host != '';
+END;
+
+CREATE TABLE checkins_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  event_id INTEGER REFERENCES events(id) ON DELETE CASCADE,
+  guest_id INTEGER NOT NULL REFERENCES guests(id) ON DELETE CASCADE,
+  in_ts TEXT NOT NULL,
+  out_ts TEXT,
+  in_by TEXT,
+  out_by TEXT,
+  batch_id INTEGER REFERENCES import_batches(id) ON DELETE SET NULL
+);
+
+INSERT INTO checkins_new (id, event_id, guest_id, in_ts, out_ts, in_by, out_by, batch_id)
+SELECT id, event_id, guest_id, in_ts, out_ts, in_by, out_by, batch_id FROM checkins;
+
+DROP TABLE checkins;
+ALTER TABLE checkins_new RENAME TO checkins;
+
+CREATE INDEX checkins_event_guest_idx ON checkins(event_id, guest_id);
+CREATE INDEX checkins_open_idx ON checkins(guest_id) WHERE out_ts IS NULL;
+CREATE INDEX checkins_batch_idx ON checkins(batch_id);
+
+-- Ids already freed may still be named in undo history; start both sequences
+-- past them.
+DELETE FROM sqlite_sequence WHERE name IN ('guests', 'checkins');
+INSERT INTO sqlite_sequence (name, seq)
+SELECT 'guests', MAX(
+  (SELECT COALESCE(MAX(id), 0) FROM guests),
+  (SELECT COALESCE(MAX(guest_id), 0) FROM undo_history),
+  (SELECT COALESCE(MAX(j.value), 0) FROM undo_history h, json_each(h.before_state, '$.guestIds') j)
+);
+INSERT INTO sqlite_sequence (name, seq)
+SELECT 'checkins', MAX(
+  (SELECT COALESCE(MAX(id), 0) FROM checkins),
+  (SELECT COALESCE(MAX(checkin_id), 0) FROM undo_history),
+  (SELECT COALESCE(MAX(j.value ->> 'id'), 0) FROM undo_history h, json_each(h.before_state, '$.checkins') j),
+  (SELECT COALESCE(MAX(j.value ->> 'id'), 0) FROM undo_history h, json_each(h.after_state, '$.checkins') j)
+);
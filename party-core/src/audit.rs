@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::names::clean_optional;
+use crate::time::{now_timestamp, to_timestamp};
+
+/// One `audit_log` row. States are JSON snapshots of what the action touched,
+/// `None` when there was nothing there. Actions that are not tied to one event
+/// (such as editing the banned list) leave `event_id` empty.
+pub(crate) struct AuditRecord<'a> {
+  pub(crate) event_id: Option<i64>,
+  pub(crate) guest_id: Option<i64>,
+  pub(crate) action: &'a str,
+  pub(crate) operator: Option<&'a str>,
+  pub(crate) before: Option<String>,
+  pub(crate) after: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFilter {
+  pub event_id: Option<i64>,
+  pub guest_id: Option<i64>,
+  pub guest_name: Option<String>,
+  pub operator: Option<String>,
+  pub since: Option<String>,
+  pub until: Option<String>,
+  pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+  pub id: i64,
+  pub event_id: Option<i64>,
+  pub guest_id: Option<i64>,
+  pub guest_name: Option<String>,
+  pub action: String,
+  pub operator: Option<String>,
+  pub created_at: String,
+  pub before_state: Option<serde_json::Value>,
+  pub after_state: Option<serde_json::Value>,
+}
+
+pub fn query_audit_log(conn: &Connection, filter: AuditFilter) -> Result<Vec<AuditEntry>> {
+  let guest_name = clean_optional(filter.guest_name);
+  let operator = clean_optional(filter.operator);
+  let since = parse_audit_bound(filter.since)?;
+  let until = parse_audit_bound(filter.until)?;
+  let limit = filter.limit.unwrap_or(200).min(2000) as i64;
+
+  let mut stmt = conn.prepare_cached(
+    "SELECT id, event_id, guest_id, guest_name, action, operator, created_at, before_state, after_state
+     FROM audit_log
+     WHERE (?1 IS NULL OR event_id = ?1)
+       AND (?2 IS NULL OR guest_id = ?2)
+       AND (?3 IS NULL OR instr(lower(guest_name), lower(?3)) > 0)
+       AND (?4 IS NULL OR lower(operator) = lower(?4))
+       AND (?5 IS NULL OR created_at >= ?5)
+       AND (?6 IS NULL OR created_at <= ?6)
+     ORDER BY id DESC
+     LIMIT ?7",
+  )?;
+  let mut rows = stmt.query(params![
+    filter.event_id,
+    filter.guest_id,
+    guest_name,
+    operator,
+    since,
+    until,
+    limit
+  ])?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    let before: Option<String> = row.get(7)?;
+    let after: Option<String> = row.get(8)?;
+    results.push(AuditEntry {
+      id: row.get(0)?,
+      event_id: row.get(1)?,
+      guest_id: row.get(2)?,
+      guest_name: row.get(3)?,
+      action: row.get(4)?,
+      operator: row.get(5)?,
+      created_at: row.get(6)?,
+      before_state: before.map(|value| serde_json::from_str(&value)).transpose()?,
+      after_state: after.map(|value| serde_json::from_str(&value)).transpose()?,
+    });
+  }
+
+  Ok(results)
+}
+
+/// Appends to the audit log. The guest's name is copied in so the entry still
+/// reads correctly after the guest is deleted or re-imported.
+pub(crate) fn record_audit(conn: &Connection, record: AuditRecord<'_>) -> Result<()> {
+  conn.execute(
+    "INSERT INTO audit_log (event_id, guest_id, guest_name, action, operator, created_at, before_state, after_state)
+     VALUES (?1, ?2, (SELECT display_name FROM guests WHERE id = ?2), ?3, ?4, ?5, ?6, ?7)",
+    params![
+      record.event_id,
+      record.guest_id,
+      record.action,
+      record.operator,
+      now_timestamp(),
+      record.before,
+      record.after
+    ],
+  )?;
+  Ok(())
+}
+
+pub(crate) fn checkin_snapshot(conn: &Connection, checkin_id: i64) -> Result<Option<String>> {
+  let snapshot = conn
+    .query_row(
+      "SELECT json_object('checkinId', id, 'inTs', in_ts, 'outTs', out_ts, 'inBy', in_by, 'outBy', out_by)
+       FROM checkins WHERE id = ?1",
+      params![checkin_id],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(snapshot)
+}
+
+pub(crate) fn event_counts(conn: &Connection, event_id: i64) -> Result<(i64, i64)> {
+  let counts = conn.query_row(
+    "SELECT (SELECT COUNT(*) FROM guests WHERE event_id = ?1),
+            (SELECT COUNT(*) FROM checkins WHERE event_id = ?1)",
+    params![event_id],
+    |row| Ok((row.get(0)?, row.get(1)?)),
+  )?;
+  Ok(counts)
+}
+
+fn parse_audit_bound(value: Option<String>) -> Result<Option<String>> {
+  let Some(value) = clean_optional(value) else {
+    return Ok(None);
+  };
+  let parsed = DateTime::parse_from_rfc3339(&value)
+    .map_err(|_| Error::Invalid(format!("invalid timestamp {}; expected RFC 3339", value)))?;
+  Ok(Some(to_timestamp(parsed.with_timezone(&Utc))))
+}
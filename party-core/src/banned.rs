@@ -0,0 +1,186 @@
+//! The do-not-admit list. Bans apply across events: guests are matched against
+//! it by name when they are imported and whenever the list changes.
+
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{record_audit, AuditRecord};
+use crate::error::{Error, Result};
+use crate::names::{clean_optional, clean_whitespace, edit_distance, name_key};
+use crate::time::now_timestamp;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedGuest {
+  pub id: i64,
+  pub name: String,
+  pub reason: Option<String>,
+  pub added_by: Option<String>,
+  pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BanInput {
+  pub name: String,
+  pub reason: Option<String>,
+}
+
+/// A ban as loaded for matching.
+#[derive(Debug)]
+pub(crate) struct Ban {
+  pub(crate) id: i64,
+  pub(crate) name: String,
+  pub(crate) reason: Option<String>,
+  key: String,
+}
+
+pub fn list_banned(conn: &Connection) -> Result<Vec<BannedGuest>> {
+  let mut stmt = conn.prepare_cached(
+    "SELECT id, name, reason, added_by, created_at FROM banned_guests ORDER BY name COLLATE NOCASE",
+  )?;
+  let rows = stmt.query_map([], map_banned_row)?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Adds a name to the banned list and flags every guest, in any event, whose
+/// name matches it.
+pub fn add_banned(conn: &mut Connection, input: BanInput, operator: Option<&str>) -> Result<BannedGuest> {
+  let name = clean_whitespace(&input.name);
+  let key = name_key(&name);
+  if key.is_empty() {
+    return Err(Error::Invalid("banned name is required".into()));
+  }
+
+  let tx = conn.transaction()?;
+  tx.execute(
+    "INSERT INTO banned_guests (name, name_key, reason, added_by, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+    params![name, key, clean_optional(input.reason), operator, now_timestamp()],
+  )?;
+  let banned = fetch_banned(&tx, tx.last_insert_rowid())?;
+  let flagged = rematch_guests(&tx)?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: None,
+      guest_id: None,
+      action: "ban_added",
+      operator,
+      before: None,
+      after: Some(serde_json::json!({ "ban": banned, "flaggedGuests": flagged }).to_string()),
+    },
+  )?;
+  tx.commit()?;
+
+  Ok(banned)
+}
+
+/// Lifts a ban. Guests it flagged are cleared unless another ban matches them.
+pub fn remove_banned(conn: &mut Connection, ban_id: i64, operator: Option<&str>) -> Result<()> {
+  let tx = conn.transaction()?;
+  let banned = fetch_banned(&tx, ban_id)?;
+  tx.execute("DELETE FROM banned_guests WHERE id = ?1", params![ban_id])?;
+  rematch_guests(&tx)?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: None,
+      guest_id: None,
+      action: "ban_removed",
+      operator,
+      before: Some(serde_json::to_string(&banned)?),
+      after: None,
+    },
+  )?;
+  tx.commit()?;
+  Ok(())
+}
+
+pub(crate) fn load_bans(conn: &Connection) -> Result<Vec<Ban>> {
+  let mut stmt = conn.prepare_cached("SELECT id, name, reason, name_key FROM banned_guests ORDER BY id")?;
+  let rows = stmt.query_map([], |row| {
+    Ok(Ban {
+      id: row.get(0)?,
+      name: row.get(1)?,
+      reason: row.get(2)?,
+      key: row.get(3)?,
+    })
+  })?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// The first ban whose name plausibly matches `name`.
+pub(crate) fn find_ban<'a>(bans: &'a [Ban], name: &str) -> Option<&'a Ban> {
+  let key = name_key(name);
+  bans.iter().find(|ban| keys_match(&ban.key, &key))
+}
+
+/// Whether two name keys plausibly belong to the same person: identical, the
+/// same words in another order ("doe jane"), or a typo or two apart. Short
+/// names must match exactly, since one letter separates too many of them.
+fn keys_match(a: &str, b: &str) -> bool {
+  if a.is_empty() || b.is_empty() {
+    return false;
+  }
+  if a == b {
+    return true;
+  }
+
+  let mut words_a: Vec<&str> = a.split(' ').collect();
+  let mut words_b: Vec<&str> = b.split(' ').collect();
+  words_a.sort_unstable();
+  words_b.sort_unstable();
+  if words_a == words_b {
+    return true;
+  }
+
+  let allowed = match a.chars().count().max(b.chars().count()) {
+    0..=5 => 0,
+    6..=11 => 1,
+    _ => 2,
+  };
+  edit_distance(a, b) <= allowed
+}
+
+/// Flags unflagged guests that match a ban. Returns how many were flagged.
+fn rematch_guests(conn: &Connection) -> Result<usize> {
+  let bans = load_bans(conn)?;
+  if bans.is_empty() {
+    return Ok(0);
+  }
+
+  let guests: Vec<(i64, String)> = {
+    let mut stmt = conn.prepare_cached("SELECT id, display_name FROM guests WHERE banned_id IS NULL")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect::<rusqlite::Result<_>>()?
+  };
+  let mut flagged = 0;
+  let mut update = conn.prepare_cached("UPDATE guests SET banned_id = ?1 WHERE id = ?2")?;
+  for (guest_id, name) in guests {
+    if let Some(ban) = find_ban(&bans, &name) {
+      update.execute(params![ban.id, guest_id])?;
+      flagged += 1;
+    }
+  }
+  Ok(flagged)
+}
+
+fn fetch_banned(conn: &Connection, ban_id: i64) -> Result<BannedGuest> {
+  conn
+    .prepare_cached("SELECT id, name, reason, added_by, created_at FROM banned_guests WHERE id = ?1")?
+    .query_row(params![ban_id], map_banned_row)
+    .map_err(|err| match err {
+      rusqlite::Error::QueryReturnedNoRows => Error::BanNotFound(ban_id),
+      other => other.into(),
+    })
+}
+
+fn map_banned_row(row: &Row<'_>) -> rusqlite::Result<BannedGuest> {
+  Ok(BannedGuest {
+    id: row.get(0)?,
+    name: row.get(1)?,
+    reason: row.get(2)?,
+    added_by: row.get(3)?,
+    created_at: row.get(4)?,
+  })
+}
@@ -0,0 +1,330 @@
+//! Where imported guests came from. Each import is recorded as a batch, and
+//! the guests and check-ins it added point back at it, so one bad file can be
+//! rolled back without touching the rest of the list. A Replace import first
+//! archives the list it clears, and a Sync the member and removal of each guest
+//! it changes; rolling them back restores those.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::audit::{record_audit, AuditRecord};
+use crate::error::{Error, Result};
+use crate::events::fetch_event;
+use crate::guests::{copy_guests, guests_snapshot};
+use crate::import::ImportMode;
+use crate::time::now_timestamp;
+
+/// The file an import was read from.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSource {
+  pub file_name: Option<String>,
+  /// SHA-256 of the file's bytes, in hex.
+  pub file_hash: Option<String>,
+}
+
+impl ImportSource {
+  pub fn from_file(path: &str, bytes: &[u8]) -> Self {
+    let file_name = std::path::Path::new(path)
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned());
+    let file_hash = Sha256::digest(bytes)
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect();
+    ImportSource {
+      file_name,
+      file_hash: Some(file_hash),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatch {
+  pub id: i64,
+  pub event_id: i64,
+  pub file_name: Option<String>,
+  pub file_hash: Option<String>,
+  pub mode: String,
+  pub operator: Option<String>,
+  pub created_at: String,
+  pub total_rows: i64,
+  pub inserted: i64,
+  /// Guests from the batch still on the list.
+  pub guests: i64,
+  /// Check-ins of those guests made or closed at the door rather than
+  /// imported. Rolling the batch back drops them.
+  pub door_checkins: i64,
+  /// Replace and Sync imports since this one that are still in place; rolling
+  /// this batch back undoes what they changed.
+  pub later_imports: i64,
+  pub rolled_back_at: Option<String>,
+  pub rolled_back_by: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackSummary {
+  pub guests_removed: usize,
+  /// Check-ins removed with those guests, including any made at the door.
+  pub checkins_removed: usize,
+  /// Of those, the ones made or closed at the door.
+  pub door_checkins_removed: usize,
+  /// Later Replace and Sync imports whose changes were overridden.
+  pub later_imports_overridden: usize,
+  /// Guests put back from the list a Replace batch archived, or returned to
+  /// their member and un-removed after a Sync.
+  pub guests_restored: usize,
+}
+
+/// The list a Replace import cleared, saved before it was removed. A Sync
+/// keeps a [`SyncArchive`] instead.
+#[derive(Debug)]
+pub(crate) struct Archive {
+  pub id: i64,
+  pub guests: usize,
+  pub checkins: usize,
+  /// Guests with an open visit when the list was archived.
+  pub checked_in: usize,
+}
+
+/// Every import into the event, newest first.
+pub fn list_batches(conn: &Connection, event_id: i64) -> Result<Vec<ImportBatch>> {
+  fetch_event(conn, event_id)?;
+  let mut stmt = conn.prepare_cached(&format!("{} WHERE b.event_id = ?1 ORDER BY b.id DESC", BATCH_SELECT))?;
+  let rows = stmt.query_map(params![event_id], map_batch_row)?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Removes the guests a batch added, with their check-ins, in one
+/// transaction. Door actions on those guests leave the undo history too, since
+/// there is nothing left for them to act on. Rolling back a Replace batch then
+/// adds the archived list back, under new guest ids; rolling back a Sync puts
+/// the guests it moved or removed back as they were.
+///
+/// Refuses with [`Error::RollbackLosesChanges`] when that would drop check-ins
+/// made at the door or override a later Replace or Sync, unless `confirm`.
+pub fn rollback_batch(
+  conn: &mut Connection,
+  event_id: i64,
+  batch_id: i64,
+  operator: Option<&str>,
+  confirm: bool,
+) -> Result<RollbackSummary> {
+  let tx = conn.transaction()?;
+  let batch = fetch_batch(&tx, event_id, batch_id)?;
+  if batch.rolled_back_at.is_some() {
+    return Err(Error::Invalid(format!("import batch {} was already rolled back", batch_id)));
+  }
+  if !confirm && (batch.door_checkins > 0 || batch.later_imports > 0) {
+    return Err(Error::RollbackLosesChanges {
+      door_checkins: batch.door_checkins,
+      later_imports: batch.later_imports,
+    });
+  }
+
+  let checkins_removed = tx.query_row(
+    "SELECT COUNT(*) FROM checkins WHERE guest_id IN (SELECT id FROM guests WHERE batch_id = ?1)",
+    params![batch_id],
+    |row| row.get::<_, i64>(0),
+  )? as usize;
+  tx.execute(
+    "DELETE FROM undo_history WHERE guest_id IN (SELECT id FROM guests WHERE batch_id = ?1)",
+    params![batch_id],
+  )?;
+  let guests_removed = tx.execute("DELETE FROM guests WHERE batch_id = ?1", params![batch_id])?;
+  let archived: Option<String> = tx
+    .query_row(
+      "SELECT snapshot FROM import_archives WHERE batch_id = ?1",
+      params![batch_id],
+      |row| row.get(0),
+    )
+    .optional()?;
+  let guests_restored = match archived {
+    Some(snapshot) if batch.mode == ImportMode::Sync.as_str() => unsync_guests(&tx, &snapshot)?,
+    Some(snapshot) => copy_guests(&tx, &snapshot)?,
+    None => 0,
+  };
+  tx.execute(
+    "UPDATE import_batches SET rolled_back_at = ?1, rolled_back_by = ?2 WHERE id = ?3",
+    params![now_timestamp(), operator, batch_id],
+  )?;
+
+  let summary = RollbackSummary {
+    guests_removed,
+    checkins_removed,
+    door_checkins_removed: batch.door_checkins as usize,
+    later_imports_overridden: batch.later_imports as usize,
+    guests_restored,
+  };
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: None,
+      action: "import_rollback",
+      operator,
+      before: Some(serde_json::to_string(&batch)?),
+      after: Some(serde_json::to_string(&summary)?),
+    },
+  )?;
+  tx.commit()?;
+
+  Ok(summary)
+}
+
+/// Opens a batch for an import that is about to run.
+pub(crate) fn start_batch(
+  conn: &Connection,
+  event_id: i64,
+  source: &ImportSource,
+  mode: ImportMode,
+  operator: Option<&str>,
+) -> Result<i64> {
+  conn.execute(
+    "INSERT INTO import_batches (event_id, file_name, file_hash, mode, operator, created_at)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![event_id, source.file_name, source.file_hash, mode.as_str(), operator, now_timestamp()],
+  )?;
+  Ok(conn.last_insert_rowid())
+}
+
+pub(crate) fn finish_batch(conn: &Connection, batch_id: i64, total_rows: usize, inserted: usize) -> Result<()> {
+  conn.execute(
+    "UPDATE import_batches SET total_rows = ?1, inserted = ?2 WHERE id = ?3",
+    params![total_rows as i64, inserted as i64, batch_id],
+  )?;
+  Ok(())
+}
+
+/// Saves every guest in the event, with their check-ins, as the archive of
+/// the Replace batch about to clear them. Only a Replace needs the whole list.
+pub(crate) fn archive_guests(conn: &Connection, event_id: i64, batch_id: Option<i64>) -> Result<Archive> {
+  let guest_ids = conn
+    .prepare_cached("SELECT id FROM guests WHERE event_id = ?1 ORDER BY id")?
+    .query_map(params![event_id], |row| row.get(0))?
+    .collect::<rusqlite::Result<Vec<i64>>>()?;
+  let (checkins, checked_in) = conn.query_row(
+    "SELECT COUNT(*), COUNT(DISTINCT guest_id) FILTER (WHERE out_ts IS NULL) FROM checkins WHERE event_id = ?1",
+    params![event_id],
+    |row| Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as usize)),
+  )?;
+  conn.execute(
+    "INSERT INTO import_archives (event_id, batch_id, created_at, guests, checkins, snapshot)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![
+      event_id,
+      batch_id,
+      now_timestamp(),
+      guest_ids.len() as i64,
+      checkins as i64,
+      guests_snapshot(conn, &guest_ids)?,
+    ],
+  )?;
+  Ok(Archive {
+    id: conn.last_insert_rowid(),
+    guests: guest_ids.len(),
+    checkins,
+    checked_in,
+  })
+}
+
+/// What a Sync changed: the member and removal each guest it moved, restored
+/// or removed had before, which is all rolling it back needs.
+#[derive(Debug, Default)]
+pub(crate) struct SyncArchive {
+  guests: Vec<SyncedGuest>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncedGuest {
+  id: i64,
+  member_host: Option<String>,
+  removed_at: Option<String>,
+}
+
+impl SyncArchive {
+  /// Notes the guest's member and removal before the Sync changes them.
+  pub(crate) fn record(&mut self, conn: &Connection, guest_id: i64) -> Result<()> {
+    let guest = conn
+      .prepare_cached("SELECT member_host, removed_at FROM guests WHERE id = ?1")?
+      .query_row(params![guest_id], |row| {
+        Ok(SyncedGuest {
+          id: guest_id,
+          member_host: row.get(0)?,
+          removed_at: row.get(1)?,
+        })
+      })?;
+    self.guests.push(guest);
+    Ok(())
+  }
+
+  /// Saves the archive for the Sync batch.
+  pub(crate) fn save(self, conn: &Connection, event_id: i64, batch_id: Option<i64>) -> Result<()> {
+    conn.execute(
+      "INSERT INTO import_archives (event_id, batch_id, created_at, guests, checkins, snapshot)
+       VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+      params![
+        event_id,
+        batch_id,
+        now_timestamp(),
+        self.guests.len() as i64,
+        serde_json::json!({ "guests": self.guests }).to_string(),
+      ],
+    )?;
+    Ok(())
+  }
+}
+
+/// Returns guests a Sync changed to the member and removal they had in its
+/// archive. Returns how many changed back.
+fn unsync_guests(conn: &Connection, snapshot: &str) -> Result<usize> {
+  Ok(conn.execute(
+    "UPDATE guests SET member_host = s.value ->> 'memberHost', removed_at = s.value ->> 'removedAt'
+     FROM json_each(?1, '$.guests') s
+     WHERE guests.id = s.value ->> 'id'
+       AND (guests.member_host IS NOT s.value ->> 'memberHost' OR guests.removed_at IS NOT s.value ->> 'removedAt')",
+    params![snapshot],
+  )?)
+}
+
+const BATCH_SELECT: &str = "SELECT b.id, b.event_id, b.file_name, b.file_hash, b.mode, b.operator, b.created_at,
+    b.total_rows, b.inserted, (SELECT COUNT(*) FROM guests g WHERE g.batch_id = b.id),
+    b.rolled_back_at, b.rolled_back_by,
+    (SELECT COUNT(*) FROM checkins c JOIN guests g ON g.id = c.guest_id
+     WHERE g.batch_id = b.id
+       AND (c.batch_id IS NOT b.id OR (c.out_ts IS NOT NULL AND c.out_by IS NOT 'import'))),
+    (SELECT COUNT(*) FROM import_batches n
+     WHERE n.event_id = b.event_id AND n.id > b.id AND n.rolled_back_at IS NULL
+       AND n.mode IN ('replace', 'sync'))
+  FROM import_batches b";
+
+fn fetch_batch(conn: &Connection, event_id: i64, batch_id: i64) -> Result<ImportBatch> {
+  conn
+    .prepare_cached(&format!("{} WHERE b.id = ?1 AND b.event_id = ?2", BATCH_SELECT))?
+    .query_row(params![batch_id, event_id], map_batch_row)
+    .optional()?
+    .ok_or(Error::BatchNotFound(batch_id))
+}
+
+fn map_batch_row(row: &Row<'_>) -> rusqlite::Result<ImportBatch> {
+  Ok(ImportBatch {
+    id: row.get(0)?,
+    event_id: row.get(1)?,
+    file_name: row.get(2)?,
+    file_hash: row.get(3)?,
+    mode: row.get(4)?,
+    operator: row.get(5)?,
+    created_at: row.get(6)?,
+    total_rows: row.get(7)?,
+    inserted: row.get(8)?,
+    guests: row.get(9)?,
+    door_checkins: row.get(12)?,
+    later_imports: row.get(13)?,
+    rolled_back_at: row.get(10)?,
+    rolled_back_by: row.get(11)?,
+  })
+}
@@ -0,0 +1,766 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::audit::{checkin_snapshot, record_audit, AuditRecord};
+use crate::events::fetch_event;
+use crate::guests::{guests_snapshot, restore_guests, GuestChange};
+use crate::quotas::{listed_guests, quota_for};
+use crate::time::now_timestamp;
+
+#[derive(Debug, Clone)]
+enum UndoAction {
+  CheckIn { checkin_id: i64 },
+  CheckOut { checkin_id: i64 },
+  ForcedCheckOut { checkin_id: i64 },
+}
+
+impl UndoAction {
+  fn from_stored(kind: &str, checkin_id: i64) -> Result<Self> {
+    match kind {
+      "check_in" => Ok(UndoAction::CheckIn { checkin_id }),
+      "check_out" => Ok(UndoAction::CheckOut { checkin_id }),
+      "forced_check_out" => Ok(UndoAction::ForcedCheckOut { checkin_id }),
+      other => Err(Error::Invalid(format!("unknown undo action {}", other))),
+    }
+  }
+
+  fn kind(&self) -> &'static str {
+    match self {
+      UndoAction::CheckIn { .. } => "check_in",
+      UndoAction::CheckOut { .. } => "check_out",
+      UndoAction::ForcedCheckOut { .. } => "forced_check_out",
+    }
+  }
+
+  fn checkin_id(&self) -> i64 {
+    match self {
+      UndoAction::CheckIn { checkin_id }
+      | UndoAction::CheckOut { checkin_id }
+      | UndoAction::ForcedCheckOut { checkin_id } => *checkin_id,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckAction {
+  In,
+  Out,
+}
+
+/// Door limits an admin has chosen to waive for a single check-in. Every
+/// waiver that actually takes effect is written to the audit log.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Overrides {
+  pub capacity: bool,
+  /// Admit guests beyond their member's quota.
+  pub quota: bool,
+  /// Admit a guest who matches the banned list.
+  pub banned: bool,
+  /// Let a Replace import clear the list while guests are checked in.
+  pub checked_in: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToggleResult {
+  pub status: ToggleStatus,
+}
+
+#[derive(Debug)]
+struct ToggleOutcome {
+  result: ToggleResult,
+  undo: Option<UndoAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToggleStatus {
+  CheckedIn,
+  CheckedOut,
+  AlreadyIn,
+  NotCheckedIn,
+  NeverCheckedIn,
+  /// The venue is full; pass `Overrides::capacity` to admit the guest anyway.
+  AtCapacity,
+  /// The guest is past their member's guest-list quota; waive with
+  /// `Overrides::quota`.
+  OverGuestQuota,
+  /// The member already has as many guests inside as allowed; waive with
+  /// `Overrides::quota`.
+  MemberQuotaFull,
+  /// The guest matches the banned list; waive with `Overrides::banned`.
+  Banned,
+  /// A sync import dropped the guest from the list.
+  Removed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoResult {
+  pub status: UndoStatus,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoStatus {
+  RevertedCheckIn,
+  RevertedCheckOut,
+  RevertedEdit,
+  RevertedDelete,
+  RevertedMerge,
+  Empty,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedoResult {
+  pub status: RedoStatus,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedoStatus {
+  ReappliedCheckIn,
+  ReappliedCheckOut,
+  ReappliedEdit,
+  ReappliedDelete,
+  ReappliedMerge,
+  Empty,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoHistoryEntry {
+  pub id: i64,
+  pub action: String,
+  pub guest_id: i64,
+  pub display_name: String,
+  pub operator: Option<String>,
+  pub created_at: String,
+  pub undone_at: Option<String>,
+  pub undone_by: Option<String>,
+}
+
+/// Checks a guest in or out. `force` records a check-out for a guest who was
+/// never checked in; `overrides` waives door limits on check-in.
+pub fn toggle_checkin(
+  conn: &mut Connection,
+  event_id: i64,
+  guest_id: i64,
+  action: CheckAction,
+  operator: Option<&str>,
+  force: bool,
+  overrides: Overrides,
+) -> Result<ToggleResult> {
+  require_guest(conn, event_id, guest_id)?;
+
+  let tx = conn.transaction()?;
+  let outcome = match action {
+    CheckAction::In => check_in(&tx, event_id, guest_id, operator, overrides)?,
+    CheckAction::Out => check_out(&tx, event_id, guest_id, operator, force)?,
+  };
+  if let Some(undo_action) = &outcome.undo {
+    record_undo(&tx, event_id, undo_action, operator)?;
+  }
+  tx.commit()?;
+
+  Ok(outcome.result)
+}
+
+/// Checks a guest in as part of the caller's transaction, recording undo
+/// history when they are admitted. Returns the status as `toggle_checkin`
+/// would.
+pub(crate) fn check_in_within(
+  tx: &Transaction<'_>,
+  event_id: i64,
+  guest_id: i64,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<ToggleStatus> {
+  let outcome = check_in(tx, event_id, guest_id, operator, overrides)?;
+  if let Some(undo_action) = &outcome.undo {
+    record_undo(tx, event_id, undo_action, operator)?;
+  }
+  Ok(outcome.result.status)
+}
+
+pub fn undo_last(conn: &mut Connection, event_id: i64, operator: Option<&str>) -> Result<UndoResult> {
+  fetch_event(conn, event_id)?;
+
+  let tx = conn.transaction()?;
+  let entry = tx
+    .query_row(
+      "SELECT id, action, checkin_id, guest_id, before_state, after_state FROM undo_history
+       WHERE event_id = ?1 AND undone_at IS NULL
+       ORDER BY id DESC
+       LIMIT 1",
+      params![event_id],
+      |row| {
+        Ok((
+          row.get::<_, i64>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, Option<i64>>(2)?,
+          row.get::<_, i64>(3)?,
+          row.get::<_, Option<String>>(4)?,
+          row.get::<_, Option<String>>(5)?,
+        ))
+      },
+    )
+    .optional()?;
+
+  let Some((entry_id, kind, checkin_id, guest_id, before_state, after_state)) = entry else {
+    return Ok(UndoResult {
+      status: UndoStatus::Empty,
+    });
+  };
+
+  let (status, before, after) = if let Some(change) = GuestChange::from_kind(&kind) {
+    restore_guests(&tx, &stored_state(&kind, before_state)?)?;
+    let status = match change {
+      GuestChange::Edit => UndoStatus::RevertedEdit,
+      GuestChange::Delete => UndoStatus::RevertedDelete,
+      GuestChange::Merge => UndoStatus::RevertedMerge,
+    };
+    (status, after_state, Some(guests_snapshot(&tx, &snapshot_guest_ids(&tx, entry_id)?)?))
+  } else {
+    let checkin_id = stored_checkin(&kind, checkin_id)?;
+    let before = checkin_snapshot(&tx, checkin_id)?;
+    let status = match UndoAction::from_stored(&kind, checkin_id)? {
+      UndoAction::CheckIn { checkin_id } => {
+        tx.execute("DELETE FROM checkins WHERE id = ?1", params![checkin_id])?;
+        UndoStatus::RevertedCheckIn
+      }
+      UndoAction::CheckOut { checkin_id } => {
+        tx.execute(
+          "UPDATE checkins SET out_ts = NULL, out_by = NULL WHERE id = ?1",
+          params![checkin_id],
+        )?;
+        UndoStatus::RevertedCheckOut
+      }
+      UndoAction::ForcedCheckOut { checkin_id } => {
+        tx.execute("DELETE FROM checkins WHERE id = ?1", params![checkin_id])?;
+        UndoStatus::RevertedCheckOut
+      }
+    };
+    (status, before, checkin_snapshot(&tx, checkin_id)?)
+  };
+
+  tx.execute(
+    "UPDATE undo_history SET undone_at = ?1, undone_by = ?2 WHERE id = ?3",
+    params![now_timestamp(), operator, entry_id],
+  )?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: &format!("undo_{}", kind),
+      operator,
+      before,
+      after,
+    },
+  )?;
+  tx.commit()?;
+
+  Ok(UndoResult { status })
+}
+
+pub fn redo_last(conn: &mut Connection, event_id: i64, operator: Option<&str>) -> Result<RedoResult> {
+  fetch_event(conn, event_id)?;
+
+  let tx = conn.transaction()?;
+  // Undone entries always form a suffix of the history (new actions discard
+  // them), so the most recently undone one is the oldest of the suffix.
+  let entry = tx
+    .query_row(
+      "SELECT id, action, checkin_id, guest_id, in_ts, out_ts, in_by, out_by, after_state FROM undo_history
+       WHERE event_id = ?1 AND undone_at IS NOT NULL
+       ORDER BY id ASC
+       LIMIT 1",
+      params![event_id],
+      |row| {
+        Ok((
+          row.get::<_, i64>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, Option<i64>>(2)?,
+          row.get::<_, i64>(3)?,
+          row.get::<_, Option<String>>(4)?,
+          row.get::<_, Option<String>>(5)?,
+          row.get::<_, Option<String>>(6)?,
+          row.get::<_, Option<String>>(7)?,
+          row.get::<_, Option<String>>(8)?,
+        ))
+      },
+    )
+    .optional()?;
+
+  let Some((entry_id, kind, checkin_id, guest_id, in_ts, out_ts, in_by, out_by, after_state)) = entry else {
+    return Ok(RedoResult {
+      status: RedoStatus::Empty,
+    });
+  };
+
+  let (status, before, after) = if let Some(change) = GuestChange::from_kind(&kind) {
+    let before = guests_snapshot(&tx, &snapshot_guest_ids(&tx, entry_id)?)?;
+    let after_state = stored_state(&kind, after_state)?;
+    restore_guests(&tx, &after_state)?;
+    let status = match change {
+      GuestChange::Edit => RedoStatus::ReappliedEdit,
+      GuestChange::Delete => RedoStatus::ReappliedDelete,
+      GuestChange::Merge => RedoStatus::ReappliedMerge,
+    };
+    (status, Some(before), Some(after_state))
+  } else {
+    let mut checkin_id = stored_checkin(&kind, checkin_id)?;
+    let in_ts = in_ts.ok_or_else(|| Error::Invalid(format!("undo entry {} has no check-in time", entry_id)))?;
+    let (status, before) = match UndoAction::from_stored(&kind, checkin_id)? {
+      UndoAction::CheckIn { checkin_id: old_id } | UndoAction::ForcedCheckOut { checkin_id: old_id } => {
+        // The check-in comes back under a new id, like any other insert,
+        // and the undone entries that refer to it follow.
+        tx.execute(
+          "INSERT INTO checkins (event_id, guest_id, in_ts, out_ts, in_by, out_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+          params![event_id, guest_id, in_ts, out_ts, in_by, out_by],
+        )?;
+        checkin_id = tx.last_insert_rowid();
+        tx.execute(
+          "UPDATE undo_history SET checkin_id = ?1
+           WHERE event_id = ?2 AND checkin_id = ?3 AND undone_at IS NOT NULL",
+          params![checkin_id, event_id, old_id],
+        )?;
+        let status = if out_ts.is_some() {
+          RedoStatus::ReappliedCheckOut
+        } else {
+          RedoStatus::ReappliedCheckIn
+        };
+        (status, None)
+      }
+      UndoAction::CheckOut { checkin_id } => {
+        let before = checkin_snapshot(&tx, checkin_id)?;
+        tx.execute(
+          "UPDATE checkins SET out_ts = ?1, out_by = ?2 WHERE id = ?3",
+          params![out_ts, out_by, checkin_id],
+        )?;
+        (RedoStatus::ReappliedCheckOut, before)
+      }
+    };
+    (status, before, checkin_snapshot(&tx, checkin_id)?)
+  };
+
+  tx.execute(
+    "UPDATE undo_history SET undone_at = NULL, undone_by = NULL WHERE id = ?1",
+    params![entry_id],
+  )?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: &format!("redo_{}", kind),
+      operator,
+      before,
+      after,
+    },
+  )?;
+  tx.commit()?;
+
+  Ok(RedoResult { status })
+}
+
+pub fn list_undo_history(
+  conn: &Connection,
+  event_id: i64,
+  limit: Option<usize>,
+) -> Result<Vec<UndoHistoryEntry>> {
+  fetch_event(conn, event_id)?;
+
+  let limit = limit.unwrap_or(20).min(200) as i64;
+  let mut stmt = conn.prepare_cached(
+    "SELECT h.id, h.action, h.guest_id, COALESCE(g.display_name, h.guest_name, ''), h.operator, h.created_at,
+       h.undone_at, h.undone_by
+     FROM undo_history h
+     LEFT JOIN guests g ON g.id = h.guest_id
+     WHERE h.event_id = ?1
+     ORDER BY h.id DESC
+     LIMIT ?2",
+  )?;
+  let mut rows = stmt.query(params![event_id, limit])?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    results.push(UndoHistoryEntry {
+      id: row.get(0)?,
+      action: row.get(1)?,
+      guest_id: row.get(2)?,
+      display_name: row.get(3)?,
+      operator: row.get(4)?,
+      created_at: row.get(5)?,
+      undone_at: row.get(6)?,
+      undone_by: row.get(7)?,
+    });
+  }
+
+  Ok(results)
+}
+
+fn check_in(
+  conn: &Connection,
+  event_id: i64,
+  guest_id: i64,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<ToggleOutcome> {
+  let existing: Option<i64> = conn
+    .query_row(
+      "SELECT id FROM checkins WHERE guest_id = ?1 AND out_ts IS NULL",
+      params![guest_id],
+      |row| row.get(0),
+    )
+    .optional()?;
+
+  if existing.is_some() {
+    return Ok(ToggleOutcome {
+      result: ToggleResult {
+        status: ToggleStatus::AlreadyIn,
+      },
+      undo: None,
+    });
+  }
+
+  let removed: bool = conn
+    .prepare_cached("SELECT removed_at IS NOT NULL FROM guests WHERE id = ?1")?
+    .query_row(params![guest_id], |row| row.get(0))?;
+  if removed {
+    return Ok(ToggleOutcome {
+      result: ToggleResult {
+        status: ToggleStatus::Removed,
+      },
+      undo: None,
+    });
+  }
+
+  // Waivers are logged only once the guest is actually admitted.
+  let mut waivers = Vec::new();
+  let ban: Option<(String, Option<String>)> = conn
+    .prepare_cached(
+      "SELECT b.name, b.reason FROM guests g JOIN banned_guests b ON b.id = g.banned_id WHERE g.id = ?1",
+    )?
+    .query_row(params![guest_id], |row| Ok((row.get(0)?, row.get(1)?)))
+    .optional()?;
+  if let Some((banned_name, reason)) = ban {
+    if !overrides.banned {
+      return Ok(ToggleOutcome {
+        result: ToggleResult {
+          status: ToggleStatus::Banned,
+        },
+        undo: None,
+      });
+    }
+    waivers.push(AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: "banned_override",
+      operator,
+      before: Some(serde_json::json!({ "bannedName": banned_name, "reason": reason }).to_string()),
+      after: None,
+    });
+  }
+
+  if let Some(status) = check_member_quota(conn, event_id, guest_id, operator, overrides, &mut waivers)? {
+    return Ok(ToggleOutcome {
+      result: ToggleResult { status },
+      undo: None,
+    });
+  }
+
+  let capacity: Option<i64> = conn
+    .prepare_cached("SELECT capacity FROM events WHERE id = ?1")?
+    .query_row(params![event_id], |row| row.get(0))?;
+  if let Some(capacity) = capacity {
+    let present = present_count(conn, event_id)?;
+    if present >= capacity {
+      if !overrides.capacity {
+        return Ok(ToggleOutcome {
+          result: ToggleResult {
+            status: ToggleStatus::AtCapacity,
+          },
+          undo: None,
+        });
+      }
+      waivers.push(AuditRecord {
+        event_id: Some(event_id),
+        guest_id: Some(guest_id),
+        action: "capacity_override",
+        operator,
+        before: Some(serde_json::json!({ "present": present, "capacity": capacity }).to_string()),
+        after: None,
+      });
+    }
+  }
+
+  let now = now_timestamp();
+  conn.execute(
+    "INSERT INTO checkins (event_id, guest_id, in_ts, out_ts, in_by) VALUES (?1, ?2, ?3, NULL, ?4)",
+    params![event_id, guest_id, now, operator],
+  )?;
+  let id = conn.last_insert_rowid();
+  for waiver in waivers {
+    record_audit(conn, waiver)?;
+  }
+  record_audit(
+    conn,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: "check_in",
+      operator,
+      before: None,
+      after: checkin_snapshot(conn, id)?,
+    },
+  )?;
+
+  Ok(ToggleOutcome {
+    result: ToggleResult {
+      status: ToggleStatus::CheckedIn,
+    },
+    undo: Some(UndoAction::CheckIn { checkin_id: id }),
+  })
+}
+
+fn check_out(
+  conn: &Connection,
+  event_id: i64,
+  guest_id: i64,
+  operator: Option<&str>,
+  force: bool,
+) -> Result<ToggleOutcome> {
+  let existing: Option<(i64, Option<String>)> = conn
+    .query_row(
+      "SELECT id, out_ts FROM checkins WHERE guest_id = ?1 AND out_ts IS NULL ORDER BY in_ts DESC LIMIT 1",
+      params![guest_id],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()?;
+
+  let Some((checkin_id, _)) = existing else {
+    let ever_checked_in = conn
+      .query_row(
+        "SELECT 1 FROM checkins WHERE guest_id = ?1 LIMIT 1",
+        params![guest_id],
+        |_| Ok(()),
+      )
+      .optional()?
+      .is_some();
+
+    if force {
+      let now = now_timestamp();
+      conn.execute(
+        "INSERT INTO checkins (event_id, guest_id, in_ts, out_ts, in_by, out_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![event_id, guest_id, now, now, operator, operator],
+      )?;
+      let id = conn.last_insert_rowid();
+      record_audit(
+        conn,
+        AuditRecord {
+          event_id: Some(event_id),
+          guest_id: Some(guest_id),
+          action: "forced_check_out",
+          operator,
+          before: None,
+          after: checkin_snapshot(conn, id)?,
+        },
+      )?;
+      return Ok(ToggleOutcome {
+        result: ToggleResult {
+          status: ToggleStatus::CheckedOut,
+        },
+        undo: Some(UndoAction::ForcedCheckOut {
+          checkin_id: id,
+        }),
+      });
+    }
+
+    let status = if ever_checked_in {
+      ToggleStatus::NotCheckedIn
+    } else {
+      ToggleStatus::NeverCheckedIn
+    };
+
+    return Ok(ToggleOutcome {
+      result: ToggleResult { status },
+      undo: None,
+    });
+  };
+
+  let before = checkin_snapshot(conn, checkin_id)?;
+  let now = now_timestamp();
+  conn.execute(
+    "UPDATE checkins SET out_ts = ?1, out_by = ?2 WHERE id = ?3",
+    params![now, operator, checkin_id],
+  )?;
+  record_audit(
+    conn,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: "check_out",
+      operator,
+      before,
+      after: checkin_snapshot(conn, checkin_id)?,
+    },
+  )?;
+
+  Ok(ToggleOutcome {
+    result: ToggleResult {
+      status: ToggleStatus::CheckedOut,
+    },
+    undo: Some(UndoAction::CheckOut { checkin_id }),
+  })
+}
+
+/// Records an undoable action along with a snapshot of its check-in row, which
+/// is what redo restores. A new action discards anything previously undone.
+fn record_undo(
+  conn: &Connection,
+  event_id: i64,
+  action: &UndoAction,
+  operator: Option<&str>,
+) -> Result<()> {
+  conn.execute(
+    "DELETE FROM undo_history WHERE event_id = ?1 AND undone_at IS NOT NULL",
+    params![event_id],
+  )?;
+  conn.execute(
+    "INSERT INTO undo_history (event_id, action, checkin_id, guest_id, guest_name, in_ts, out_ts, in_by, out_by, operator, created_at)
+     SELECT c.event_id, ?2, c.id, c.guest_id, g.display_name, c.in_ts, c.out_ts, c.in_by, c.out_by, ?3, ?4
+     FROM checkins c JOIN guests g ON g.id = c.guest_id
+     WHERE c.id = ?1",
+    params![action.checkin_id(), action.kind(), operator, now_timestamp()],
+  )?;
+  Ok(())
+}
+
+/// Records an undoable change to guest records. `before` and `after` are
+/// [`guests_snapshot`]s of the same guests; undo restores one and redo the
+/// other.
+pub(crate) fn record_guest_undo(
+  conn: &Connection,
+  event_id: i64,
+  change: GuestChange,
+  guest_id: i64,
+  before: &str,
+  after: &str,
+  operator: Option<&str>,
+) -> Result<()> {
+  conn.execute(
+    "DELETE FROM undo_history WHERE event_id = ?1 AND undone_at IS NOT NULL",
+    params![event_id],
+  )?;
+  conn.execute(
+    "INSERT INTO undo_history (event_id, action, guest_id, guest_name, before_state, after_state, operator, created_at)
+     VALUES (?1, ?2, ?3, (SELECT display_name FROM guests WHERE id = ?3), ?4, ?5, ?6, ?7)",
+    params![event_id, change.kind(), guest_id, before, after, operator, now_timestamp()],
+  )?;
+  Ok(())
+}
+
+fn stored_checkin(kind: &str, checkin_id: Option<i64>) -> Result<i64> {
+  checkin_id.ok_or_else(|| Error::Invalid(format!("{} undo entry has no check-in", kind)))
+}
+
+fn stored_state(kind: &str, state: Option<String>) -> Result<String> {
+  state.ok_or_else(|| Error::Invalid(format!("{} undo entry has no snapshot", kind)))
+}
+
+/// The guests an undo entry's snapshots cover.
+fn snapshot_guest_ids(conn: &Connection, entry_id: i64) -> Result<Vec<i64>> {
+  let mut stmt = conn.prepare_cached(
+    "SELECT j.value FROM undo_history h, json_each(h.before_state, '$.guestIds') j WHERE h.id = ?1",
+  )?;
+  let rows = stmt.query_map(params![entry_id], |row| row.get(0))?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Applies the guest's member quotas to a check-in, returning the refusal
+/// status if one is exceeded. With `overrides.quota` the guest is let through
+/// and the waiver is audited instead.
+fn check_member_quota<'a>(
+  conn: &Connection,
+  event_id: i64,
+  guest_id: i64,
+  operator: Option<&'a str>,
+  overrides: Overrides,
+  waivers: &mut Vec<AuditRecord<'a>>,
+) -> Result<Option<ToggleStatus>> {
+  let member_host: Option<String> = conn
+    .prepare_cached("SELECT member_host FROM guests WHERE id = ?1")?
+    .query_row(params![guest_id], |row| row.get(0))?;
+  let Some(member_host) = member_host.filter(|host| !host.is_empty()) else {
+    return Ok(None);
+  };
+  let quota = quota_for(conn, event_id, &member_host)?;
+
+  let mut exceeded = None;
+  if let Some(limit) = quota.guests {
+    // Guests are admitted in list order, so only the first `limit` count.
+    let position: i64 = conn
+      .prepare_cached(
+        "SELECT COUNT(*) FROM guests
+         WHERE event_id = ?1 AND lower(member_host) = lower(?2) AND id <= ?3 AND removed_at IS NULL",
+      )?
+      .query_row(params![event_id, member_host, guest_id], |row| row.get(0))?;
+    if position > limit {
+      exceeded = Some((ToggleStatus::OverGuestQuota, listed_guests(conn, event_id, &member_host)?, limit));
+    }
+  }
+  if let (None, Some(limit)) = (&exceeded, quota.present) {
+    let present: i64 = conn
+      .prepare_cached(
+        "SELECT COUNT(*) FROM checkins c
+         JOIN guests g ON g.id = c.guest_id
+         WHERE c.event_id = ?1 AND c.out_ts IS NULL AND lower(g.member_host) = lower(?2)",
+      )?
+      .query_row(params![event_id, member_host], |row| row.get(0))?;
+    if present >= limit {
+      exceeded = Some((ToggleStatus::MemberQuotaFull, present, limit));
+    }
+  }
+
+  let Some((status, count, limit)) = exceeded else {
+    return Ok(None);
+  };
+  if !overrides.quota {
+    return Ok(Some(status));
+  }
+  let kind = match status {
+    ToggleStatus::OverGuestQuota => "guests",
+    _ => "present",
+  };
+  waivers.push(AuditRecord {
+    event_id: Some(event_id),
+    guest_id: Some(guest_id),
+    action: "quota_override",
+    operator,
+    before: Some(
+      serde_json::json!({ "memberHost": member_host, "quota": kind, "count": count, "limit": limit })
+        .to_string(),
+    ),
+    after: None,
+  });
+  Ok(None)
+}
+
+/// Number of guests currently inside the event.
+pub(crate) fn present_count(conn: &Connection, event_id: i64) -> Result<i64> {
+  Ok(
+    conn
+      .prepare_cached("SELECT COUNT(*) FROM checkins WHERE event_id = ?1 AND out_ts IS NULL")?
+      .query_row(params![event_id], |row| row.get(0))?,
+  )
+}
+
+pub(crate) fn require_guest(conn: &Connection, event_id: i64, guest_id: i64) -> Result<()> {
+  conn
+    .prepare_cached("SELECT 1 FROM guests WHERE id = ?1 AND event_id = ?2")?
+    .query_row(params![guest_id, event_id], |_| Ok(()))
+    .optional()?
+    .ok_or(Error::GuestNotFound { event_id, guest_id })
+}
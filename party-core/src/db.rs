@@ -0,0 +1,52 @@
+use std::{fs, path::Path, time::Duration};
+
+use rusqlite::{functions::FunctionFlags, Connection};
+
+use crate::error::{Error, Result};
+use crate::migrations;
+use crate::names::{fold, index_text};
+use crate::phonetic::sound_key;
+
+/// Opens the database at `path`, creating it if needed, and brings its schema
+/// up to date.
+pub fn open(path: &str) -> Result<Connection> {
+  ensure_db(path)?;
+  let mut conn = Connection::open(path).map_err(|error| Error::Open {
+    path: path.to_string(),
+    error,
+  })?;
+  conn.busy_timeout(Duration::from_secs(5))?;
+  conn.pragma_update(None, "foreign_keys", "ON")?;
+  conn.pragma_update(None, "journal_mode", "WAL")?;
+  conn.pragma_update(None, "synchronous", "NORMAL")?;
+  conn.set_prepared_statement_cache_capacity(64);
+  register_functions(&conn)?;
+  migrations::migrate(&mut conn)?;
+  Ok(conn)
+}
+
+type TextFunction = fn(&str) -> String;
+
+/// Registers the Rust text functions that the search triggers and queries
+/// call: `fold`, `index_text` and `sound_key`. Each passes NULL through.
+fn register_functions(conn: &Connection) -> Result<()> {
+  let functions: [(&str, TextFunction); 3] =
+    [("fold", fold), ("index_text", index_text), ("sound_key", sound_key)];
+  for (name, function) in functions {
+    conn.create_scalar_function(
+      name,
+      1,
+      FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+      move |ctx| Ok(ctx.get::<Option<String>>(0)?.map(|value| function(&value))),
+    )?;
+  }
+  Ok(())
+}
+
+fn ensure_db(path: &str) -> Result<()> {
+  let path = Path::new(path);
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  Ok(())
+}
@@ -0,0 +1,47 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("event {0} not found")]
+  EventNotFound(i64),
+  #[error("guest {guest_id} not found in event {event_id}")]
+  GuestNotFound { event_id: i64, guest_id: i64 },
+  #[error("banned list entry {0} not found")]
+  BanNotFound(i64),
+  #[error("{0} guest(s) are still checked in; confirm to replace the list anyway")]
+  GuestsCheckedIn(i64),
+  #[error(
+    "rolling back would drop {door_checkins} check-in(s) made at the door and override {later_imports} \
+     later import(s); confirm to roll back anyway"
+  )]
+  RollbackLosesChanges { door_checkins: i64, later_imports: i64 },
+  #[error("import batch {0} not found")]
+  BatchNotFound(i64),
+  #[error("import profile {0:?} not found")]
+  ProfileNotFound(String),
+  /// Input the caller should fix: a bad date, timezone, door time and so on.
+  #[error("{0}")]
+  Invalid(String),
+  #[error("could not open database at {path}: {error}")]
+  Open { path: String, error: rusqlite::Error },
+  #[error(
+    "database schema version {found} is newer than this app supports ({supported}); update Party Sign-In to open it"
+  )]
+  SchemaTooNew { found: i64, supported: i64 },
+  #[error("migration {version} ({description}) failed: {error}")]
+  Migration {
+    version: i64,
+    description: &'static str,
+    error: Box<Error>,
+  },
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error(transparent)]
+  Csv(#[from] csv::Error),
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
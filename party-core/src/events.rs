@@ -0,0 +1,286 @@
+use chrono::{NaiveDate, NaiveTime};
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::names::{clean_optional, clean_whitespace};
+use crate::time::{parse_timezone, rezone_timestamp, DEFAULT_TIMEZONE};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+  pub id: i64,
+  pub name: String,
+  pub event_date: Option<String>,
+  pub venue: Option<String>,
+  pub doors_open: Option<String>,
+  pub doors_close: Option<String>,
+  pub timezone: String,
+  /// Fire-code limit on guests inside at once; `None` means unlimited.
+  pub capacity: Option<i64>,
+  /// Share of `capacity`, in percent, at which the dashboard starts warning.
+  pub capacity_warning_percent: i64,
+  /// Default number of guests each member may put on the list.
+  pub guest_quota: Option<i64>,
+  /// Default number of each member's guests allowed inside at once.
+  pub present_quota: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventInput {
+  pub name: String,
+  pub event_date: Option<String>,
+  pub venue: Option<String>,
+  pub doors_open: Option<String>,
+  pub doors_close: Option<String>,
+  pub timezone: Option<String>,
+  pub capacity: Option<i64>,
+  pub capacity_warning_percent: Option<i64>,
+  pub guest_quota: Option<i64>,
+  pub present_quota: Option<i64>,
+}
+
+pub fn list_events(conn: &Connection) -> Result<Vec<Event>> {
+  let mut stmt = conn.prepare_cached(
+    "SELECT id, name, event_date, venue, doors_open, doors_close, timezone, capacity, capacity_warning_percent,
+       guest_quota, present_quota
+     FROM events
+     ORDER BY COALESCE(event_date, date(created_at)) DESC, id DESC",
+  )?;
+  let mut rows = stmt.query([])?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    results.push(map_event_row(row)?);
+  }
+
+  Ok(results)
+}
+
+pub fn create_event(conn: &Connection, event: EventInput) -> Result<Event> {
+  let event = normalize_event_input(event)?;
+  let timezone = event.timezone.as_deref().unwrap_or(DEFAULT_TIMEZONE);
+  conn.execute(
+    "INSERT INTO events (name, event_date, venue, doors_open, doors_close, timezone, capacity, capacity_warning_percent,
+       guest_quota, present_quota)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, 90), ?9, ?10)",
+    params![
+      event.name,
+      event.event_date,
+      event.venue,
+      event.doors_open,
+      event.doors_close,
+      timezone,
+      event.capacity,
+      event.capacity_warning_percent,
+      event.guest_quota,
+      event.present_quota
+    ],
+  )?;
+
+  fetch_event(conn, conn.last_insert_rowid())
+}
+
+/// Replaces the event's details. The warning percentage keeps its current
+/// value when left out; a missing capacity or quota removes that limit.
+///
+/// Changing the timezone means choosing what happens to check-in times, so a
+/// timezone other than the current one is refused here; use
+/// [`set_event_timezone`] or [`update_event_with_timezone`].
+pub fn update_event(conn: &Connection, event_id: i64, event: EventInput) -> Result<Event> {
+  let event = normalize_event_input(event)?;
+  let current = fetch_event(conn, event_id)?;
+  if event.timezone.as_ref().is_some_and(|timezone| *timezone != current.timezone) {
+    return Err(Error::Invalid(format!(
+      "the timezone of {} has to be changed with set_event_timezone",
+      current.name
+    )));
+  }
+  let updated = conn.execute(
+    "UPDATE events SET name = ?1, event_date = ?2, venue = ?3, doors_open = ?4, doors_close = ?5,
+      capacity = ?6, capacity_warning_percent = COALESCE(?7, capacity_warning_percent),
+      guest_quota = ?8, present_quota = ?9
+     WHERE id = ?10",
+    params![
+      event.name,
+      event.event_date,
+      event.venue,
+      event.doors_open,
+      event.doors_close,
+      event.capacity,
+      event.capacity_warning_percent,
+      event.guest_quota,
+      event.present_quota,
+      event_id
+    ],
+  )?;
+  if updated == 0 {
+    return Err(Error::EventNotFound(event_id));
+  }
+
+  fetch_event(conn, event_id)
+}
+
+/// Changes an event's timezone. Stored timestamps are UTC instants, so by
+/// default they are untouched and simply display in the new zone. Pass
+/// `keep_wall_clock` when the old zone was wrong and times should keep
+/// reading the same on the clock (e.g. a sheet imported under the wrong zone).
+pub fn set_event_timezone(
+  conn: &mut Connection,
+  event_id: i64,
+  timezone: &str,
+  keep_wall_clock: bool,
+) -> Result<Event> {
+  let tx = conn.transaction()?;
+  change_timezone(&tx, event_id, timezone, keep_wall_clock)?;
+  tx.commit()?;
+  fetch_event(conn, event_id)
+}
+
+fn change_timezone(tx: &Transaction<'_>, event_id: i64, timezone: &str, keep_wall_clock: bool) -> Result<()> {
+  let old_tz = parse_timezone(&fetch_event(tx, event_id)?.timezone)?;
+  let new_tz = parse_timezone(timezone.trim())?;
+
+  tx.execute(
+    "UPDATE events SET timezone = ?1 WHERE id = ?2",
+    params![new_tz.name(), event_id],
+  )?;
+
+  if keep_wall_clock && old_tz != new_tz {
+    let rows: Vec<(i64, String, Option<String>)> = {
+      let mut stmt = tx.prepare_cached("SELECT id, in_ts, out_ts FROM checkins WHERE event_id = ?1")?;
+      let rows = stmt.query_map(params![event_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+      rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (checkin_id, in_ts, out_ts) in rows {
+      let in_ts = rezone_timestamp(&in_ts, old_tz, new_tz);
+      let out_ts = out_ts.map(|out| rezone_timestamp(&out, old_tz, new_tz));
+      tx.execute(
+        "UPDATE checkins SET in_ts = ?1, out_ts = ?2 WHERE id = ?3",
+        params![in_ts, out_ts, checkin_id],
+      )?;
+    }
+  }
+  Ok(())
+}
+
+/// Updates the event's details and timezone together, so a failure leaves
+/// neither changed. `keep_wall_clock` is as for [`set_event_timezone`] and only
+/// matters when the timezone differs.
+pub fn update_event_with_timezone(
+  conn: &mut Connection,
+  event_id: i64,
+  event: EventInput,
+  keep_wall_clock: bool,
+) -> Result<Event> {
+  let tx = conn.transaction()?;
+  if let Some(timezone) = event.timezone.as_deref() {
+    change_timezone(&tx, event_id, timezone, keep_wall_clock)?;
+  }
+  update_event(&tx, event_id, event)?;
+  tx.commit()?;
+  fetch_event(conn, event_id)
+}
+
+pub fn delete_event(conn: &Connection, event_id: i64) -> Result<()> {
+  let deleted = conn.execute("DELETE FROM events WHERE id = ?1", params![event_id])?;
+  if deleted == 0 {
+    return Err(Error::EventNotFound(event_id));
+  }
+  Ok(())
+}
+
+pub fn fetch_event(conn: &Connection, event_id: i64) -> Result<Event> {
+  conn
+    .prepare_cached(
+      "SELECT id, name, event_date, venue, doors_open, doors_close, timezone, capacity, capacity_warning_percent,
+         guest_quota, present_quota
+       FROM events WHERE id = ?1",
+    )?
+    .query_row(params![event_id], map_event_row)
+    .optional()?
+    .ok_or(Error::EventNotFound(event_id))
+}
+
+fn map_event_row(row: &Row<'_>) -> rusqlite::Result<Event> {
+  Ok(Event {
+    id: row.get(0)?,
+    name: row.get(1)?,
+    event_date: row.get(2)?,
+    venue: row.get(3)?,
+    doors_open: row.get(4)?,
+    doors_close: row.get(5)?,
+    timezone: row.get(6)?,
+    capacity: row.get(7)?,
+    capacity_warning_percent: row.get(8)?,
+    guest_quota: row.get(9)?,
+    present_quota: row.get(10)?,
+  })
+}
+
+fn normalize_event_input(input: EventInput) -> Result<EventInput> {
+  let name = clean_whitespace(&input.name);
+  if name.is_empty() {
+    return Err(Error::Invalid("event name is required".into()));
+  }
+
+  let event_date = clean_optional(input.event_date);
+  if let Some(date) = event_date.as_deref() {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+      .map_err(|_| Error::Invalid(format!("invalid event date {}", date)))?;
+  }
+
+  let timezone = match clean_optional(input.timezone) {
+    Some(tz) => Some(parse_timezone(&tz)?.name().to_string()),
+    None => None,
+  };
+
+  if let Some(capacity) = input.capacity {
+    if capacity < 1 {
+      return Err(Error::Invalid("capacity must be at least 1".into()));
+    }
+  }
+  validate_quota("guest quota", input.guest_quota)?;
+  validate_quota("present quota", input.present_quota)?;
+  if let Some(percent) = input.capacity_warning_percent {
+    if !(1..=100).contains(&percent) {
+      return Err(Error::Invalid("capacity warning must be between 1 and 100 percent".into()));
+    }
+  }
+
+  Ok(EventInput {
+    name,
+    event_date,
+    venue: clean_optional(input.venue),
+    doors_open: normalize_door_time(input.doors_open)?,
+    doors_close: normalize_door_time(input.doors_close)?,
+    timezone,
+    capacity: input.capacity,
+    capacity_warning_percent: input.capacity_warning_percent,
+    guest_quota: input.guest_quota,
+    present_quota: input.present_quota,
+  })
+}
+
+pub(crate) fn validate_quota(label: &str, value: Option<i64>) -> Result<()> {
+  match value {
+    Some(quota) if quota < 1 => Err(Error::Invalid(format!("{} must be at least 1", label))),
+    _ => Ok(()),
+  }
+}
+
+fn normalize_door_time(value: Option<String>) -> Result<Option<String>> {
+  let Some(raw) = clean_optional(value) else {
+    return Ok(None);
+  };
+
+  const DOOR_FORMATS: &[&str] = &["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M%p"];
+
+  for fmt in DOOR_FORMATS {
+    if let Ok(time) = NaiveTime::parse_from_str(&raw, fmt) {
+      return Ok(Some(time.format("%H:%M").to_string()));
+    }
+  }
+
+  Err(Error::Invalid(format!("invalid door time {}", raw)))
+}
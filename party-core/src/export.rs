@@ -0,0 +1,120 @@
+use std::{fs, path::Path};
+
+use chrono::Utc;
+use chrono_tz::Tz;
+use rusqlite::{params, Connection};
+
+use crate::error::{Error, Result};
+use crate::events::fetch_event;
+use crate::time::{format_local_timestamp, parse_timezone};
+
+/// Writes the event's guest list to `out_dir` (the desktop when `None`) and
+/// returns the path of the new file.
+pub fn export_csv(conn: &Connection, event_id: i64, out_dir: Option<&str>) -> Result<String> {
+  let event = fetch_event(conn, event_id)?;
+  let tz = parse_timezone(&event.timezone)?;
+  let data = render_csv(conn, event_id)?;
+
+  let output_dir = match out_dir {
+    Some(dir) => dir.to_string(),
+    None => desktop_dir_path()?,
+  };
+  let file_path = Path::new(&output_dir).join(export_filename(&event.name, tz));
+  if let Some(parent) = file_path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(&file_path, data)?;
+
+  Ok(file_path
+    .to_str()
+    .ok_or_else(|| Error::Invalid("export path is not valid UTF-8".into()))?
+    .to_string())
+}
+
+/// The export file's contents, with times in the event's timezone.
+pub fn render_csv(conn: &Connection, event_id: i64) -> Result<Vec<u8>> {
+  let tz = parse_timezone(&fetch_event(conn, event_id)?.timezone)?;
+
+  let mut stmt = conn.prepare_cached(
+    "SELECT g.display_name, g.member_host,
+      MAX(CASE WHEN c.id IS NOT NULL AND c.out_ts IS NULL THEN 1 ELSE 0 END) AS in_status,
+      MAX(c.in_ts) AS last_in,
+      MAX(c.out_ts) AS last_out,
+      g.is_walk_in
+    FROM guests g
+    LEFT JOIN checkins c ON c.guest_id = g.id
+    WHERE g.event_id = ?1 AND g.removed_at IS NULL
+    GROUP BY g.id
+    ORDER BY g.display_name"
+  )?;
+
+  let mut rows = stmt.query(params![event_id])?;
+
+  let mut wtr = csv::Writer::from_writer(vec![]);
+  wtr.write_record([
+    "Member Name",
+    "Guest Name",
+    "Check In Y/N",
+    "Check In Time",
+    "Check Out Y/N",
+    "Check Out Time",
+    "Walk-In Y/N",
+  ])?;
+
+  while let Some(row) = rows.next()? {
+    let guest_name: String = row.get(0)?;
+    let member_host: Option<String> = row.get(1)?;
+    let is_in: i64 = row.get(2)?;
+    let in_ts: Option<String> = row.get(3)?;
+    let out_ts: Option<String> = row.get(4)?;
+    let is_walk_in: bool = row.get(5)?;
+
+    let check_in_flag = if is_in == 1 || in_ts.is_some() {
+      "Y"
+    } else {
+      "N"
+    };
+    let check_out_flag = if let Some(out) = &out_ts {
+      if !out.is_empty() {
+        "Y"
+      } else {
+        "N"
+      }
+    } else {
+      "N"
+    };
+
+    wtr.write_record([
+      member_host.clone().unwrap_or_default(),
+      guest_name,
+      check_in_flag.to_string(),
+      in_ts.as_deref().map(|ts| format_local_timestamp(ts, tz)).unwrap_or_default(),
+      check_out_flag.to_string(),
+      out_ts.as_deref().map(|ts| format_local_timestamp(ts, tz)).unwrap_or_default(),
+      if is_walk_in { "Y" } else { "N" }.to_string(),
+    ])?;
+  }
+
+  wtr.into_inner().map_err(|e| Error::Io(e.into_error()))
+}
+
+fn export_filename(event_name: &str, tz: Tz) -> String {
+  let now = Utc::now().with_timezone(&tz);
+  let slug = event_name
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .filter(|part| !part.is_empty())
+    .map(|part| part.to_lowercase())
+    .collect::<Vec<_>>()
+    .join("-");
+  if slug.is_empty() {
+    format!("party-sign-in-{}.csv", now.format("%Y%m%d-%H%M%S"))
+  } else {
+    format!("party-sign-in-{}-{}.csv", slug, now.format("%Y%m%d-%H%M%S"))
+  }
+}
+
+fn desktop_dir_path() -> Result<String> {
+  dirs::desktop_dir()
+    .ok_or_else(|| Error::Invalid("desktop directory unavailable".into()))
+    .and_then(|p| p.into_os_string().into_string().map_err(|_| Error::Invalid("desktop path is not valid UTF-8".into())))
+}
@@ -0,0 +1,399 @@
+//! Changes to individual guests made at the door, outside of a list import.
+//! Edits, deletions and merges go on the undo history like check-ins do.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{record_audit, AuditRecord};
+use crate::banned::{find_ban, load_bans};
+use crate::checkins::{check_in_within, record_guest_undo, require_guest, Overrides, ToggleStatus};
+use crate::error::{Error, Result};
+use crate::events::fetch_event;
+use crate::names::{clean_name, clean_optional};
+
+/// Kinds of undoable guest change, as stored in `undo_history.action`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GuestChange {
+  Edit,
+  Delete,
+  Merge,
+}
+
+impl GuestChange {
+  pub(crate) fn kind(self) -> &'static str {
+    match self {
+      GuestChange::Edit => "edit_guest",
+      GuestChange::Delete => "delete_guest",
+      GuestChange::Merge => "merge_guests",
+    }
+  }
+
+  pub(crate) fn from_kind(kind: &str) -> Option<Self> {
+    [GuestChange::Edit, GuestChange::Delete, GuestChange::Merge]
+      .into_iter()
+      .find(|change| change.kind() == kind)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuestEdit {
+  pub display_name: String,
+  pub member_host: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkInInput {
+  pub display_name: String,
+  pub member_host: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkInResult {
+  /// `CheckedIn`, or the reason the guest was refused (see `toggle_checkin`).
+  pub status: ToggleStatus,
+  /// The new guest; `None` when they were refused, since nothing is kept.
+  pub guest_id: Option<i64>,
+}
+
+/// Adds someone who is not on the list and checks them in, in one step. The
+/// guest is marked as a walk-in added by `operator`. Door limits apply as for
+/// any check-in; if one refuses the guest, they are not added either.
+pub fn add_guest(
+  conn: &mut Connection,
+  event_id: i64,
+  input: WalkInInput,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<WalkInResult> {
+  fetch_event(conn, event_id)?;
+  let display_name = clean_name(&input.display_name)
+    .ok_or_else(|| Error::Invalid("guest name is required".into()))?;
+  let member_host = clean_optional(input.member_host);
+
+  let tx = conn.transaction()?;
+  let listed = tx
+    .query_row(
+      "SELECT 1 FROM guests WHERE event_id = ?1 AND lower(display_name) = lower(?2)
+         AND lower(COALESCE(member_host, '')) = lower(COALESCE(?3, '')) AND removed_at IS NULL",
+      params![event_id, display_name, member_host],
+      |_| Ok(()),
+    )
+    .optional()?
+    .is_some();
+  if listed {
+    return Err(Error::Invalid(format!("{} is already on the guest list", display_name)));
+  }
+
+  let banned_id = find_ban(&load_bans(&tx)?, &display_name).map(|ban| ban.id);
+  tx.execute(
+    "INSERT INTO guests (event_id, display_name, member_host, banned_id, is_walk_in, added_by)
+     VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+    params![event_id, display_name, member_host, banned_id, operator],
+  )?;
+  let guest_id = tx.last_insert_rowid();
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: "add_walk_in",
+      operator,
+      before: None,
+      after: Some(serde_json::json!({ "displayName": display_name, "memberHost": member_host }).to_string()),
+    },
+  )?;
+
+  let status = check_in_within(&tx, event_id, guest_id, operator, overrides)?;
+  if status != ToggleStatus::CheckedIn {
+    return Ok(WalkInResult { status, guest_id: None });
+  }
+  tx.commit()?;
+
+  Ok(WalkInResult {
+    status,
+    guest_id: Some(guest_id),
+  })
+}
+
+/// Corrects a guest's name and host. The guest is matched against the banned
+/// list again under the new name.
+pub fn edit_guest(
+  conn: &mut Connection,
+  event_id: i64,
+  guest_id: i64,
+  edit: GuestEdit,
+  operator: Option<&str>,
+) -> Result<()> {
+  require_guest(conn, event_id, guest_id)?;
+  let display_name = clean_name(&edit.display_name)
+    .ok_or_else(|| Error::Invalid("guest name is required".into()))?;
+  let member_host = clean_optional(edit.member_host);
+
+  let tx = conn.transaction()?;
+  let duplicate: Option<i64> = tx
+    .query_row(
+      "SELECT id FROM guests WHERE event_id = ?1 AND id != ?2 AND lower(display_name) = lower(?3)
+         AND lower(COALESCE(member_host, '')) = lower(COALESCE(?4, ''))",
+      params![event_id, guest_id, display_name, member_host],
+      |row| row.get(0),
+    )
+    .optional()?;
+  if duplicate.is_some() {
+    return Err(Error::Invalid(format!(
+      "{} is already on the guest list; merge the two instead",
+      display_name
+    )));
+  }
+
+  let before = guests_snapshot(&tx, &[guest_id])?;
+  let banned_id = find_ban(&load_bans(&tx)?, &display_name).map(|ban| ban.id);
+  tx.execute(
+    "UPDATE guests SET display_name = ?1, member_host = ?2, banned_id = ?3 WHERE id = ?4",
+    params![display_name, member_host, banned_id, guest_id],
+  )?;
+  let after = guests_snapshot(&tx, &[guest_id])?;
+  finish_change(&tx, event_id, GuestChange::Edit, guest_id, before, after, operator)?;
+  tx.commit()?;
+  Ok(())
+}
+
+/// Removes a guest and their check-ins.
+pub fn delete_guest(conn: &mut Connection, event_id: i64, guest_id: i64, operator: Option<&str>) -> Result<()> {
+  require_guest(conn, event_id, guest_id)?;
+
+  let tx = conn.transaction()?;
+  let before = guests_snapshot(&tx, &[guest_id])?;
+  // Logged first, while the guest's name can still be looked up.
+  finish_change(&tx, event_id, GuestChange::Delete, guest_id, before, empty_snapshot(guest_id), operator)?;
+  tx.execute("DELETE FROM guests WHERE id = ?1", params![guest_id])?;
+  tx.commit()?;
+  Ok(())
+}
+
+/// Folds `duplicate_id` into `survivor_id` when both rows are the same person.
+/// The duplicate's check-ins move to the survivor, who also takes its host if
+/// they had none. If both were checked in, that is one visit recorded twice:
+/// the earliest stays open and the later open ones are dropped.
+pub fn merge_guests(
+  conn: &mut Connection,
+  event_id: i64,
+  survivor_id: i64,
+  duplicate_id: i64,
+  operator: Option<&str>,
+) -> Result<()> {
+  require_guest(conn, event_id, survivor_id)?;
+  require_guest(conn, event_id, duplicate_id)?;
+  if survivor_id == duplicate_id {
+    return Err(Error::Invalid("cannot merge a guest with themselves".into()));
+  }
+
+  let tx = conn.transaction()?;
+  let ids = [survivor_id, duplicate_id];
+  let before = guests_snapshot(&tx, &ids)?;
+  tx.execute(
+    "UPDATE guests SET member_host = (SELECT member_host FROM guests WHERE id = ?2)
+     WHERE id = ?1 AND (member_host IS NULL OR member_host = '')",
+    params![survivor_id, duplicate_id],
+  )?;
+  tx.execute(
+    "UPDATE checkins SET guest_id = ?1 WHERE guest_id = ?2",
+    params![survivor_id, duplicate_id],
+  )?;
+  tx.execute(
+    "DELETE FROM checkins
+     WHERE guest_id = ?1 AND out_ts IS NULL
+       AND id != (SELECT id FROM checkins WHERE guest_id = ?1 AND out_ts IS NULL ORDER BY in_ts, id LIMIT 1)",
+    params![survivor_id],
+  )?;
+  tx.execute("DELETE FROM guests WHERE id = ?1", params![duplicate_id])?;
+  let after = guests_snapshot(&tx, &ids)?;
+  finish_change(&tx, event_id, GuestChange::Merge, survivor_id, before, after, operator)?;
+  tx.commit()?;
+  Ok(())
+}
+
+fn finish_change(
+  conn: &Connection,
+  event_id: i64,
+  change: GuestChange,
+  guest_id: i64,
+  before: String,
+  after: String,
+  operator: Option<&str>,
+) -> Result<()> {
+  record_guest_undo(conn, event_id, change, guest_id, &before, &after, operator)?;
+  record_audit(
+    conn,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: Some(guest_id),
+      action: change.kind(),
+      operator,
+      before: Some(before),
+      after: Some(after),
+    },
+  )
+}
+
+/// JSON copy of the guest rows and check-ins for `guest_ids`, which
+/// [`restore_guests`] puts back exactly. Ids that no longer exist are kept in
+/// `guestIds` so restoring removes them again.
+pub(crate) fn guests_snapshot(conn: &Connection, guest_ids: &[i64]) -> Result<String> {
+  let ids = serde_json::to_string(guest_ids)?;
+  Ok(conn.prepare_cached(
+    "SELECT json_object(
+       'guestIds', json(?1),
+       'guests', (SELECT json_group_array(json_object(
+           'id', id, 'eventId', event_id, 'displayName', display_name, 'memberHost', member_host,
+           'sourceRow', source_row, 'createdAt', created_at, 'bannedId', banned_id,
+           'isWalkIn', is_walk_in, 'addedBy', added_by, 'batchId', batch_id, 'removedAt', removed_at))
+         FROM guests WHERE id IN (SELECT value FROM json_each(?1))),
+       'checkins', (SELECT json_group_array(json_object(
+           'id', id, 'eventId', event_id, 'guestId', guest_id, 'inTs', in_ts, 'outTs', out_ts,
+           'inBy', in_by, 'outBy', out_by, 'batchId', batch_id))
+         FROM checkins WHERE guest_id IN (SELECT value FROM json_each(?1))))",
+  )?
+  .query_row(params![ids], |row| row.get(0))?)
+}
+
+fn empty_snapshot(guest_id: i64) -> String {
+  serde_json::json!({ "guestIds": [guest_id], "guests": [], "checkins": [] }).to_string()
+}
+
+/// Replaces the snapshot's guests, and their check-ins, with the captured
+/// rows. A ban lifted or an import batch deleted since the snapshot is not
+/// brought back with them.
+///
+/// Ids are never reused, so a row holding one of the snapshot's ids should be
+/// the captured guest; if it is not, nothing is overwritten.
+pub(crate) fn restore_guests(conn: &Connection, snapshot: &str) -> Result<()> {
+  let replaced: Option<i64> = conn
+    .query_row(
+      "SELECT g.id FROM json_each(?1, '$.guests') s JOIN guests g ON g.id = s.value ->> 'id'
+       WHERE g.event_id IS NOT s.value ->> 'eventId' OR g.created_at IS NOT s.value ->> 'createdAt'
+       LIMIT 1",
+      params![snapshot],
+      |row| row.get(0),
+    )
+    .optional()?;
+  if let Some(guest_id) = replaced {
+    return Err(Error::Invalid(format!(
+      "guest {} is no longer the guest this change was made to",
+      guest_id
+    )));
+  }
+  conn.execute(
+    "DELETE FROM guests WHERE id IN (SELECT value FROM json_each(?1, '$.guestIds'))",
+    params![snapshot],
+  )?;
+  conn.execute(
+    "INSERT INTO guests (id, event_id, display_name, member_host, source_row, created_at, banned_id, is_walk_in, added_by, batch_id,
+       removed_at)
+     SELECT value ->> 'id', value ->> 'eventId', value ->> 'displayName', value ->> 'memberHost',
+       value ->> 'sourceRow', value ->> 'createdAt',
+       (SELECT b.id FROM banned_guests b WHERE b.id = value ->> 'bannedId'),
+       value ->> 'isWalkIn', value ->> 'addedBy',
+       (SELECT ib.id FROM import_batches ib WHERE ib.id = value ->> 'batchId'), value ->> 'removedAt'
+     FROM json_each(?1, '$.guests')",
+    params![snapshot],
+  )?;
+  conn.execute(
+    "INSERT INTO checkins (id, event_id, guest_id, in_ts, out_ts, in_by, out_by, batch_id)
+     SELECT value ->> 'id', value ->> 'eventId', value ->> 'guestId', value ->> 'inTs', value ->> 'outTs',
+       value ->> 'inBy', value ->> 'outBy',
+       (SELECT ib.id FROM import_batches ib WHERE ib.id = value ->> 'batchId')
+     FROM json_each(?1, '$.checkins')",
+    params![snapshot],
+  )?;
+  Ok(())
+}
+
+/// A guest row as captured by [`guests_snapshot`].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotGuest {
+  id: i64,
+  event_id: i64,
+  display_name: String,
+  member_host: Option<String>,
+  source_row: Option<i64>,
+  created_at: Option<String>,
+  banned_id: Option<i64>,
+  is_walk_in: i64,
+  added_by: Option<String>,
+  batch_id: Option<i64>,
+  // Missing from snapshots taken before guests could be removed.
+  #[serde(default)]
+  removed_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotCheckin {
+  event_id: i64,
+  guest_id: i64,
+  in_ts: String,
+  out_ts: Option<String>,
+  in_by: Option<String>,
+  out_by: Option<String>,
+  batch_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+  guests: Vec<SnapshotGuest>,
+  checkins: Vec<SnapshotCheckin>,
+}
+
+/// Adds the snapshot's guests, and their check-ins, back as new rows. Unlike
+/// [`restore_guests`] the old ids are not reused, since other guests may have
+/// taken them since. Returns how many guests were added.
+pub(crate) fn copy_guests(conn: &Connection, snapshot: &str) -> Result<usize> {
+  let snapshot: Snapshot = serde_json::from_str(snapshot)?;
+  let mut new_ids = HashMap::with_capacity(snapshot.guests.len());
+  let mut guest_stmt = conn.prepare_cached(
+    "INSERT INTO guests (event_id, display_name, member_host, source_row, created_at, banned_id, is_walk_in, added_by, batch_id,
+       removed_at)
+     VALUES (?1, ?2, ?3, ?4, ?5, (SELECT b.id FROM banned_guests b WHERE b.id = ?6), ?7, ?8,
+       (SELECT ib.id FROM import_batches ib WHERE ib.id = ?9), ?10)",
+  )?;
+  for guest in &snapshot.guests {
+    guest_stmt.execute(params![
+      guest.event_id,
+      guest.display_name,
+      guest.member_host,
+      guest.source_row,
+      guest.created_at,
+      guest.banned_id,
+      guest.is_walk_in,
+      guest.added_by,
+      guest.batch_id,
+      guest.removed_at,
+    ])?;
+    new_ids.insert(guest.id, conn.last_insert_rowid());
+  }
+
+  let mut checkin_stmt = conn.prepare_cached(
+    "INSERT INTO checkins (event_id, guest_id, in_ts, out_ts, in_by, out_by, batch_id)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, (SELECT ib.id FROM import_batches ib WHERE ib.id = ?7))",
+  )?;
+  for checkin in &snapshot.checkins {
+    if let Some(guest_id) = new_ids.get(&checkin.guest_id) {
+      checkin_stmt.execute(params![
+        checkin.event_id,
+        guest_id,
+        checkin.in_ts,
+        checkin.out_ts,
+        checkin.in_by,
+        checkin.out_by,
+        checkin.batch_id,
+      ])?;
+    }
+  }
+  Ok(snapshot.guests.len())
+}
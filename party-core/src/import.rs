@@ -0,0 +1,839 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::audit::{event_counts, record_audit, AuditRecord};
+use crate::banned::{find_ban, load_bans};
+use crate::batches::{archive_guests, finish_batch, start_batch, ImportSource, SyncArchive};
+use crate::checkins::Overrides;
+use crate::events::fetch_event;
+use crate::quotas::{listed_guests, quota_for};
+use crate::name_list::{parse_name_list, Ambiguity, NameList};
+use crate::names::clean_whitespace;
+use crate::profiles::{find_profile, ColumnMapping};
+use crate::time::{ensure_after, event_day, now_timestamp, parse_import_timestamp, EventDay};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CsvRow {
+  #[serde(rename = "memberName")]
+  pub member_name: Option<String>,
+  #[serde(rename = "guestNames")]
+  pub guest_names: Option<String>,
+  #[serde(rename = "sourceRow")]
+  pub source_row: Option<i64>,
+  #[serde(rename = "checkIn")]
+  pub check_in: Option<String>,
+  #[serde(rename = "checkInTime")]
+  pub check_in_time: Option<String>,
+  #[serde(rename = "checkOut")]
+  pub check_out: Option<String>,
+  #[serde(rename = "checkOutTime")]
+  pub check_out_time: Option<String>,
+  /// Marks the guests as walk-ins, as exports do for guests added at the door.
+  #[serde(rename = "walkIn")]
+  pub walk_in: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+  Replace,
+  Append,
+  /// Brings the list in line with the sheet: adds new guests, moves guests
+  /// whose member changed, and marks guests missing from the sheet as
+  /// removed. Check-in history is left alone.
+  Sync,
+}
+
+impl ImportMode {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ImportMode::Replace => "replace",
+      ImportMode::Append => "append",
+      ImportMode::Sync => "sync",
+    }
+  }
+}
+
+/// A guest list file read into rows, with where it came from.
+#[derive(Debug)]
+pub struct ImportFile {
+  pub source: ImportSource,
+  pub rows: Vec<CsvRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+  /// The batch recording this import; `None` in a preview.
+  pub batch_id: Option<i64>,
+  pub inserted: usize,
+  pub total_rows: usize,
+  /// Guests past their member's guest quota. They are left off the list
+  /// unless the import overrides quotas.
+  pub over_quota: Vec<QuotaOverflow>,
+  /// Imported guests whose names match the banned list. They are added but
+  /// flagged, so the door refuses them.
+  pub banned: Vec<BannedMatch>,
+  /// The list a Replace import cleared; `None` for an append.
+  pub replaced: Option<ReplacedList>,
+  /// How a Sync import changed the list; `None` for the other modes.
+  pub synced: Option<SyncCounts>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncCounts {
+  /// New guests, and removed guests back on the sheet.
+  pub added: usize,
+  /// Guests now listed under a different member.
+  pub updated: usize,
+  /// Guests missing from the sheet. Walk-ins are never removed.
+  pub removed: usize,
+  pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplacedList {
+  /// The archive holding the removed rows; `None` in a preview.
+  pub archive_id: Option<i64>,
+  pub guests: usize,
+  pub checkins: usize,
+  /// Guests who were still checked in.
+  pub checked_in: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaOverflow {
+  pub source_row: Option<i64>,
+  pub member_name: String,
+  pub guest_name: String,
+  pub quota: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BannedMatch {
+  pub source_row: Option<i64>,
+  pub guest_name: String,
+  pub banned_name: String,
+  pub reason: Option<String>,
+}
+
+/// What an import would do, row by row, without writing anything.
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+  /// The totals the import would report.
+  pub summary: ImportSummary,
+  pub rows: Vec<RowPreview>,
+  /// Guests a Sync import would mark as removed.
+  pub removed: Vec<ExistingGuest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowPreview {
+  pub source_row: Option<i64>,
+  /// The member name as it would be stored on the row's guests.
+  pub member_host: Option<String>,
+  /// Every name split out of the row's guest column, in order.
+  pub guests: Vec<GuestPreview>,
+  /// The visit recorded for each new guest from the row's check-in columns.
+  pub history: Option<HistoryPreview>,
+  /// Times that could not be read; the import treats them as blank.
+  pub unparsed_check_in_time: Option<String>,
+  pub unparsed_check_out_time: Option<String>,
+  /// Guesses made splitting the guest column, for someone to check.
+  pub ambiguities: Vec<Ambiguity>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuestPreview {
+  pub name: String,
+  pub outcome: GuestOutcome,
+  /// The guest already listed under this name and host, for duplicates; the
+  /// guest a Sync matched, as it was before, for the sync outcomes.
+  pub duplicate_of: Option<ExistingGuest>,
+  /// The banned-list entry the name matches. The guest is still added.
+  pub banned_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuestOutcome {
+  Added,
+  Duplicate,
+  /// Left off for the member's guest quota (see `ImportSummary::over_quota`).
+  OverQuota,
+  /// Already listed and left as is by a Sync.
+  Unchanged,
+  /// Already listed; a Sync moves them to the row's member.
+  Updated,
+  /// Removed by an earlier Sync and back on the sheet.
+  Restored,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExistingGuest {
+  pub guest_id: i64,
+  pub display_name: String,
+  pub member_host: Option<String>,
+  /// The file row the guest came from; an earlier row of this same file when
+  /// a name is listed twice.
+  pub source_row: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryPreview {
+  pub in_ts: String,
+  pub out_ts: Option<String>,
+}
+
+/// Adds the rows' guests to the event. Each member's guest quota is applied
+/// in row order; `overrides.quota` admits the guests past it (and audits that).
+/// A Replace archives the current list before clearing it, and refuses while
+/// anyone is checked in unless `overrides.checked_in` is set.
+pub fn import_rows(
+  conn: &mut Connection,
+  event_id: i64,
+  rows: &[CsvRow],
+  mode: ImportMode,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<ImportSummary> {
+  run_import(conn, event_id, rows, &ImportSource::default(), mode, operator, overrides)
+}
+
+/// [`import_rows`] for a file read with [`read_import_file`], recording the
+/// file's name and hash on the batch.
+pub fn import_file(
+  conn: &mut Connection,
+  event_id: i64,
+  file: &ImportFile,
+  mode: ImportMode,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<ImportSummary> {
+  run_import(conn, event_id, &file.rows, &file.source, mode, operator, overrides)
+}
+
+fn run_import(
+  conn: &mut Connection,
+  event_id: i64,
+  rows: &[CsvRow],
+  source: &ImportSource,
+  mode: ImportMode,
+  operator: Option<&str>,
+  overrides: Overrides,
+) -> Result<ImportSummary> {
+  let day = event_day(&fetch_event(conn, event_id)?);
+
+  let tx = conn.transaction()?;
+  let (guests_before, checkins_before) = event_counts(&tx, event_id)?;
+  let batch_id = start_batch(&tx, event_id, source, mode, operator)?;
+  let ImportPreview { summary, .. } = apply_rows(&tx, event_id, &day, rows, mode, Some(batch_id), overrides)?;
+  finish_batch(&tx, batch_id, rows.len(), summary.inserted)?;
+
+  if overrides.quota && !summary.over_quota.is_empty() {
+    record_audit(
+      &tx,
+      AuditRecord {
+        event_id: Some(event_id),
+        guest_id: None,
+        action: "quota_override",
+        operator,
+        before: None,
+        after: Some(serde_json::json!({ "guests": summary.over_quota }).to_string()),
+      },
+    )?;
+  }
+
+  let (guests_after, checkins_after) = event_counts(&tx, event_id)?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: None,
+      action: "import",
+      operator,
+      before: Some(
+        serde_json::json!({ "guests": guests_before, "checkins": checkins_before }).to_string(),
+      ),
+      after: Some(
+        serde_json::json!({
+          "batchId": batch_id,
+          "fileName": source.file_name,
+          "mode": mode.as_str(),
+          "totalRows": rows.len(),
+          "inserted": summary.inserted,
+          "overQuota": summary.over_quota.len(),
+          "banned": summary.banned.len(),
+          "archiveId": summary.replaced.as_ref().and_then(|replaced| replaced.archive_id),
+          "synced": summary.synced.as_ref().map(|synced| {
+            serde_json::json!({
+              "added": synced.added,
+              "updated": synced.updated,
+              "removed": synced.removed,
+              "unchanged": synced.unchanged,
+            })
+          }),
+          "guests": guests_after,
+          "checkins": checkins_after,
+        })
+        .to_string(),
+      ),
+    },
+  )?;
+
+  tx.commit()?;
+  Ok(summary)
+}
+
+/// Runs the import exactly as [`import_rows`] would and reports what each row
+/// does, then rolls it all back. History times filled in with "now" are the
+/// preview's own clock, so they shift slightly on the real import. A Replace
+/// is previewed even while guests are checked in, so it can report them.
+pub fn preview_import(
+  conn: &mut Connection,
+  event_id: i64,
+  rows: &[CsvRow],
+  mode: ImportMode,
+  overrides: Overrides,
+) -> Result<ImportPreview> {
+  let day = event_day(&fetch_event(conn, event_id)?);
+  let tx = conn.transaction()?;
+  // Dropped without committing.
+  apply_rows(&tx, event_id, &day, rows, mode, None, overrides)
+}
+
+fn apply_rows(
+  tx: &Transaction<'_>,
+  event_id: i64,
+  day: &EventDay,
+  rows: &[CsvRow],
+  mode: ImportMode,
+  batch_id: Option<i64>,
+  overrides: Overrides,
+) -> Result<ImportPreview> {
+  let mut inserted = 0usize;
+  let mut over_quota = Vec::new();
+  let mut banned = Vec::new();
+  let mut previews = Vec::with_capacity(rows.len());
+  // Lower-cased member name -> (guest quota, guests listed so far).
+  let mut member_counts: HashMap<String, (Option<i64>, i64)> = HashMap::new();
+
+  let bans = load_bans(tx)?;
+
+  let replaced = match mode {
+    ImportMode::Replace => {
+      let archive = archive_guests(tx, event_id, batch_id)?;
+      // A preview goes ahead so it can show who would be cleared.
+      if archive.checked_in > 0 && batch_id.is_some() && !overrides.checked_in {
+        return Err(Error::GuestsCheckedIn(archive.checked_in as i64));
+      }
+      tx.execute("DELETE FROM guests WHERE event_id = ?1", params![event_id])?;
+      // Nothing left to undo once the guests it refers to are gone.
+      tx.execute("DELETE FROM undo_history WHERE event_id = ?1", params![event_id])?;
+      Some(ReplacedList {
+        archive_id: batch_id.map(|_| archive.id),
+        guests: archive.guests,
+        checkins: archive.checkins,
+        checked_in: archive.checked_in,
+      })
+    }
+    ImportMode::Append | ImportMode::Sync => None,
+  };
+  // Guests the sheet lists, for a Sync to tell who is missing from it.
+  let mut listed_ids = HashSet::new();
+  let mut sync_archive = SyncArchive::default();
+  let mut sync_counts = SyncCounts::default();
+
+  {
+    let mut insert_stmt = tx.prepare_cached(
+      "INSERT INTO guests(event_id, display_name, member_host, source_row, is_walk_in, batch_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+    )?;
+    // Guests a Sync removed are off the list, so listing them again adds them.
+    let mut exists_stmt = tx.prepare_cached(
+      "SELECT id, display_name, member_host, source_row FROM guests WHERE event_id = ?3 AND lower(display_name) = lower(?1) AND (
+        ( ?2 IS NULL AND member_host IS NULL ) OR lower(COALESCE(member_host, '')) = lower(COALESCE(?2, ''))
+      ) AND removed_at IS NULL"
+    )?;
+
+    for row in rows.iter() {
+      let check_in_flag = parse_import_flag(row.check_in.as_deref());
+      let check_out_flag = parse_import_flag(row.check_out.as_deref());
+      let walk_in = parse_import_flag(row.walk_in.as_deref());
+      let check_in_time = parse_import_timestamp(row.check_in_time.as_deref(), day);
+      let check_out_time = parse_import_timestamp(row.check_out_time.as_deref(), day)
+        .map(|out| ensure_after(out, check_in_time.as_deref()));
+      let unparsed = |raw: &Option<String>, parsed: &Option<String>| {
+        raw.as_deref().map(str::trim).filter(|raw| !raw.is_empty() && parsed.is_none()).map(String::from)
+      };
+      let history = import_history(
+        check_in_flag,
+        check_out_flag,
+        check_in_time.as_deref(),
+        check_out_time.as_deref(),
+      );
+
+      let host_clean = row.member_name.as_ref().map(|s| clean_whitespace(s));
+      let host_ref = host_clean.as_deref();
+      let NameList { names, ambiguities } = row
+        .guest_names
+        .as_deref()
+        .map(parse_name_list)
+        .unwrap_or_default();
+
+      let mut preview = RowPreview {
+        source_row: row.source_row,
+        member_host: host_clean.clone(),
+        guests: Vec::new(),
+        history: None,
+        unparsed_check_in_time: unparsed(&row.check_in_time, &check_in_time),
+        unparsed_check_out_time: unparsed(&row.check_out_time, &check_out_time),
+        ambiguities,
+      };
+
+      for display in names {
+        // A guest a Sync moves to this member or puts back on the list joins
+        // it like a new one, so goes through the same quota and ban checks.
+        let synced = match mode {
+          ImportMode::Sync => sync_guest(tx, event_id, &display, host_ref, &mut listed_ids)?,
+          _ => None,
+        };
+        let exists = match &synced {
+          Some((GuestOutcome::Updated | GuestOutcome::Restored, _)) => None,
+          Some((outcome, existing)) => Some((*outcome, existing.clone())),
+          None => exists_stmt
+            .query_row(params![display.as_str(), host_ref, event_id], |row| {
+              Ok(ExistingGuest {
+                guest_id: row.get(0)?,
+                display_name: row.get(1)?,
+                member_host: row.get(2)?,
+                source_row: row.get(3)?,
+              })
+            })
+            .optional()?
+            .map(|existing| (GuestOutcome::Duplicate, existing)),
+        };
+        if let Some((outcome, existing)) = exists {
+          if outcome == GuestOutcome::Unchanged {
+            sync_counts.unchanged += 1;
+          }
+          preview.guests.push(GuestPreview {
+            name: display,
+            outcome,
+            duplicate_of: Some(existing),
+            banned_name: None,
+          });
+          continue;
+        }
+
+        if let Some(host) = host_ref.filter(|host| !host.is_empty()) {
+          let key = host.to_lowercase();
+          if !member_counts.contains_key(&key) {
+            let limit = quota_for(tx, event_id, host)?.guests;
+            member_counts.insert(key.clone(), (limit, listed_guests(tx, event_id, host)?));
+          }
+          let (limit, listed) = member_counts.get_mut(&key).expect("member counted above");
+          if let Some(limit) = *limit {
+            if *listed >= limit {
+              over_quota.push(QuotaOverflow {
+                source_row: row.source_row,
+                member_name: host.to_string(),
+                guest_name: display.clone(),
+                quota: limit,
+              });
+              if !overrides.quota {
+                preview.guests.push(GuestPreview {
+                  name: display,
+                  outcome: GuestOutcome::OverQuota,
+                  duplicate_of: synced.map(|(_, existing)| existing),
+                  banned_name: None,
+                });
+                continue;
+              }
+            }
+          }
+          *listed += 1;
+        }
+
+        let guest_id = match &synced {
+          Some((outcome, existing)) => {
+            sync_archive.record(tx, existing.guest_id)?;
+            tx.execute(
+              "UPDATE guests SET member_host = ?1, removed_at = NULL WHERE id = ?2",
+              params![host_ref, existing.guest_id],
+            )?;
+            match outcome {
+              GuestOutcome::Restored => sync_counts.added += 1,
+              _ => sync_counts.updated += 1,
+            }
+            existing.guest_id
+          }
+          None => {
+            insert_stmt.execute(params![event_id, display.as_str(), host_ref, row.source_row, walk_in, batch_id])?;
+            inserted += 1;
+            let guest_id = tx.last_insert_rowid();
+            listed_ids.insert(guest_id);
+            guest_id
+          }
+        };
+        let ban = find_ban(&bans, &display);
+        if let Some(ban) = ban {
+          tx.execute("UPDATE guests SET banned_id = ?1 WHERE id = ?2", params![ban.id, guest_id])?;
+          banned.push(BannedMatch {
+            source_row: row.source_row,
+            guest_name: display.clone(),
+            banned_name: ban.name.clone(),
+            reason: ban.reason.clone(),
+          });
+        }
+        if let Some((in_ts, out_ts)) = history.as_ref().filter(|_| synced.is_none()) {
+          tx.execute(
+            "INSERT INTO checkins (event_id, guest_id, in_ts, out_ts, in_by, out_by, batch_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![event_id, guest_id, in_ts, out_ts, "import", out_ts.as_ref().map(|_| "import"), batch_id],
+          )?;
+          preview.history = Some(HistoryPreview {
+            in_ts: in_ts.clone(),
+            out_ts: out_ts.clone(),
+          });
+        }
+        let (outcome, duplicate_of) = match synced {
+          Some((outcome, existing)) => (outcome, Some(existing)),
+          None => (GuestOutcome::Added, None),
+        };
+        preview.guests.push(GuestPreview {
+          name: display,
+          outcome,
+          duplicate_of,
+          banned_name: ban.map(|ban| ban.name.clone()),
+        });
+      }
+      previews.push(preview);
+    }
+  }
+
+  let (removed, synced) = match mode {
+    ImportMode::Sync => {
+      let removed = remove_unlisted(tx, event_id, &listed_ids, &mut sync_archive)?;
+      sync_archive.save(tx, event_id, batch_id)?;
+      sync_counts.added += inserted;
+      sync_counts.removed = removed.len();
+      (removed, Some(sync_counts))
+    }
+    _ => (Vec::new(), None),
+  };
+
+  Ok(ImportPreview {
+    summary: ImportSummary {
+      batch_id,
+      inserted,
+      total_rows: rows.len(),
+      over_quota,
+      banned,
+      replaced,
+      synced,
+    },
+    rows: previews,
+    removed,
+  })
+}
+
+/// Matches a sheet name to a guest already in the event for a Sync, and says
+/// what bringing that guest in line with the row changes; the caller applies
+/// it. Names match case-insensitively; of several guests with the name, one
+/// under the row's member wins. Returns `None` when the name is new, or is a
+/// repeat of a guest the sheet already listed under another member, so the
+/// caller adds it.
+fn sync_guest(
+  tx: &Transaction<'_>,
+  event_id: i64,
+  display: &str,
+  host: Option<&str>,
+  listed_ids: &mut HashSet<i64>,
+) -> Result<Option<(GuestOutcome, ExistingGuest)>> {
+  let mut stmt = tx.prepare_cached(
+    "SELECT id, display_name, member_host, source_row, removed_at IS NOT NULL FROM guests
+     WHERE event_id = ?1 AND lower(display_name) = lower(?2)
+     ORDER BY lower(COALESCE(member_host, '')) != lower(COALESCE(?3, '')), removed_at IS NOT NULL, id",
+  )?;
+  let candidates = stmt
+    .query_map(params![event_id, display, host], |row| {
+      Ok((
+        ExistingGuest {
+          guest_id: row.get(0)?,
+          display_name: row.get(1)?,
+          member_host: row.get(2)?,
+          source_row: row.get(3)?,
+        },
+        row.get::<_, bool>(4)?,
+      ))
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+  let same_host = |guest: &ExistingGuest| {
+    guest.member_host.as_deref().unwrap_or("").eq_ignore_ascii_case(host.unwrap_or(""))
+  };
+  // Listed twice under the same member: a duplicate, as in an append.
+  if let Some((earlier, _)) = candidates
+    .iter()
+    .find(|(guest, _)| listed_ids.contains(&guest.guest_id) && same_host(guest))
+  {
+    return Ok(Some((GuestOutcome::Duplicate, earlier.clone())));
+  }
+  let Some((existing, removed)) = candidates
+    .into_iter()
+    .find(|(guest, _)| !listed_ids.contains(&guest.guest_id))
+  else {
+    return Ok(None);
+  };
+  listed_ids.insert(existing.guest_id);
+
+  let outcome = match (removed, !same_host(&existing)) {
+    (true, _) => GuestOutcome::Restored,
+    (false, true) => GuestOutcome::Updated,
+    (false, false) => GuestOutcome::Unchanged,
+  };
+  Ok(Some((outcome, existing)))
+}
+
+/// Marks the event's guests the sheet did not list as removed, leaving
+/// walk-ins alone since they were never on a sheet.
+fn remove_unlisted(
+  tx: &Transaction<'_>,
+  event_id: i64,
+  listed_ids: &HashSet<i64>,
+  archive: &mut SyncArchive,
+) -> Result<Vec<ExistingGuest>> {
+  let listed = serde_json::to_string(listed_ids)?;
+  let removed = tx
+    .prepare_cached(
+      "SELECT id, display_name, member_host, source_row FROM guests
+       WHERE event_id = ?1 AND removed_at IS NULL AND is_walk_in = 0
+         AND id NOT IN (SELECT value FROM json_each(?2))
+       ORDER BY display_name",
+    )?
+    .query_map(params![event_id, listed], |row| {
+      Ok(ExistingGuest {
+        guest_id: row.get(0)?,
+        display_name: row.get(1)?,
+        member_host: row.get(2)?,
+        source_row: row.get(3)?,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  let now = now_timestamp();
+  for guest in &removed {
+    archive.record(tx, guest.guest_id)?;
+    tx.execute("UPDATE guests SET removed_at = ?1 WHERE id = ?2", params![now, guest.guest_id])?;
+  }
+  Ok(removed)
+}
+
+/// Reads a guest list exported by the app (or a sheet with the same
+/// headers) into import rows. Header names are matched case-insensitively.
+pub fn read_csv<R: Read>(mut reader: R) -> Result<Vec<CsvRow>> {
+  let mut bytes = Vec::new();
+  reader.read_to_end(&mut bytes)?;
+  read_sheet(&bytes, None)
+}
+
+/// Reads the guest list file at `path` with the named profile's columns, or
+/// the app's own export headers when `profile` is `None`.
+pub fn read_import_file(conn: &Connection, path: &str, profile: Option<&str>) -> Result<ImportFile> {
+  let columns = profile.map(|name| find_profile(conn, name)).transpose()?.map(|profile| profile.columns);
+  let bytes = read_file(path)?;
+  Ok(ImportFile {
+    rows: read_sheet(&bytes, columns.as_ref())?,
+    source: ImportSource::from_file(path, &bytes),
+  })
+}
+
+/// The header row of the file at `path`, for setting up a profile.
+pub fn read_headers(path: &str) -> Result<Vec<String>> {
+  let (text, delimiter) = decode_sheet(&read_file(path)?);
+  let mut rdr = csv::ReaderBuilder::new().delimiter(delimiter).flexible(true).from_reader(text.as_bytes());
+  Ok(rdr.headers()?.iter().map(|h| h.trim().to_string()).collect())
+}
+
+/// Reads a sheet saved as CSV, TSV or semicolon-separated text, in UTF-8
+/// (with or without a byte-order mark), UTF-16 or Windows-1252 as Excel writes
+/// them. `columns` picks the fields; without it the export headers are used.
+pub fn read_sheet(bytes: &[u8], columns: Option<&ColumnMapping>) -> Result<Vec<CsvRow>> {
+  let (text, delimiter) = decode_sheet(bytes);
+  let mut rdr = csv::ReaderBuilder::new()
+    .delimiter(delimiter)
+    .flexible(true)
+    .from_reader(text.as_bytes());
+  let headers: Vec<String> = rdr
+    .headers()?
+    .iter()
+    .map(|h| h.trim().to_lowercase())
+    .collect();
+  let column = |names: &[&str]| {
+    headers
+      .iter()
+      .position(|h| names.iter().any(|name| h == name))
+  };
+  let mapped = |header: &Option<String>| -> Result<Option<usize>> {
+    let Some(header) = header else {
+      return Ok(None);
+    };
+    column(&[header.to_lowercase().as_str()])
+      .map(Some)
+      .ok_or_else(|| Error::Invalid(format!("the file has no {:?} column", header)))
+  };
+
+  let (member, guests, check_in, check_in_time, check_out, check_out_time, walk_in) = match columns {
+    Some(columns) => (
+      mapped(&columns.member_name)?,
+      mapped(&columns.guest_names)?,
+      mapped(&columns.check_in)?,
+      mapped(&columns.check_in_time)?,
+      mapped(&columns.check_out)?,
+      mapped(&columns.check_out_time)?,
+      mapped(&columns.walk_in)?,
+    ),
+    None => (
+      column(&["member name", "member_name"]),
+      column(&["guest names", "guest_names", "guest name"]),
+      column(&["check in y/n", "check_in_y/n", "check_in_y_n"]),
+      column(&["check in time", "check_in_time"]),
+      column(&["check out y/n", "check_out_y/n", "check_out_y_n"]),
+      column(&["check out time", "check_out_time"]),
+      column(&["walk-in y/n", "walk_in", "walk in y/n"]),
+    ),
+  };
+  if guests.is_none() {
+    return Err(Error::Invalid(
+      "the file has no guest names column; import it with a profile that names one".into(),
+    ));
+  }
+
+  let mut rows = Vec::new();
+  for (index, record) in rdr.records().enumerate() {
+    let record = record?;
+    let pull = |col: Option<usize>| {
+      col
+        .and_then(|i| record.get(i))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    };
+    rows.push(CsvRow {
+      member_name: pull(member),
+      guest_names: pull(guests),
+      source_row: Some(index as i64 + 2),
+      check_in: pull(check_in),
+      check_in_time: pull(check_in_time),
+      check_out: pull(check_out),
+      check_out_time: pull(check_out_time),
+      walk_in: pull(walk_in),
+    });
+  }
+  Ok(rows)
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>> {
+  std::fs::read(path).map_err(|err| Error::Invalid(format!("could not read {}: {}", path, err)))
+}
+
+/// The sheet's text and the delimiter it uses. Excel's `sep=;` first line is
+/// honoured and dropped; otherwise the delimiter is whichever of comma, tab,
+/// semicolon and pipe appears most in the header row.
+fn decode_sheet(bytes: &[u8]) -> (String, u8) {
+  let text = decode_text(bytes);
+  let first_line = text.lines().next().unwrap_or("");
+  if let Some(sep) = first_line.strip_prefix("sep=").filter(|sep| sep.trim().len() == 1) {
+    let delimiter = sep.trim().as_bytes()[0];
+    let rest = text[first_line.len()..].trim_start_matches(['\r', '\n']).to_string();
+    return (rest, delimiter);
+  }
+
+  let mut counts = [(b',', 0), (b'\t', 0), (b';', 0), (b'|', 0)];
+  let mut quoted = false;
+  for byte in first_line.bytes() {
+    if byte == b'"' {
+      quoted = !quoted;
+    } else if !quoted {
+      if let Some(count) = counts.iter_mut().find(|(delimiter, _)| *delimiter == byte) {
+        count.1 += 1;
+      }
+    }
+  }
+  // Ties go to the earlier entry, so a header with no delimiter reads as CSV.
+  let delimiter = counts
+    .iter()
+    .fold((b',', 0), |best, &count| if count.1 > best.1 { count } else { best })
+    .0;
+  (text, delimiter)
+}
+
+fn decode_text(bytes: &[u8]) -> String {
+  let utf16 = |bytes: &[u8], unit: fn([u8; 2]) -> u16| {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]])).collect();
+    String::from_utf16_lossy(&units)
+  };
+  if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+    return String::from_utf8_lossy(rest).into_owned();
+  }
+  if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+    return utf16(rest, u16::from_le_bytes);
+  }
+  if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+    return utf16(rest, u16::from_be_bytes);
+  }
+  match std::str::from_utf8(bytes) {
+    Ok(text) => text.to_string(),
+    // Not UTF-8, so most likely Excel's "CSV" on Windows: Windows-1252.
+    Err(_) => bytes.iter().map(|&byte| windows_1252(byte)).collect(),
+  }
+}
+
+fn windows_1252(byte: u8) -> char {
+  const HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+  ];
+  match byte {
+    0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+    _ => char::from(byte),
+  }
+}
+
+fn parse_import_flag(value: Option<&str>) -> bool {
+  value
+    .map(|v| v.trim().to_lowercase())
+    .filter(|v| !v.is_empty())
+    .map(|v| matches!(v.as_str(), "y" | "yes" | "true" | "1" | "checked" | "in"))
+    .unwrap_or(false)
+}
+
+/// The `(in_ts, out_ts)` visit a row's check-in columns describe, if any.
+fn import_history(
+  check_in_flag: bool,
+  check_out_flag: bool,
+  check_in_time: Option<&str>,
+  check_out_time: Option<&str>,
+) -> Option<(String, Option<String>)> {
+  if !(check_in_flag || check_out_flag || check_in_time.is_some() || check_out_time.is_some()) {
+    return None;
+  }
+
+  let mut in_ts = check_in_time.map(|s| s.to_string());
+  if in_ts.is_none() && (check_in_flag || check_out_flag || check_out_time.is_some()) {
+    in_ts = check_out_time.map(|s| s.to_string()).or_else(|| Some(now_timestamp()));
+  }
+
+  let mut out_ts = None;
+  if check_out_flag || check_out_time.is_some() {
+    let value = check_out_time.or(in_ts.as_deref()).unwrap_or("");
+    let formatted = if value.is_empty() {
+      now_timestamp()
+    } else {
+      value.to_string()
+    };
+    out_ts = Some(formatted);
+  }
+
+  Some((in_ts.unwrap_or_else(now_timestamp), out_ts))
+}
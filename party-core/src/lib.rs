@@ -0,0 +1,24 @@
+//! Guest lists, check-ins and reports for Party Sign-In. The desktop app and
+//! `party-cli` are thin front ends over this crate.
+
+pub mod audit;
+pub mod banned;
+pub mod batches;
+pub mod checkins;
+pub mod db;
+mod error;
+pub mod events;
+pub mod export;
+pub mod guests;
+pub mod import;
+mod migrations;
+pub mod name_list;
+pub mod names;
+pub mod phonetic;
+pub mod profiles;
+pub mod quotas;
+pub mod search;
+pub mod stats;
+pub mod time;
+
+pub use error::{Error, Result};
@@ -0,0 +1,355 @@
+//! Schema migrations keyed on `PRAGMA user_version`.
+//!
+//! Each step runs in its own transaction and bumps `user_version` on commit,
+//! so a database is always at exactly one known version. Steps are append-only:
+//! never edit one that has shipped, add a new one instead.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+
+use crate::error::{Error, Result};
+use crate::names::name_key;
+use crate::time::{ensure_after, parse_import_timestamp, EventDay, DEFAULT_TIMEZONE};
+
+struct Migration {
+  description: &'static str,
+  up: fn(&Transaction<'_>) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+  Migration {
+    description: "guests, checkins and search index",
+    up: initial_schema,
+  },
+  Migration {
+    description: "events",
+    up: events,
+  },
+  Migration {
+    description: "RFC 3339 check-in timestamps",
+    up: rfc3339_timestamps,
+  },
+  Migration {
+    description: "undo history",
+    up: undo_history,
+  },
+  Migration {
+    description: "audit log",
+    up: audit_log,
+  },
+  Migration {
+    description: "event capacity",
+    up: event_capacity,
+  },
+  Migration {
+    description: "member quotas",
+    up: member_quotas,
+  },
+  Migration {
+    description: "banned guests",
+    up: banned_guests,
+  },
+  Migration {
+    description: "guest sound keys",
+    up: guest_sounds,
+  },
+  Migration {
+    description: "accent-folded search index",
+    up: folded_search_index,
+  },
+  Migration {
+    description: "member index",
+    up: members,
+  },
+  Migration {
+    description: "walk-in guests",
+    up: walk_ins,
+  },
+  Migration {
+    description: "undoable guest changes",
+    up: guest_undo,
+  },
+  Migration {
+    description: "import profiles",
+    up: import_profiles,
+  },
+  Migration {
+    description: "import batches",
+    up: import_batches,
+  },
+  Migration {
+    description: "replace archives",
+    up: import_archives,
+  },
+  Migration {
+    description: "removed guests",
+    up: removed_guests,
+  },
+  Migration {
+    description: "ids never reused",
+    up: stable_ids,
+  },
+];
+
+pub const LATEST_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Brings the database up to [`LATEST_VERSION`], refusing databases written by
+/// a newer build of the app.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+  let current: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+  if current > LATEST_VERSION {
+    return Err(Error::SchemaTooNew {
+      found: current,
+      supported: LATEST_VERSION,
+    });
+  }
+  if current == LATEST_VERSION {
+    return Ok(());
+  }
+
+  // A step that rebuilds a table drops the old one, which with foreign keys
+  // on would cascade into the rows that point at it. The pragma cannot change
+  // inside a transaction, so it is off for the whole run.
+  let foreign_keys: bool = conn.pragma_query_value(None, "foreign_keys", |row| row.get(0))?;
+  conn.pragma_update(None, "foreign_keys", false)?;
+  let result = apply_migrations(conn, current);
+  conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+  result
+}
+
+fn apply_migrations(conn: &mut Connection, current: i64) -> Result<()> {
+  for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+    let version = index as i64 + 1;
+    // Another connection may be opening the same database; take the write
+    // lock first and skip the step if it got there before us.
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let applied: i64 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if applied >= version {
+      continue;
+    }
+    (migration.up)(&tx).map_err(|error| Error::Migration {
+      version,
+      description: migration.description,
+      error: Box::new(error),
+    })?;
+    tx.pragma_update(None, "user_version", version)?;
+    tx.commit()?;
+  }
+
+  Ok(())
+}
+
+// Databases from before migrations existed already have these tables, so the
+// statements stay `IF NOT EXISTS`.
+fn initial_schema(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0001_initial.sql"))?;
+  Ok(())
+}
+
+fn events(tx: &Transaction<'_>) -> Result<()> {
+  add_column_if_missing(tx, "guests", "event_id", "INTEGER REFERENCES events(id) ON DELETE CASCADE")?;
+  add_column_if_missing(tx, "checkins", "event_id", "INTEGER REFERENCES events(id) ON DELETE CASCADE")?;
+  tx.execute_batch(include_str!("../migrations/0002_events.sql"))?;
+
+  // Rows from a single-event database move into an event of their own.
+  let has_unscoped = tx
+    .query_row("SELECT 1 FROM guests WHERE event_id IS NULL LIMIT 1", [], |_| Ok(()))
+    .optional()?
+    .is_some();
+  if !has_unscoped {
+    return Ok(());
+  }
+
+  // `created_at` is UTC; the event's date is the one in its own timezone,
+  // which for these rows is the column default.
+  let first_added: Option<String> =
+    tx.query_row("SELECT MIN(created_at) FROM guests WHERE event_id IS NULL", [], |row| row.get(0))?;
+  let tz = DEFAULT_TIMEZONE.parse::<Tz>().unwrap_or(chrono_tz::America::Chicago);
+  let event_date = first_added
+    .and_then(|added| NaiveDateTime::parse_from_str(&added, "%Y-%m-%d %H:%M:%S").ok())
+    .map(|added| Utc.from_utc_datetime(&added).with_timezone(&tz).format("%Y-%m-%d").to_string());
+  tx.execute(
+    "INSERT INTO events (name, event_date) VALUES ('Imported event', ?1)",
+    params![event_date],
+  )?;
+  let event_id = tx.last_insert_rowid();
+  tx.execute(
+    "UPDATE guests SET event_id = ?1 WHERE event_id IS NULL",
+    params![event_id],
+  )?;
+  tx.execute(
+    "UPDATE checkins SET event_id = (SELECT g.event_id FROM guests g WHERE g.id = checkins.guest_id)
+     WHERE event_id IS NULL",
+    [],
+  )?;
+  Ok(())
+}
+
+/// Older builds stored only the wall-clock time (`11:50:00 PM`); rewrite those
+/// rows as RFC 3339 UTC instants on their event's date.
+fn rfc3339_timestamps(tx: &Transaction<'_>) -> Result<()> {
+  let legacy: Vec<(i64, i64, String, Option<String>)> = {
+    let mut stmt = tx.prepare(
+      "SELECT id, event_id, in_ts, out_ts FROM checkins
+       WHERE in_ts NOT GLOB '[0-9][0-9][0-9][0-9]-*'
+         OR (out_ts IS NOT NULL AND out_ts NOT GLOB '[0-9][0-9][0-9][0-9]-*')",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+    rows.collect::<rusqlite::Result<_>>()?
+  };
+
+  let mut days: HashMap<i64, EventDay> = HashMap::new();
+  for (checkin_id, event_id, in_ts, out_ts) in legacy {
+    let day = match days.get(&event_id) {
+      Some(day) => *day,
+      None => {
+        let day = legacy_event_day(tx, event_id)?;
+        days.insert(event_id, day);
+        day
+      }
+    };
+
+    let in_ts = parse_import_timestamp(Some(&in_ts), &day).unwrap_or(in_ts);
+    let out_ts = out_ts.map(|out| {
+      let parsed = parse_import_timestamp(Some(&out), &day).unwrap_or(out);
+      ensure_after(parsed, Some(&in_ts))
+    });
+    tx.execute(
+      "UPDATE checkins SET in_ts = ?1, out_ts = ?2 WHERE id = ?3",
+      params![in_ts, out_ts, checkin_id],
+    )?;
+  }
+  Ok(())
+}
+
+/// The event's night as migration 3 saw it. Frozen here rather than read
+/// through `fetch_event`, whose query needs columns later migrations add.
+fn legacy_event_day(tx: &Transaction<'_>, event_id: i64) -> Result<EventDay> {
+  let (event_date, timezone): (Option<String>, String) = tx.query_row(
+    "SELECT event_date, timezone FROM events WHERE id = ?1",
+    params![event_id],
+    |row| Ok((row.get(0)?, row.get(1)?)),
+  )?;
+  let tz = timezone.parse::<Tz>().unwrap_or(chrono_tz::America::Chicago);
+  let date = event_date
+    .as_deref()
+    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    .unwrap_or_else(|| Utc::now().with_timezone(&tz).date_naive());
+  Ok(EventDay {
+    date,
+    rollover: NaiveTime::from_hms_opt(6, 0, 0).expect("valid time"),
+    tz,
+  })
+}
+
+fn undo_history(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0004_undo_history.sql"))?;
+  Ok(())
+}
+
+fn audit_log(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0005_audit_log.sql"))?;
+  Ok(())
+}
+
+fn event_capacity(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0006_event_capacity.sql"))?;
+  Ok(())
+}
+
+fn member_quotas(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0007_member_quotas.sql"))?;
+  Ok(())
+}
+
+fn banned_guests(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0008_banned_guests.sql"))?;
+  Ok(())
+}
+
+fn guest_sounds(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0009_guest_sounds.sql"))?;
+  Ok(())
+}
+
+/// Rebuilds the name index from folded text and recomputes banned-list keys,
+/// which now fold accents too.
+fn folded_search_index(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0010_folded_search_index.sql"))?;
+
+  let bans: Vec<(i64, String)> = {
+    let mut stmt = tx.prepare("SELECT id, name FROM banned_guests")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect::<rusqlite::Result<_>>()?
+  };
+  for (id, name) in bans {
+    tx.execute(
+      "UPDATE banned_guests SET name_key = ?1 WHERE id = ?2",
+      params![name_key(&name), id],
+    )?;
+  }
+  Ok(())
+}
+
+fn members(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0011_members.sql"))?;
+  Ok(())
+}
+
+fn walk_ins(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0012_walk_ins.sql"))?;
+  Ok(())
+}
+
+fn guest_undo(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0013_guest_undo.sql"))?;
+  Ok(())
+}
+
+fn import_profiles(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0014_import_profiles.sql"))?;
+  Ok(())
+}
+
+fn import_batches(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0015_import_batches.sql"))?;
+  Ok(())
+}
+
+fn import_archives(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0016_import_archives.sql"))?;
+  Ok(())
+}
+
+fn removed_guests(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0017_removed_guests.sql"))?;
+  Ok(())
+}
+
+fn stable_ids(tx: &Transaction<'_>) -> Result<()> {
+  tx.execute_batch(include_str!("../migrations/0018_stable_ids.sql"))?;
+  Ok(())
+}
+
+fn add_column_if_missing(
+  tx: &Transaction<'_>,
+  table: &str,
+  column: &str,
+  definition: &str,
+) -> Result<()> {
+  let has_column = tx
+    .query_row(
+      &format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table),
+      params![column],
+      |_| Ok(()),
+    )
+    .optional()?
+    .is_some();
+  if !has_column {
+    tx.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+  }
+  Ok(())
+}
@@ -0,0 +1,265 @@
+//! Splitting a sheet's guest column into names. Sign-up sheets are free
+//! text, so besides commas this understands "and", "&", semicolons and line
+//! breaks, couples sharing a surname ("John and Jane Smith", "Dr. and Mrs.
+//! Lee"), "+N" for unnamed extra guests and notes in brackets. Guesses are
+//! reported alongside the names so an import preview can show them.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+use crate::names::clean_name;
+
+/// " and " between two names, read from the whitespace before it.
+static AND_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^\s+and\s+").expect("valid regex"));
+
+static PLUS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\+\s*(\d+)").expect("valid regex"));
+
+/// The most unnamed guests one "+N" stands for. Anything larger is more likely
+/// a phone number than a guest count, and is left out as a note.
+const MAX_PLUS_GUESTS: usize = 10;
+
+static BRACKETS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(([^()]*)\)|\[([^\[\]]*)\]").expect("valid regex"));
+
+/// Forms of address that can stand in for a first name.
+const TITLES: &[&str] = &["mr", "mrs", "ms", "miss", "mx", "dr", "prof", "rev"];
+
+/// Words after a surname that are not the surname.
+const SUFFIXES: &[&str] = &["jr", "sr", "ii", "iii", "iv"];
+
+/// A guest column split into names.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct NameList {
+  pub names: Vec<String>,
+  /// Where the split is a guess someone should check.
+  pub ambiguities: Vec<Ambiguity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ambiguity {
+  pub kind: AmbiguityKind,
+  /// The part of the column the guess was made from.
+  pub text: String,
+  /// The names it became.
+  pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbiguityKind {
+  /// A first name was given the surname of the name after it.
+  SharedSurname,
+  /// "+N" was read as that many unnamed guests, listed under placeholders.
+  PlusGuests,
+  /// A note in brackets, or a "+N" too large to be a guest count, was left
+  /// out of the name.
+  Note,
+}
+
+/// One name as read from the column, before surnames are shared.
+struct Person<'a> {
+  text: &'a str,
+  name: Option<String>,
+  /// Unnamed guests from a "+N".
+  extra: usize,
+  notes: bool,
+}
+
+/// Names joined by "and", "&" or "+", which may share a surname.
+struct Group<'a> {
+  text: &'a str,
+  parts: Vec<&'a str>,
+}
+
+pub fn parse_name_list(input: &str) -> NameList {
+  let mut list = NameList::default();
+  for group in split_groups(input) {
+    read_group(&group, &mut list);
+  }
+  list
+}
+
+/// Splits on commas, semicolons and line breaks into groups, and each group on
+/// "and", "&" and "+" into parts. Nothing inside brackets splits, unless the
+/// brackets do not pair up.
+fn split_groups(input: &str) -> Vec<Group<'_>> {
+  let balanced = brackets_balance(input);
+  let mut groups = Vec::new();
+  let mut parts = Vec::new();
+  let (mut group_start, mut part_start, mut depth) = (0, 0, 0usize);
+  let mut chars = input.char_indices().peekable();
+  while let Some((i, c)) = chars.next() {
+    match c {
+      '(' | '[' if balanced => depth += 1,
+      ')' | ']' if balanced => depth = depth.saturating_sub(1),
+      _ if depth > 0 => {}
+      ',' | ';' | '\n' | '\r' => {
+        parts.push(&input[part_start..i]);
+        groups.push(Group {
+          text: input[group_start..i].trim(),
+          parts: std::mem::take(&mut parts),
+        });
+        group_start = i + 1;
+        part_start = i + 1;
+      }
+      '&' => {
+        parts.push(&input[part_start..i]);
+        part_start = i + 1;
+      }
+      // "+2" counts guests; "Amy + Ben" joins two names.
+      '+' if !input[i + 1..].trim_start().starts_with(|c: char| c.is_ascii_digit()) => {
+        parts.push(&input[part_start..i]);
+        part_start = i + 1;
+      }
+      c if c.is_whitespace() => {
+        if let Some(and) = AND_RE.find(&input[i..]) {
+          parts.push(&input[part_start..i]);
+          part_start = i + and.end();
+          while chars.next_if(|(j, _)| *j < part_start).is_some() {}
+        }
+      }
+      _ => {}
+    }
+  }
+  parts.push(&input[part_start..]);
+  groups.push(Group {
+    text: input[group_start..].trim(),
+    parts,
+  });
+  groups
+}
+
+fn brackets_balance(input: &str) -> bool {
+  let mut open = Vec::new();
+  for c in input.chars() {
+    match c {
+      '(' | '[' => open.push(c),
+      ')' if open.pop() != Some('(') => return false,
+      ']' if open.pop() != Some('[') => return false,
+      _ => {}
+    }
+  }
+  open.is_empty()
+}
+
+fn read_group(group: &Group<'_>, list: &mut NameList) {
+  let mut people: Vec<Person<'_>> = group.parts.iter().map(|part| read_part(part)).collect();
+
+  // "John and Jane Smith": a lone first name, or a lone title, takes the
+  // surname of the last name in the group.
+  let last = people.iter().rposition(|person| person.name.is_some());
+  let shared = last.and_then(|last| {
+    let (surname, has_first_name) = surname(people[last].name.as_deref()?)?;
+    Some((last, surname.to_string(), has_first_name))
+  });
+  let mut guessed = false;
+  if let Some((last, surname, has_first_name)) = shared {
+    for person in &mut people[..last] {
+      let Some(name) = &mut person.name else { continue };
+      if name.contains(' ') {
+        continue;
+      }
+      // "Dr. and Mrs. Lee" needs no guess; "Mr. and Mrs. John Smith" does.
+      guessed |= !is_title(name) || has_first_name;
+      *name = format!("{} {}", name, surname);
+    }
+  }
+
+  let first = list.names.len();
+  for person in people {
+    let mut names: Vec<String> = person.name.iter().cloned().collect();
+    for n in 1..=person.extra {
+      let placeholder = match &person.name {
+        Some(name) => format!("{}'s guest", name),
+        None => "Guest".to_string(),
+      };
+      names.push(if n == 1 { placeholder } else { format!("{} {}", placeholder, n) });
+    }
+    if person.extra > 0 {
+      list.ambiguities.push(ambiguity(AmbiguityKind::PlusGuests, person.text, &names));
+    }
+    if person.notes {
+      list.ambiguities.push(ambiguity(AmbiguityKind::Note, person.text, &names));
+    }
+    list.names.extend(names);
+  }
+  if guessed {
+    let names = list.names[first..].to_vec();
+    list.ambiguities.push(ambiguity(AmbiguityKind::SharedSurname, group.text, &names));
+  }
+}
+
+/// Reads one name, taking out "+N" and bracketed notes.
+fn read_part(text: &str) -> Person<'_> {
+  let mut extra = 0;
+  let mut notes = false;
+  let without_notes = BRACKETS_RE.replace_all(text, |caps: &Captures<'_>| {
+    let inner = caps.get(1).or_else(|| caps.get(2)).map_or("", |inner| inner.as_str());
+    match plus_count(inner) {
+      Some(count) => extra += count,
+      None => notes |= !inner.trim().is_empty(),
+    }
+    " "
+  });
+  let without_plus = PLUS_RE.replace_all(&without_notes, |caps: &Captures<'_>| {
+    match guest_count(&caps[1]) {
+      Some(count) => extra += count,
+      None => notes = true,
+    }
+    " "
+  });
+  Person {
+    text: text.trim(),
+    name: clean_name(&without_plus),
+    extra,
+    notes,
+  }
+}
+
+/// The count in a bracket holding only "+N".
+fn plus_count(inner: &str) -> Option<usize> {
+  let inner = inner.trim();
+  let caps = PLUS_RE.captures(inner)?;
+  if caps[0].len() != inner.len() {
+    return None;
+  }
+  guest_count(&caps[1])
+}
+
+/// The digits of a "+N" as a number of guests, if it is small enough to be one.
+fn guest_count(digits: &str) -> Option<usize> {
+  digits.parse().ok().filter(|count| *count <= MAX_PLUS_GUESTS)
+}
+
+/// The surname of a full name, and whether the name also has a first name.
+/// `None` for a single word, which could be either.
+fn surname(name: &str) -> Option<(&str, bool)> {
+  let mut words: Vec<&str> = name.split(' ').collect();
+  let titled = words.first().is_some_and(|word| is_title(word));
+  if titled {
+    words.remove(0);
+  }
+  while words.len() > 1 && words.last().is_some_and(|word| is_suffix(word)) {
+    words.pop();
+  }
+  if words.is_empty() || (!titled && words.len() < 2) {
+    return None;
+  }
+  Some((words[words.len() - 1], words.len() > 1))
+}
+
+fn is_title(word: &str) -> bool {
+  TITLES.contains(&word.trim_end_matches('.').to_lowercase().as_str())
+}
+
+fn is_suffix(word: &str) -> bool {
+  SUFFIXES.contains(&word.trim_end_matches('.').to_lowercase().as_str())
+}
+
+fn ambiguity(kind: AmbiguityKind, text: &str, names: &[String]) -> Ambiguity {
+  Ambiguity {
+    kind,
+    text: text.to_string(),
+    names: names.to_vec(),
+  }
+}
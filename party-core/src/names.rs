@@ -0,0 +1,159 @@
+//! Cleaning guest names and search terms.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+static MULTISPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").expect("valid regex"));
+
+pub fn clean_name(value: &str) -> Option<String> {
+  let trimmed = value.trim();
+  if trimmed.is_empty() {
+    return None;
+  }
+  let collapsed = MULTISPACE_RE.replace_all(trimmed, " ");
+  let mut out = collapsed.to_string();
+  if is_all_caps(&out) {
+    out = out
+      .split(' ')
+      .map(|token| {
+        if token.is_empty() {
+          String::new()
+        } else {
+          let mut chars = token.chars();
+          let first = chars.next().unwrap();
+          first.to_uppercase().collect::<String>()
+            + &chars.as_str().to_lowercase()
+        }
+      })
+      .collect::<Vec<_>>()
+      .join(" ");
+  }
+  Some(out)
+}
+
+pub fn clean_whitespace(value: &str) -> String {
+  let trimmed = value.trim();
+  MULTISPACE_RE.replace_all(trimmed, " ").to_string()
+}
+
+pub fn clean_optional(value: Option<String>) -> Option<String> {
+  value
+    .map(|v| clean_whitespace(&v))
+    .filter(|v| !v.is_empty())
+}
+
+/// Reduces a search word to lower-case letters and digits in any script,
+/// with accents and apostrophes folded away ("José" -> "jose", "O'Neil" ->
+/// "oneil").
+pub fn clean_token(token: &str) -> String {
+  fold(token).chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// Lower-cases text and strips accents and apostrophes, leaving other
+/// punctuation and spacing alone: "Zoë O’Brien-Núñez" -> "zoe obrien-nunez".
+/// The search index and search terms are both folded this way.
+pub fn fold(value: &str) -> String {
+  value
+    .nfkd()
+    .filter(|c| !is_combining_mark(*c) && !matches!(c, '\'' | '’' | 'ʼ' | '`'))
+    .flat_map(char::to_lowercase)
+    .collect()
+}
+
+/// What the search index stores for a name: its folded words, with each
+/// hyphenated word also kept whole, so "Smith-Jones" is found by "smith",
+/// "jones" and "smithjones".
+pub fn index_text(value: &str) -> String {
+  let mut words = Vec::new();
+  for word in fold(value).split_whitespace() {
+    let parts: Vec<&str> = word.split('-').filter(|part| !part.is_empty()).collect();
+    words.extend(parts.iter().map(|part| part.to_string()));
+    if parts.len() > 1 {
+      words.push(parts.concat());
+    }
+  }
+  words.join(" ")
+}
+
+/// Comparison key for a person's name: lower-case letters and digits, one
+/// space between words, punctuation dropped ("O'Neil,  Pat" -> "oneil pat").
+pub fn name_key(value: &str) -> String {
+  value
+    .split_whitespace()
+    .map(clean_token)
+    .filter(|token| !token.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Edit distance between two strings, counted in characters. Swapping two
+/// neighbouring letters ("jonh") counts as one edit, not two.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+  // Three rows of the optimal-string-alignment table: i-2, i-1 and i.
+  let mut before: Vec<usize> = vec![0; b.len() + 1];
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  let mut curr = vec![0; b.len() + 1];
+  for i in 0..a.len() {
+    curr[0] = i + 1;
+    for j in 0..b.len() {
+      let substitution = prev[j] + usize::from(a[i] != b[j]);
+      curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+      if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+        curr[j + 1] = curr[j + 1].min(before[j - 1] + 1);
+      }
+    }
+    std::mem::swap(&mut before, &mut prev);
+    std::mem::swap(&mut prev, &mut curr);
+  }
+  prev[b.len()]
+}
+
+/// How closely a search matches a name, from 0 (nothing alike) to 1. Each
+/// search word is scored against its closest word in the name, where typing
+/// the start of a word counts as a full match, and the scores are averaged.
+/// Both halves of a hyphenated name count as words.
+pub fn name_similarity(query: &str, name: &str) -> f64 {
+  let query = name_key(query);
+  let name = name_key(&index_text(name));
+  let words: Vec<&str> = name.split(' ').filter(|word| !word.is_empty()).collect();
+  let terms: Vec<&str> = query.split(' ').filter(|term| !term.is_empty()).collect();
+  if words.is_empty() || terms.is_empty() {
+    return 0.0;
+  }
+
+  let total: f64 = terms
+    .iter()
+    .map(|term| {
+      words
+        .iter()
+        .map(|word| word_similarity(term, word))
+        .fold(0.0, f64::max)
+    })
+    .sum();
+  total / terms.len() as f64
+}
+
+fn word_similarity(term: &str, word: &str) -> f64 {
+  if word.starts_with(term) {
+    return 1.0;
+  }
+  let similarity = |other: &str| {
+    let len = term.chars().count().max(other.chars().count());
+    1.0 - edit_distance(term, other) as f64 / len as f64
+  };
+  // A half-typed word is compared with the same length of the name's word.
+  let head: String = word.chars().take(term.chars().count()).collect();
+  similarity(word).max(similarity(&head))
+}
+
+pub fn fts_escape(token: &str) -> String {
+  token.replace('"', "\"\"")
+}
+
+fn is_all_caps(value: &str) -> bool {
+  let letters: String = value.chars().filter(|c| c.is_alphabetic()).collect();
+  !letters.is_empty() && letters.chars().all(|c| c.is_uppercase())
+}
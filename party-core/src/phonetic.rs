@@ -0,0 +1,171 @@
+//! Sound-alike keys for names, so "Caitlin" finds "Katelyn" and "Sean" finds
+//! "Shawn". The encoding is a Metaphone variant tuned for first and last names:
+//! vowels after the first letter are dropped and consonants that sound alike
+//! share a code ("PH" and "V" become "F", a soft "C" becomes "S", and so on).
+
+use crate::names::fold;
+
+/// Sound key for each word of `name`, separated by spaces
+/// ("Caitlin Smyth" -> "KTLN SM0"). Accents are folded first, so "José" and
+/// "Jose" share a key.
+pub fn sound_key(name: &str) -> String {
+  fold(name)
+    .split(|c: char| c.is_whitespace() || c == '-')
+    .map(word_sound)
+    .filter(|key| !key.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn word_sound(word: &str) -> String {
+  let w: Vec<char> = word
+    .chars()
+    .filter(char::is_ascii_alphabetic)
+    .map(|c| c.to_ascii_uppercase())
+    .collect();
+  if w.is_empty() {
+    return String::new();
+  }
+  let at = |i: usize| w.get(i).copied().unwrap_or('\0');
+  let starts = |prefix: &str| prefix.chars().enumerate().all(|(i, c)| at(i) == c);
+
+  let mut out = String::new();
+  let mut i = 0;
+  if ["KN", "GN", "PN", "WR", "AE"].iter().any(|p| starts(p)) {
+    i = 1;
+  } else if starts("X") {
+    out.push('S');
+    i = 1;
+  } else if starts("WH") {
+    out.push('W');
+    i = 2;
+  } else if ["SEA", "SIA", "SIO"].iter().any(|p| starts(p)) {
+    // Sean, Siobhan, Sian: Irish "S" before these vowels sounds like "SH".
+    out.push('X');
+    i = 1;
+  } else if is_vowel(at(0)) {
+    out.push('A');
+    i = 1;
+  }
+
+  while i < w.len() {
+    let c = w[i];
+    let next = at(i + 1);
+    let prev = if i > 0 { w[i - 1] } else { '\0' };
+    if c == prev && c != 'C' {
+      i += 1;
+      continue;
+    }
+
+    match c {
+      'A' | 'E' | 'I' | 'O' | 'U' => {}
+      'B' => {
+        // The "B" in "-MB" is silent (Lamb, Plumb).
+        if !(prev == 'M' && i + 1 == w.len()) {
+          out.push('B');
+        }
+      }
+      'C' => {
+        if next == 'H' {
+          // "CHR" and a leading "CH" before a consonant are hard (Christine).
+          let hard = at(i + 2) == 'R' || (i == 0 && !is_vowel(at(i + 2)));
+          out.push(if hard { 'K' } else { 'X' });
+          i += 2;
+          continue;
+        }
+        if matches!(next, 'E' | 'I' | 'Y') {
+          out.push('S');
+        } else if next != 'K' {
+          out.push('K');
+        }
+      }
+      'D' => {
+        if next == 'G' && matches!(at(i + 2), 'E' | 'I' | 'Y') {
+          out.push('J');
+          i += 3;
+          continue;
+        }
+        out.push('T');
+      }
+      'G' => {
+        if next == 'H' {
+          if i == 0 {
+            out.push('K');
+          }
+          i += 2;
+          continue;
+        }
+        if next == 'N' && (i + 2 == w.len() || (at(i + 2) == 'E' && at(i + 3) == 'D')) {
+          // Silent in "-GN" and "-GNED".
+        } else if matches!(next, 'E' | 'I' | 'Y') {
+          out.push('J');
+        } else {
+          out.push('K');
+        }
+      }
+      'H' => {
+        if is_vowel(next) && !matches!(prev, 'C' | 'G' | 'P' | 'S' | 'T') {
+          out.push('H');
+        }
+      }
+      'K' => {
+        if prev != 'C' {
+          out.push('K');
+        }
+      }
+      'P' => {
+        if next == 'H' {
+          out.push('F');
+          i += 2;
+          continue;
+        }
+        out.push('P');
+      }
+      'Q' => out.push('K'),
+      'S' => {
+        if next == 'H' {
+          out.push('X');
+          i += 2;
+          continue;
+        }
+        if next == 'C' && at(i + 2) == 'H' {
+          out.push_str("SK");
+          i += 3;
+          continue;
+        }
+        if next == 'I' && matches!(at(i + 2), 'O' | 'A') {
+          out.push('X');
+        } else {
+          out.push('S');
+        }
+      }
+      'T' => {
+        if next == 'H' {
+          out.push('0');
+          i += 2;
+          continue;
+        }
+        if next == 'I' && matches!(at(i + 2), 'O' | 'A') {
+          out.push('X');
+        } else if !(next == 'C' && at(i + 2) == 'H') {
+          out.push('T');
+        }
+      }
+      'V' => out.push('F'),
+      'W' | 'Y' => {
+        if is_vowel(next) {
+          out.push(c);
+        }
+      }
+      'X' => out.push_str("KS"),
+      'Z' => out.push('S'),
+      other => out.push(other),
+    }
+    i += 1;
+  }
+  out
+}
+
+fn is_vowel(c: char) -> bool {
+  matches!(c, 'A' | 'E' | 'I' | 'O' | 'U')
+}
@@ -0,0 +1,165 @@
+//! Saved column mappings for importing sheets whose headers differ from the
+//! app's own export. Profiles are shared by every event.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{record_audit, AuditRecord};
+use crate::error::{Error, Result};
+use crate::names::{clean_optional, clean_whitespace};
+use crate::time::now_timestamp;
+
+/// The sheet header to read each import field from. Headers are matched
+/// case-insensitively; a field left out is not imported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+  pub member_name: Option<String>,
+  pub guest_names: Option<String>,
+  pub check_in: Option<String>,
+  pub check_in_time: Option<String>,
+  pub check_out: Option<String>,
+  pub check_out_time: Option<String>,
+  pub walk_in: Option<String>,
+}
+
+impl ColumnMapping {
+  fn cleaned(self) -> Self {
+    ColumnMapping {
+      member_name: clean_optional(self.member_name),
+      guest_names: clean_optional(self.guest_names),
+      check_in: clean_optional(self.check_in),
+      check_in_time: clean_optional(self.check_in_time),
+      check_out: clean_optional(self.check_out),
+      check_out_time: clean_optional(self.check_out_time),
+      walk_in: clean_optional(self.walk_in),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProfile {
+  pub id: i64,
+  pub name: String,
+  pub columns: ColumnMapping,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInput {
+  pub name: String,
+  pub columns: ColumnMapping,
+}
+
+pub fn list_profiles(conn: &Connection) -> Result<Vec<ImportProfile>> {
+  let mut stmt = conn.prepare_cached(
+    "SELECT id, name, columns, created_at, updated_at FROM import_profiles ORDER BY name COLLATE NOCASE",
+  )?;
+  let rows = stmt.query_map([], map_profile_row)?;
+  rows
+    .map(|row| row?.into_profile())
+    .collect()
+}
+
+/// The profile called `name`, compared case-insensitively.
+pub fn find_profile(conn: &Connection, name: &str) -> Result<ImportProfile> {
+  conn
+    .prepare_cached("SELECT id, name, columns, created_at, updated_at FROM import_profiles WHERE name = ?1")?
+    .query_row(params![clean_whitespace(name)], map_profile_row)
+    .optional()?
+    .ok_or_else(|| Error::ProfileNotFound(name.to_string()))?
+    .into_profile()
+}
+
+/// Saves a profile, replacing the columns of any profile with the same name.
+pub fn save_profile(conn: &mut Connection, input: ProfileInput, operator: Option<&str>) -> Result<ImportProfile> {
+  let name = clean_whitespace(&input.name);
+  if name.is_empty() {
+    return Err(Error::Invalid("profile name is required".into()));
+  }
+  let columns = input.columns.cleaned();
+  if columns.guest_names.is_none() {
+    return Err(Error::Invalid("a profile must name the guest names column".into()));
+  }
+
+  let tx = conn.transaction()?;
+  let before = match find_profile(&tx, &name) {
+    Ok(profile) => Some(serde_json::to_string(&profile)?),
+    Err(Error::ProfileNotFound(_)) => None,
+    Err(err) => return Err(err),
+  };
+  let now = now_timestamp();
+  tx.execute(
+    "INSERT INTO import_profiles (name, columns, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)
+     ON CONFLICT(name) DO UPDATE SET name = excluded.name, columns = excluded.columns, updated_at = excluded.updated_at",
+    params![name, serde_json::to_string(&columns)?, now],
+  )?;
+  let saved = find_profile(&tx, &name)?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: None,
+      guest_id: None,
+      action: "profile_saved",
+      operator,
+      before,
+      after: Some(serde_json::to_string(&saved)?),
+    },
+  )?;
+  tx.commit()?;
+
+  Ok(saved)
+}
+
+pub fn delete_profile(conn: &mut Connection, name: &str, operator: Option<&str>) -> Result<()> {
+  let tx = conn.transaction()?;
+  let profile = find_profile(&tx, name)?;
+  tx.execute("DELETE FROM import_profiles WHERE id = ?1", params![profile.id])?;
+  record_audit(
+    &tx,
+    AuditRecord {
+      event_id: None,
+      guest_id: None,
+      action: "profile_deleted",
+      operator,
+      before: Some(serde_json::to_string(&profile)?),
+      after: None,
+    },
+  )?;
+  tx.commit()?;
+  Ok(())
+}
+
+/// A profile row before its `columns` JSON is parsed.
+struct StoredProfile {
+  id: i64,
+  name: String,
+  columns: String,
+  created_at: String,
+  updated_at: String,
+}
+
+impl StoredProfile {
+  fn into_profile(self) -> Result<ImportProfile> {
+    Ok(ImportProfile {
+      id: self.id,
+      name: self.name,
+      columns: serde_json::from_str(&self.columns)?,
+      created_at: self.created_at,
+      updated_at: self.updated_at,
+    })
+  }
+}
+
+fn map_profile_row(row: &Row<'_>) -> rusqlite::Result<StoredProfile> {
+  Ok(StoredProfile {
+    id: row.get(0)?,
+    name: row.get(1)?,
+    columns: row.get(2)?,
+    created_at: row.get(3)?,
+    updated_at: row.get(4)?,
+  })
+}
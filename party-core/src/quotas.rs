@@ -0,0 +1,114 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{record_audit, AuditRecord};
+use crate::error::{Error, Result};
+use crate::events::{fetch_event, validate_quota};
+use crate::names::clean_whitespace;
+
+/// A member's own limits, overriding the event defaults. `None` falls back to
+/// the event's `guest_quota` / `present_quota`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberQuota {
+  pub member_host: String,
+  pub guest_quota: Option<i64>,
+  pub present_quota: Option<i64>,
+}
+
+/// The limits that apply to one member once defaults are filled in.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Quota {
+  pub(crate) guests: Option<i64>,
+  pub(crate) present: Option<i64>,
+}
+
+pub fn list_member_quotas(conn: &Connection, event_id: i64) -> Result<Vec<MemberQuota>> {
+  fetch_event(conn, event_id)?;
+
+  let mut stmt = conn.prepare_cached(
+    "SELECT member_host, guest_quota, present_quota FROM member_quotas
+     WHERE event_id = ?1
+     ORDER BY member_host",
+  )?;
+  let rows = stmt.query_map(params![event_id], |row| {
+    Ok(MemberQuota {
+      member_host: row.get(0)?,
+      guest_quota: row.get(1)?,
+      present_quota: row.get(2)?,
+    })
+  })?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Sets a member's own limits. Clearing both removes the exception so the
+/// member follows the event defaults again.
+pub fn set_member_quota(
+  conn: &Connection,
+  event_id: i64,
+  quota: MemberQuota,
+  operator: Option<&str>,
+) -> Result<()> {
+  fetch_event(conn, event_id)?;
+  let member_host = clean_whitespace(&quota.member_host);
+  if member_host.is_empty() {
+    return Err(Error::Invalid("member name is required".into()));
+  }
+  validate_quota("guest quota", quota.guest_quota)?;
+  validate_quota("present quota", quota.present_quota)?;
+
+  if quota.guest_quota.is_none() && quota.present_quota.is_none() {
+    conn.execute(
+      "DELETE FROM member_quotas WHERE event_id = ?1 AND member_host = ?2",
+      params![event_id, member_host],
+    )?;
+  } else {
+    conn.execute(
+      "INSERT INTO member_quotas (event_id, member_host, guest_quota, present_quota) VALUES (?1, ?2, ?3, ?4)
+       ON CONFLICT (event_id, member_host) DO UPDATE SET
+         guest_quota = excluded.guest_quota, present_quota = excluded.present_quota",
+      params![event_id, member_host, quota.guest_quota, quota.present_quota],
+    )?;
+  }
+
+  record_audit(
+    conn,
+    AuditRecord {
+      event_id: Some(event_id),
+      guest_id: None,
+      action: "set_member_quota",
+      operator,
+      before: None,
+      after: Some(serde_json::to_string(&MemberQuota { member_host, ..quota })?),
+    },
+  )
+}
+
+/// Limits for `member_host` at this event, with event defaults filled in.
+pub(crate) fn quota_for(conn: &Connection, event_id: i64, member_host: &str) -> Result<Quota> {
+  Ok(
+    conn
+      .prepare_cached(
+        "SELECT COALESCE(q.guest_quota, e.guest_quota), COALESCE(q.present_quota, e.present_quota)
+         FROM events e
+         LEFT JOIN member_quotas q ON q.event_id = e.id AND q.member_host = ?2
+         WHERE e.id = ?1",
+      )?
+      .query_row(params![event_id, member_host], |row| {
+        Ok(Quota {
+          guests: row.get(0)?,
+          present: row.get(1)?,
+        })
+      })?,
+  )
+}
+
+/// Number of guests `member_host` has on the list, leaving out removed ones.
+pub(crate) fn listed_guests(conn: &Connection, event_id: i64, member_host: &str) -> Result<i64> {
+  Ok(
+    conn
+      .prepare_cached("SELECT COUNT(*) FROM guests
+         WHERE event_id = ?1 AND lower(member_host) = lower(?2) AND removed_at IS NULL")?
+      .query_row(params![event_id, member_host], |row| row.get(0))?,
+  )
+}
@@ -0,0 +1,607 @@
+use rusqlite::{params, types::ToSql, Connection, Row};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::events::fetch_event;
+use crate::names::{clean_token, fold, fts_escape, name_similarity};
+use crate::phonetic::sound_key;
+
+/// Lowest similarity a misspelled search may have and still be offered.
+const FUZZY_MIN_SCORE: f64 = 0.6;
+
+#[derive(Debug, Serialize)]
+pub struct GuestSearchResult {
+  pub id: i64,
+  pub display_name: String,
+  pub member_host: Option<String>,
+  pub is_checked_in: bool,
+  pub has_history: bool,
+  /// Matches the banned list; check-in is refused without an override.
+  pub is_banned: bool,
+  pub banned_reason: Option<String>,
+  /// Dropped from the list by a sync import; kept so their visits still show
+  /// and they can be checked out, but check-in is refused.
+  pub is_removed: bool,
+  /// How closely the name matches the search, from 0 to 1; 1 when every word
+  /// was found as typed. `None` when there was no search text.
+  pub score: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberSearchResult {
+  pub member_host: String,
+  pub total_guests: i64,
+  pub present_guests: i64,
+  /// How closely the member's name matches the search, from 0 to 1. `None`
+  /// when there was no search text.
+  pub score: Option<f64>,
+}
+
+/// One row of a unified search: a guest, or a member standing for their group
+/// of guests.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SearchHit {
+  Guest(GuestSearchResult),
+  Member(MemberSearchResult),
+}
+
+/// Attendance state a search can be narrowed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+  /// Here now (`in:`).
+  In,
+  /// Came and left (`out:`).
+  Out,
+  /// Has not arrived yet (`never:`).
+  Never,
+}
+
+impl StatusFilter {
+  /// SQL condition on guest `g`.
+  fn condition(self) -> &'static str {
+    match self {
+      StatusFilter::In => "EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL)",
+      StatusFilter::Out => {
+        "EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id)
+         AND NOT EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL)"
+      }
+      StatusFilter::Never => "NOT EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id)",
+    }
+  }
+}
+
+/// A search box entry split into its parts. `host:smith in: jo` is guests
+/// whose name starts with "jo", brought by a member matching "smith", who are
+/// here now.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ParsedQuery {
+  /// Words matched against guest names, and member names when there is no
+  /// `host:`.
+  pub text: String,
+  /// Words from `host:` qualifiers, matched against the member's name.
+  pub host: String,
+  /// The last of `in:`, `out:` and `never:`, if any.
+  pub status: Option<StatusFilter>,
+}
+
+/// Splits qualifiers out of a search. Double quotes group words, so
+/// `host:"van der berg"` is one qualifier; `host: smith` also works. Words
+/// after `in:`, `out:` or `never:` (as in `in:jo`) are ordinary search text,
+/// as is anything with an unknown qualifier.
+pub fn parse_query(q: &str) -> ParsedQuery {
+  let mut text = Vec::new();
+  let mut host = Vec::new();
+  let mut status = None;
+
+  let mut words = split_words(q).into_iter().peekable();
+  while let Some(word) = words.next() {
+    let Some((key, value)) = word.split_once(':') else {
+      text.push(word);
+      continue;
+    };
+    let filter = match key.to_lowercase().as_str() {
+      "host" => {
+        if !value.is_empty() {
+          host.push(value.to_string());
+        } else if let Some(next) = words.next_if(|next| !next.contains(':')) {
+          host.push(next);
+        }
+        continue;
+      }
+      "in" => StatusFilter::In,
+      "out" => StatusFilter::Out,
+      "never" => StatusFilter::Never,
+      _ => {
+        text.push(word);
+        continue;
+      }
+    };
+    status = Some(filter);
+    if !value.is_empty() {
+      text.push(value.to_string());
+    }
+  }
+
+  ParsedQuery {
+    text: text.join(" "),
+    host: host.join(" "),
+    status,
+  }
+}
+
+/// Whitespace-separated words, keeping double-quoted runs together and
+/// dropping the quotes.
+fn split_words(q: &str) -> Vec<String> {
+  let mut words = Vec::new();
+  let mut word = String::new();
+  let mut quoted = false;
+  for c in q.chars() {
+    match c {
+      '"' => quoted = !quoted,
+      c if c.is_whitespace() && !quoted => {
+        if !word.is_empty() {
+          words.push(std::mem::take(&mut word));
+        }
+      }
+      c => word.push(c),
+    }
+  }
+  if !word.is_empty() {
+    words.push(word);
+  }
+  words
+}
+
+/// Guests and member groups in one list, best match first. Accepts the
+/// qualifiers described at [`parse_query`]. Members are offered when there is
+/// `host:` or name text, and with a status qualifier only those with a guest
+/// in that state.
+pub fn search(
+  conn: &Connection,
+  event_id: i64,
+  q: &str,
+  limit: Option<usize>,
+) -> Result<Vec<SearchHit>> {
+  let lim = limit.unwrap_or(25).min(100);
+  fetch_event(conn, event_id)?;
+
+  let parsed = parse_query(q);
+  let host_tokens = tokenize(&parsed.host);
+  let filter = GuestFilter::new(&host_tokens, parsed.status);
+  let guests = find_guests(conn, event_id, parsed.text.trim(), &filter, lim as i64)?;
+
+  let member_tokens = if host_tokens.is_empty() {
+    tokenize(&parsed.text)
+  } else {
+    host_tokens
+  };
+  let mut members = if member_tokens.is_empty() {
+    Vec::new()
+  } else {
+    find_members(conn, event_id, &member_tokens, parsed.status, lim as i64)?
+  };
+  // Best match first, as for guests; members that match equally keep the
+  // order `find_members` gave them.
+  members.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+
+  // Guests keep their own order; each member goes in ahead of the first guest
+  // that matched less well.
+  let mut hits = Vec::with_capacity(guests.len() + members.len());
+  let mut members = members.into_iter().peekable();
+  for guest in guests {
+    let guest_score = guest.score.unwrap_or(0.0);
+    while let Some(member) = members.next_if(|member| member.score.unwrap_or(0.0) >= guest_score) {
+      hits.push(SearchHit::Member(member));
+    }
+    hits.push(SearchHit::Guest(guest));
+  }
+  hits.extend(members.map(SearchHit::Member));
+  hits.truncate(lim);
+  Ok(hits)
+}
+
+pub fn search_guests(
+  conn: &Connection,
+  event_id: i64,
+  q: &str,
+  limit: Option<usize>,
+) -> Result<Vec<GuestSearchResult>> {
+  let lim = limit.unwrap_or(25).min(100) as i64;
+  fetch_event(conn, event_id)?;
+  find_guests(conn, event_id, q.trim(), &GuestFilter::default(), lim)
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+  text
+    .split_whitespace()
+    .map(clean_token)
+    .filter(|t| !t.is_empty())
+    .collect()
+}
+
+/// Extra conditions on guest `g` for `host:` words and a status. Host words
+/// match the start of a word in the member's name.
+#[derive(Debug, Default)]
+struct GuestFilter {
+  /// FTS query over `member_host`, bound after the statement's own parameters.
+  host_query: Option<String>,
+  status: Option<StatusFilter>,
+}
+
+impl GuestFilter {
+  fn new(host_tokens: &[String], status: Option<StatusFilter>) -> Self {
+    let host_query = (!host_tokens.is_empty()).then(|| {
+      host_tokens
+        .iter()
+        .map(|t| format!("member_host:\"{}\"*", fts_escape(t)))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+    });
+    GuestFilter { host_query, status }
+  }
+
+  /// The filter as `AND ...` SQL for a statement with `bound` parameters of
+  /// its own. Only the presence of a host query and the status change it, so
+  /// cached statements are reused across searches.
+  fn sql(&self, bound: usize) -> String {
+    let mut sql = String::new();
+    if self.host_query.is_some() {
+      sql.push_str(&format!(
+        " AND g.id IN (SELECT rowid FROM guest_fts WHERE guest_fts MATCH ?{})",
+        bound + 1
+      ));
+    }
+    if let Some(status) = self.status {
+      sql.push_str(&format!(" AND {}", status.condition()));
+    }
+    sql
+  }
+
+  /// The statement's own parameters followed by the host query.
+  fn params<'a>(&'a self, bound: &[&'a dyn ToSql]) -> Vec<&'a dyn ToSql> {
+    let mut params = bound.to_vec();
+    if let Some(host_query) = &self.host_query {
+      params.push(host_query);
+    }
+    params
+  }
+}
+
+/// Guests matching `query` by name, narrowed by `filter`: prefix matches first, then substring, then names that
+/// sound alike or are close misspellings.
+fn find_guests(
+  conn: &Connection,
+  event_id: i64,
+  query: &str,
+  filter: &GuestFilter,
+  lim: i64,
+) -> Result<Vec<GuestSearchResult>> {
+  if query.is_empty() {
+    return fetch_default_results(conn, event_id, filter, lim);
+  }
+
+  let tokens = tokenize(query);
+  if tokens.is_empty() {
+    return fetch_default_results(conn, event_id, filter, lim);
+  }
+
+  let fts_query = tokens
+    .iter()
+    .map(|t| format!("display_name:\"{}\"*", fts_escape(t)))
+    .collect::<Vec<_>>()
+    .join(" AND ");
+
+  let mut stmt = conn.prepare_cached(&format!(
+    "SELECT g.id, g.display_name, g.member_host,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+      g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+     FROM guest_fts f
+     JOIN guests g ON g.id = f.rowid
+     LEFT JOIN banned_guests b ON b.id = g.banned_id
+     WHERE guest_fts MATCH ?1 AND g.event_id = ?3{}
+     ORDER BY bm25(guest_fts)
+     LIMIT ?2",
+    filter.sql(3)
+  ))?;
+
+  let mut rows = stmt.query(&*filter.params(params![fts_query, lim, event_id]))?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    results.push(map_guest_row(row)?);
+  }
+
+  if results.is_empty() {
+    let like = format!("%{}%", fold(query));
+    let mut fallback = conn.prepare_cached(&format!(
+      "SELECT g.id, g.display_name, g.member_host,
+        EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+        EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+        g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+       FROM guests g
+       LEFT JOIN banned_guests b ON b.id = g.banned_id
+       WHERE g.event_id = ?3 AND fold(g.display_name) LIKE ?1{}
+       ORDER BY g.display_name
+       LIMIT ?2",
+      filter.sql(3)
+    ))?;
+
+    let mut rows = fallback.query(&*filter.params(params![like, lim, event_id]))?;
+    while let Some(row) = rows.next()? {
+      results.push(map_guest_row(row)?);
+    }
+  }
+
+  for result in &mut results {
+    result.score = Some(name_similarity(query, &result.display_name));
+  }
+
+  // Sound-alike names come after everything that matched as typed. When
+  // nothing did, they compete with close misspellings on similarity.
+  let mut near = match sound_fts_query("name_sound", &tokens) {
+    Some(sound_query) => sound_alike_results(conn, event_id, &sound_query, filter, lim)?,
+    None => Vec::new(),
+  };
+  for sound in &mut near {
+    sound.score = Some(name_similarity(query, &sound.display_name));
+  }
+  if results.is_empty() {
+    let misspelled = fuzzy_results(conn, event_id, query, filter, lim as usize)?;
+    near.retain(|sound| misspelled.iter().all(|fuzzy| fuzzy.id != sound.id));
+    near.extend(misspelled);
+  }
+  near.retain(|candidate| results.iter().all(|result| result.id != candidate.id));
+  near.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+  results.extend(near);
+  results.truncate(lim as usize);
+
+  Ok(results)
+}
+
+fn sound_alike_results(
+  conn: &Connection,
+  event_id: i64,
+  sound_query: &str,
+  filter: &GuestFilter,
+  limit: i64,
+) -> Result<Vec<GuestSearchResult>> {
+  let mut stmt = conn.prepare_cached(&format!(
+    "SELECT g.id, g.display_name, g.member_host,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+      g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+     FROM guest_sounds s
+     JOIN guests g ON g.id = s.rowid
+     LEFT JOIN banned_guests b ON b.id = g.banned_id
+     WHERE guest_sounds MATCH ?1 AND g.event_id = ?3{}
+     ORDER BY g.display_name
+     LIMIT ?2",
+    filter.sql(3)
+  ))?;
+  let rows = stmt.query_map(&*filter.params(params![sound_query, limit, event_id]), map_guest_row)?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// FTS query matching every search word by sound in `column` of
+/// `guest_sounds`. The last word may be half typed, so it matches as a prefix.
+fn sound_fts_query(column: &str, tokens: &[String]) -> Option<String> {
+  let keys: Vec<String> = tokens
+    .iter()
+    .map(|token| sound_key(token))
+    .filter(|key| !key.is_empty())
+    .collect();
+  let last = keys.len().checked_sub(1)?;
+  Some(
+    keys
+      .iter()
+      .enumerate()
+      .map(|(i, key)| format!("{}:\"{}\"{}", column, key, if i == last { "*" } else { "" }))
+      .collect::<Vec<_>>()
+      .join(" AND "),
+  )
+}
+
+/// Ranks every guest of the event by similarity to `query`, for searches with
+/// typos that neither FTS nor substring matching can find.
+fn fuzzy_results(
+  conn: &Connection,
+  event_id: i64,
+  query: &str,
+  filter: &GuestFilter,
+  limit: usize,
+) -> Result<Vec<GuestSearchResult>> {
+  let mut scored: Vec<(f64, i64, String)> = {
+    let mut stmt = conn.prepare_cached(&format!(
+      "SELECT g.id, g.display_name FROM guests g WHERE g.event_id = ?1{}",
+      filter.sql(1)
+    ))?;
+    let rows = stmt.query_map(&*filter.params(params![event_id]), |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    let mut scored = Vec::new();
+    for row in rows {
+      let (id, name) = row?;
+      let score = name_similarity(query, &name);
+      if score >= FUZZY_MIN_SCORE {
+        scored.push((score, id, name));
+      }
+    }
+    scored
+  };
+  scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+  scored.truncate(limit);
+
+  let mut stmt = conn.prepare_cached(
+    "SELECT g.id, g.display_name, g.member_host,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+      g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+     FROM guests g
+     LEFT JOIN banned_guests b ON b.id = g.banned_id
+     WHERE g.id = ?1",
+  )?;
+  let mut results = Vec::with_capacity(scored.len());
+  for (score, id, _) in scored {
+    let mut result = stmt.query_row(params![id], map_guest_row)?;
+    result.score = Some(score);
+    results.push(result);
+  }
+  Ok(results)
+}
+
+pub fn search_members(
+  conn: &Connection,
+  event_id: i64,
+  q: &str,
+  limit: Option<usize>,
+) -> Result<Vec<MemberSearchResult>> {
+  fetch_event(conn, event_id)?;
+
+  let limit = limit.unwrap_or(25).min(200) as i64;
+  find_members(conn, event_id, &tokenize(q), None, limit)
+}
+
+/// Members whose name has a word starting with each token, then members whose
+/// name only sounds alike, optionally only those with a guest in `status`.
+/// Reads the `members` index, so only matching members are counted.
+fn find_members(
+  conn: &Connection,
+  event_id: i64,
+  tokens: &[String],
+  status: Option<StatusFilter>,
+  limit: i64,
+) -> Result<Vec<MemberSearchResult>> {
+  let typed_query = (!tokens.is_empty()).then(|| {
+    tokens
+      .iter()
+      .map(|t| format!("member_host:\"{}\"*", fts_escape(t)))
+      .collect::<Vec<_>>()
+      .join(" AND ")
+  });
+  let sound_query = sound_fts_query("member_sound", tokens);
+
+  let matched = match (&typed_query, &sound_query) {
+    (None, _) => "SELECT id, 1 AS typed FROM members WHERE event_id = ?2",
+    (Some(_), None) => "SELECT rowid AS id, 1 AS typed FROM member_fts WHERE member_fts MATCH ?3",
+    (Some(_), Some(_)) => {
+      "SELECT rowid AS id, 1 AS typed FROM member_fts WHERE member_fts MATCH ?3
+       UNION ALL
+       SELECT rowid AS id, 0 AS typed FROM member_fts WHERE member_fts MATCH ?4"
+    }
+  };
+  let status_filter = match status {
+    Some(status) => format!(
+      " AND EXISTS(SELECT 1 FROM guests g
+         WHERE g.event_id = m.event_id AND g.member_host = m.member_host COLLATE NOCASE AND {})",
+      status.condition()
+    ),
+    None => String::new(),
+  };
+
+  let mut stmt = conn.prepare_cached(&format!(
+    "SELECT m.member_host,
+      (SELECT COUNT(*) FROM guests g
+       WHERE g.event_id = m.event_id AND g.member_host = m.member_host COLLATE NOCASE
+         AND g.removed_at IS NULL) as total_guests,
+      (SELECT COUNT(*) FROM guests g
+       WHERE g.event_id = m.event_id AND g.member_host = m.member_host COLLATE NOCASE
+         AND EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL)) as present_guests,
+      MAX(x.typed) as typed
+     FROM ({matched}) x
+     JOIN members m ON m.id = x.id
+     WHERE m.event_id = ?2{status_filter}
+     GROUP BY m.id
+     ORDER BY typed DESC, present_guests DESC, total_guests DESC, m.member_host
+     LIMIT ?1"
+  ))?;
+  let mut rows = match (&typed_query, &sound_query) {
+    (None, _) => stmt.query(params![limit, event_id])?,
+    (Some(typed), None) => stmt.query(params![limit, event_id, typed])?,
+    (Some(typed), Some(sound)) => stmt.query(params![limit, event_id, typed, sound])?,
+  };
+
+  let query = tokens.join(" ");
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    let host: String = row.get(0)?;
+    results.push(MemberSearchResult {
+      score: (!tokens.is_empty()).then(|| name_similarity(&query, &host)),
+      member_host: host,
+      total_guests: row.get(1)?,
+      present_guests: row.get(2)?,
+    });
+  }
+
+  Ok(results)
+}
+
+pub fn guests_for_member(
+  conn: &Connection,
+  event_id: i64,
+  member_host: &str,
+) -> Result<Vec<GuestSearchResult>> {
+  fetch_event(conn, event_id)?;
+
+  if member_host.trim().is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let mut stmt = conn.prepare_cached(
+    "SELECT g.id, g.display_name, g.member_host,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+      g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+     FROM guests g
+     LEFT JOIN banned_guests b ON b.id = g.banned_id
+     WHERE g.event_id = ?2 AND g.member_host = ?1 COLLATE NOCASE
+     ORDER BY g.display_name",
+  )?;
+
+  let mut rows = stmt.query(params![member_host.trim(), event_id])?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    results.push(map_guest_row(row)?);
+  }
+
+  Ok(results)
+}
+
+fn fetch_default_results(
+  conn: &Connection,
+  event_id: i64,
+  filter: &GuestFilter,
+  limit: i64,
+) -> Result<Vec<GuestSearchResult>> {
+  let mut stmt = conn.prepare_cached(&format!(
+    "SELECT g.id, g.display_name, g.member_host,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) as is_in,
+      EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id) as has_history,
+      g.banned_id IS NOT NULL as is_banned, b.reason, g.removed_at IS NOT NULL as is_removed
+     FROM guests g
+     LEFT JOIN banned_guests b ON b.id = g.banned_id
+     WHERE g.event_id = ?2{}
+     ORDER BY g.display_name
+     LIMIT ?1",
+    filter.sql(2)
+  ))?;
+  let mut rows = stmt.query(&*filter.params(params![limit, event_id]))?;
+  let mut results = Vec::new();
+  while let Some(row) = rows.next()? {
+    results.push(map_guest_row(row)?);
+  }
+  Ok(results)
+}
+
+fn map_guest_row(row: &Row<'_>) -> rusqlite::Result<GuestSearchResult> {
+  Ok(GuestSearchResult {
+    id: row.get(0)?,
+    display_name: row.get(1)?,
+    member_host: row.get(2)?,
+    is_checked_in: row.get(3)?,
+    has_history: row.get(4)?,
+    is_banned: row.get(5)?,
+    banned_reason: row.get(6)?,
+    is_removed: row.get(7)?,
+    score: None,
+  })
+}
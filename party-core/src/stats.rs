@@ -0,0 +1,123 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::events::fetch_event;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSummary {
+  pub total_guests: i64,
+  pub total_check_ins: i64,
+  pub total_check_outs: i64,
+  pub currently_present: i64,
+  /// Guests added at the door rather than from the list.
+  pub walk_ins: i64,
+  pub capacity: Option<i64>,
+  /// Occupancy at which the event counts as nearly full.
+  pub capacity_warning_at: Option<i64>,
+  pub near_capacity: bool,
+  pub present_guests: Vec<PresentGuest>,
+  pub top_hosts: Vec<HostSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentGuest {
+  pub id: i64,
+  pub display_name: String,
+  pub member_host: Option<String>,
+  pub in_ts: Option<String>,
+  pub operator: Option<String>,
+  pub is_walk_in: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostSummary {
+  pub member_host: String,
+  pub total_guests: i64,
+  pub present_guests: i64,
+}
+
+pub fn stats_summary(conn: &Connection, event_id: i64) -> Result<StatsSummary> {
+  let event = fetch_event(conn, event_id)?;
+
+  let (total_guests, walk_ins): (i64, i64) = conn
+    .query_row(
+      "SELECT COUNT(*), COALESCE(SUM(is_walk_in), 0) FROM guests WHERE event_id = ?1 AND removed_at IS NULL",
+      params![event_id],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .unwrap_or((0, 0));
+
+  let (total_check_ins, total_check_outs, currently_present) = conn
+    .query_row(
+      "SELECT
+        (SELECT COUNT(*) FROM checkins WHERE event_id = ?1 AND in_ts IS NOT NULL) as check_ins,
+        (SELECT COUNT(*) FROM checkins WHERE event_id = ?1 AND out_ts IS NOT NULL) as check_outs,
+        (SELECT COUNT(*) FROM checkins WHERE event_id = ?1 AND out_ts IS NULL) as present",
+      params![event_id],
+      |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .unwrap_or((0, 0, 0));
+
+  let mut present_stmt = conn.prepare_cached(
+    "SELECT g.id, g.display_name, g.member_host, c.in_ts, c.in_by, g.is_walk_in
+     FROM checkins c
+     JOIN guests g ON g.id = c.guest_id
+     WHERE c.event_id = ?1 AND c.out_ts IS NULL
+     ORDER BY c.in_ts DESC
+     LIMIT 200",
+  )?;
+  let mut present_rows = present_stmt.query(params![event_id])?;
+  let mut present_guests = Vec::new();
+  while let Some(row) = present_rows.next()? {
+    present_guests.push(PresentGuest {
+      id: row.get(0)?,
+      display_name: row.get(1)?,
+      member_host: row.get(2)?,
+      in_ts: row.get(3)?,
+      operator: row.get(4)?,
+      is_walk_in: row.get(5)?,
+    });
+  }
+
+  let mut host_stmt = conn.prepare_cached(
+    "SELECT g.member_host as host,
+      COUNT(*) as total_guests,
+      SUM(CASE WHEN EXISTS(SELECT 1 FROM checkins c WHERE c.guest_id = g.id AND c.out_ts IS NULL) THEN 1 ELSE 0 END) as present_guests
+     FROM guests g
+     WHERE g.event_id = ?1 AND g.member_host IS NOT NULL AND g.member_host != '' AND g.removed_at IS NULL
+     GROUP BY host
+     ORDER BY present_guests DESC, total_guests DESC
+     LIMIT 10",
+  )?;
+  let mut host_rows = host_stmt.query(params![event_id])?;
+  let mut top_hosts = Vec::new();
+  while let Some(row) = host_rows.next()? {
+    top_hosts.push(HostSummary {
+      member_host: row.get(0)?,
+      total_guests: row.get(1)?,
+      present_guests: row.get(2)?,
+    });
+  }
+
+  // Round the threshold up so a 90% warning on 15 seats fires at 14, not 13.
+  let capacity_warning_at = event
+    .capacity
+    .map(|capacity| (capacity * event.capacity_warning_percent + 99) / 100);
+
+  Ok(StatsSummary {
+    total_guests,
+    total_check_ins,
+    total_check_outs,
+    currently_present,
+    walk_ins,
+    capacity: event.capacity,
+    capacity_warning_at,
+    near_capacity: capacity_warning_at.is_some_and(|at| currently_present >= at),
+    present_guests,
+    top_hosts,
+  })
+}
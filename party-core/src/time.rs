@@ -0,0 +1,144 @@
+//! Timestamps are stored as RFC 3339 UTC instants and shown in the event's
+//! timezone.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{Error, Result};
+use crate::events::Event;
+
+pub const DEFAULT_TIMEZONE: &str = "America/Chicago";
+
+/// Calendar context used to pin a bare time of day onto a real instant.
+#[derive(Debug, Clone, Copy)]
+pub struct EventDay {
+  pub date: NaiveDate,
+  pub rollover: NaiveTime,
+  pub tz: Tz,
+}
+
+pub fn now_timestamp() -> String {
+  to_timestamp(Utc::now())
+}
+
+pub fn to_timestamp(value: DateTime<Utc>) -> String {
+  value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+pub fn format_local_timestamp(value: &str, tz: Tz) -> String {
+  match DateTime::parse_from_rfc3339(value) {
+    Ok(dt) => dt.with_timezone(&tz).format("%m/%d/%Y %I:%M:%S %p").to_string(),
+    Err(_) => value.to_string(),
+  }
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+  name
+    .parse::<Tz>()
+    .map_err(|_| Error::Invalid(format!("unknown timezone {}", name)))
+}
+
+fn local_to_utc(value: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+  // A wall-clock time skipped by a DST jump doesn't exist; the hour after it does.
+  tz
+    .from_local_datetime(&value)
+    .earliest()
+    .or_else(|| tz.from_local_datetime(&(value + Duration::hours(1))).earliest())
+    .map(|dt| dt.with_timezone(&Utc))
+    .unwrap_or_else(|| Utc.from_utc_datetime(&value))
+}
+
+/// Shifts an instant so it reads the same on the wall clock in `to` as it
+/// did in `from`.
+pub fn rezone_timestamp(value: &str, from: Tz, to: Tz) -> String {
+  match DateTime::parse_from_rfc3339(value) {
+    Ok(dt) => to_timestamp(local_to_utc(dt.with_timezone(&from).naive_local(), to)),
+    Err(_) => value.to_string(),
+  }
+}
+
+pub fn event_day(event: &Event) -> EventDay {
+  let tz = event.timezone.parse::<Tz>().unwrap_or(chrono_tz::America::Chicago);
+  let date = event
+    .event_date
+    .as_deref()
+    .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    .unwrap_or_else(|| Utc::now().with_timezone(&tz).date_naive());
+  let noon = NaiveTime::from_hms_opt(12, 0, 0).expect("valid time");
+  let rollover = event
+    .doors_open
+    .as_deref()
+    .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+    .map(|doors| doors.min(noon))
+    .unwrap_or_else(|| NaiveTime::from_hms_opt(6, 0, 0).expect("valid time"));
+  EventDay { date, rollover, tz }
+}
+
+/// Places a time of day on the event's night: anything before the rollover
+/// (doors open, or 6 AM when unknown) is taken to be after midnight.
+fn resolve_event_time(day: &EventDay, time: NaiveTime) -> DateTime<Utc> {
+  let date = if time < day.rollover {
+    day.date + Duration::days(1)
+  } else {
+    day.date
+  };
+  local_to_utc(date.and_time(time), day.tz)
+}
+
+/// A check-out that lands before its check-in crossed midnight.
+pub fn ensure_after(out_ts: String, in_ts: Option<&str>) -> String {
+  let (Ok(out), Some(Ok(in_))) = (
+    DateTime::parse_from_rfc3339(&out_ts),
+    in_ts.map(DateTime::parse_from_rfc3339),
+  ) else {
+    return out_ts;
+  };
+  if out < in_ && in_ - out < Duration::days(1) {
+    to_timestamp(out.with_timezone(&Utc) + Duration::days(1))
+  } else {
+    out_ts
+  }
+}
+
+pub fn parse_import_timestamp(value: Option<&str>, day: &EventDay) -> Option<String> {
+  let raw = value?.trim();
+  if raw.is_empty() {
+    return None;
+  }
+
+  const TIME_FORMATS: &[&str] = &[
+    "%I:%M:%S %p",
+    "%I:%M %p",
+    "%H:%M:%S",
+    "%H:%M",
+    "%I%M%p",
+    "%H%M%S",
+  ];
+
+  for fmt in TIME_FORMATS {
+    if let Ok(time) = NaiveTime::parse_from_str(raw, fmt) {
+      return Some(to_timestamp(resolve_event_time(day, time)));
+    }
+  }
+
+  const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %I:%M %p",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+  ];
+
+  for fmt in DATETIME_FORMATS {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(raw, fmt) {
+      return Some(to_timestamp(local_to_utc(dt, day.tz)));
+    }
+  }
+
+  if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+    return Some(to_timestamp(dt.with_timezone(&Utc)));
+  }
+
+  None
+}
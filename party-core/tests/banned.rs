@@ -0,0 +1,64 @@
+mod common;
+
+use common::{guest_id, row};
+use party_core::{
+  banned::{self, BanInput},
+  checkins::{self, CheckAction, Overrides, ToggleStatus},
+  import::ImportMode,
+  search,
+};
+
+fn ban(db: &mut common::TestDb, name: &str) -> i64 {
+  let input = BanInput {
+    name: name.into(),
+    reason: Some("fight at spring rush".into()),
+  };
+  banned::add_banned(&mut db.conn, input, Some("chair")).unwrap().id
+}
+
+#[test]
+fn import_flags_close_matches() {
+  let mut db = common::open();
+  ban(&mut db, "Jonathan Smythe");
+  ban(&mut db, "Al Li");
+
+  let rows = [row("Amy Lee", "jonathan  SMYTH, Smythe Jonathan, Al Lu, Jane Doe")];
+  let summary = common::import(&mut db, &rows, ImportMode::Append);
+
+  let mut flagged: Vec<_> = summary.banned.iter().map(|m| m.guest_name.as_str()).collect();
+  flagged.sort_unstable();
+  // A one-letter typo on a long name still matches; on a short name it does not.
+  assert_eq!(flagged, ["Smythe Jonathan", "jonathan SMYTH"]);
+  assert_eq!(summary.banned[0].banned_name, "Jonathan Smythe");
+  assert_eq!(summary.banned[0].reason.as_deref(), Some("fight at spring rush"));
+}
+
+#[test]
+fn banned_guests_are_flagged_and_refused() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("Amy Lee", "Carl Poe, Jane Doe")], ImportMode::Append);
+  // Banning after import flags guests already on the list.
+  let ban_id = ban(&mut db, "Carl Poe");
+
+  let results = search::search_guests(&db.conn, event_id, "carl", None).unwrap();
+  assert!(results[0].is_banned);
+  assert_eq!(results[0].banned_reason.as_deref(), Some("fight at spring rush"));
+
+  let carl = guest_id(&db.conn, event_id, "Carl Poe");
+  let check_in = |db: &mut common::TestDb, overrides| {
+    checkins::toggle_checkin(&mut db.conn, event_id, carl, CheckAction::In, Some("door"), false, overrides)
+      .unwrap()
+      .status
+  };
+  assert_eq!(check_in(&mut db, Overrides::default()), ToggleStatus::Banned);
+  let admit = Overrides {
+    banned: true,
+    ..Overrides::default()
+  };
+  assert_eq!(check_in(&mut db, admit), ToggleStatus::CheckedIn);
+
+  banned::remove_banned(&mut db.conn, ban_id, Some("chair")).unwrap();
+  let results = search::search_guests(&db.conn, event_id, "carl", None).unwrap();
+  assert!(!results[0].is_banned);
+}
@@ -0,0 +1,296 @@
+mod common;
+
+use common::{checkins, guest_id, row};
+use party_core::{
+  banned::{self, BanInput},
+  checkins::{self, CheckAction, Overrides, RedoStatus, ToggleStatus, UndoStatus},
+  audit::{self, AuditFilter},
+  events::{self, EventInput},
+  import::{CsvRow, ImportMode},
+  quotas::{self, MemberQuota},
+  stats, Error,
+};
+
+fn seeded() -> (common::TestDb, i64) {
+  let mut db = common::open();
+  let rows = [row("John Smith", "Jane Doe")];
+  common::import(&mut db, &rows, ImportMode::Append);
+  let guest = guest_id(&db.conn, db.event.id, "Jane Doe");
+  (db, guest)
+}
+
+#[test]
+fn toggling_in_and_out() {
+  let (mut db, guest) = seeded();
+  let event_id = db.event.id;
+  let toggle = |db: &mut common::TestDb, action| {
+    let overrides = Overrides::default();
+    checkins::toggle_checkin(&mut db.conn, event_id, guest, action, Some("door"), false, overrides)
+      .unwrap()
+      .status
+  };
+
+  assert_eq!(toggle(&mut db, CheckAction::Out), ToggleStatus::NeverCheckedIn);
+  assert_eq!(toggle(&mut db, CheckAction::In), ToggleStatus::CheckedIn);
+  assert_eq!(toggle(&mut db, CheckAction::In), ToggleStatus::AlreadyIn);
+  assert_eq!(toggle(&mut db, CheckAction::Out), ToggleStatus::CheckedOut);
+  assert_eq!(toggle(&mut db, CheckAction::Out), ToggleStatus::NotCheckedIn);
+
+  let rows = checkins(&db.conn, guest);
+  assert_eq!(rows.len(), 1);
+  assert!(rows[0].1.is_some());
+}
+
+#[test]
+fn forced_checkout_records_a_visit() {
+  let (mut db, guest) = seeded();
+
+  let result = checkins::toggle_checkin(
+    &mut db.conn,
+    db.event.id,
+    guest,
+    CheckAction::Out,
+    None,
+    true,
+    Overrides::default(),
+  )
+  .unwrap();
+  assert_eq!(result.status, ToggleStatus::CheckedOut);
+
+  let rows = checkins(&db.conn, guest);
+  assert_eq!(rows.len(), 1);
+  assert_eq!(Some(&rows[0].0), rows[0].1.as_ref());
+}
+
+#[test]
+fn undo_and_redo_walk_the_history() {
+  let (mut db, guest) = seeded();
+  let event_id = db.event.id;
+
+  let none = Overrides::default();
+  checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::In, None, false, none).unwrap();
+  checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::Out, None, false, none).unwrap();
+
+  let undo = |db: &mut common::TestDb| {
+    checkins::undo_last(&mut db.conn, event_id, Some("chair")).unwrap().status
+  };
+  assert_eq!(undo(&mut db), UndoStatus::RevertedCheckOut);
+  assert_eq!(checkins(&db.conn, guest)[0].1, None);
+  assert_eq!(undo(&mut db), UndoStatus::RevertedCheckIn);
+  assert!(checkins(&db.conn, guest).is_empty());
+  assert_eq!(undo(&mut db), UndoStatus::Empty);
+
+  let redo = checkins::redo_last(&mut db.conn, event_id, None).unwrap();
+  assert_eq!(redo.status, RedoStatus::ReappliedCheckIn);
+  assert_eq!(checkins(&db.conn, guest).len(), 1);
+
+  // A new action discards what is left to redo.
+  checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::Out, None, false, none).unwrap();
+  let redo = checkins::redo_last(&mut db.conn, event_id, None).unwrap();
+  assert_eq!(redo.status, RedoStatus::Empty);
+}
+
+#[test]
+fn redo_survives_its_checkin_id_being_reused() {
+  let (mut db, guest) = seeded();
+  let event_id = db.event.id;
+
+  let none = Overrides::default();
+  checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::In, None, false, none).unwrap();
+  checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::Out, None, false, none).unwrap();
+  checkins::undo_last(&mut db.conn, event_id, None).unwrap();
+  checkins::undo_last(&mut db.conn, event_id, None).unwrap();
+
+  // An import with check-in times takes the freed id; it leaves the redo
+  // entries alone.
+  let rows = [CsvRow {
+    check_in_time: Some("11:30 PM".into()),
+    ..row("Amy Lee", "Bob Roe")
+  }];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  let redo = checkins::redo_last(&mut db.conn, event_id, None).unwrap();
+  assert_eq!(redo.status, RedoStatus::ReappliedCheckIn);
+  let redo = checkins::redo_last(&mut db.conn, event_id, None).unwrap();
+  assert_eq!(redo.status, RedoStatus::ReappliedCheckOut);
+  assert!(checkins(&db.conn, guest)[0].1.is_some());
+  let other = guest_id(&db.conn, event_id, "Bob Roe");
+  assert_eq!(checkins(&db.conn, other).len(), 1);
+  assert_eq!(checkins(&db.conn, other)[0].1, None);
+}
+
+#[test]
+fn undo_of_a_forced_checkout_removes_it() {
+  let (mut db, guest) = seeded();
+
+  let none = Overrides::default();
+  checkins::toggle_checkin(&mut db.conn, db.event.id, guest, CheckAction::Out, None, true, none).unwrap();
+  let undo = checkins::undo_last(&mut db.conn, db.event.id, None).unwrap();
+
+  assert_eq!(undo.status, UndoStatus::RevertedCheckOut);
+  assert!(checkins(&db.conn, guest).is_empty());
+}
+
+#[test]
+fn guests_are_scoped_to_their_event() {
+  let (mut db, guest) = seeded();
+
+  let none = Overrides::default();
+  let err = checkins::toggle_checkin(&mut db.conn, db.event.id + 1, guest, CheckAction::In, None, false, none)
+    .unwrap_err();
+  assert!(matches!(err, Error::GuestNotFound { .. }), "{:?}", err);
+}
+
+#[test]
+fn capacity_turns_guests_away_unless_overridden() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let input = EventInput {
+    name: db.event.name.clone(),
+    event_date: db.event.event_date.clone(),
+    doors_open: db.event.doors_open.clone(),
+    capacity: Some(2),
+    capacity_warning_percent: Some(50),
+    ..EventInput::default()
+  };
+  events::update_event(&db.conn, event_id, input).unwrap();
+  let rows = [row("John Smith", "Jane Doe, Bob Roe, Carl Poe")];
+  common::import(&mut db, &rows, ImportMode::Append);
+  let check_in = |db: &mut common::TestDb, name: &str, overrides| {
+    let guest = guest_id(&db.conn, event_id, name);
+    checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::In, Some("door"), false, overrides)
+      .unwrap()
+      .status
+  };
+
+  assert_eq!(check_in(&mut db, "Jane Doe", Overrides::default()), ToggleStatus::CheckedIn);
+  let summary = stats::stats_summary(&db.conn, event_id).unwrap();
+  assert_eq!(summary.capacity_warning_at, Some(1));
+  assert!(summary.near_capacity);
+
+  assert_eq!(check_in(&mut db, "Bob Roe", Overrides::default()), ToggleStatus::CheckedIn);
+  assert_eq!(check_in(&mut db, "Carl Poe", Overrides::default()), ToggleStatus::AtCapacity);
+  let admit = Overrides {
+    capacity: true,
+    ..Overrides::default()
+  };
+  assert_eq!(check_in(&mut db, "Carl Poe", admit), ToggleStatus::CheckedIn);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().currently_present, 3);
+
+  let filter = AuditFilter {
+    event_id: Some(event_id),
+    ..AuditFilter::default()
+  };
+  let overrides: Vec<_> = audit::query_audit_log(&db.conn, filter)
+    .unwrap()
+    .into_iter()
+    .filter(|entry| entry.action == "capacity_override")
+    .collect();
+  assert_eq!(overrides.len(), 1);
+  assert_eq!(overrides[0].operator.as_deref(), Some("door"));
+}
+
+#[test]
+fn refused_check_ins_log_no_waivers() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let input = EventInput {
+    name: db.event.name.clone(),
+    event_date: db.event.event_date.clone(),
+    doors_open: db.event.doors_open.clone(),
+    capacity: Some(1),
+    ..EventInput::default()
+  };
+  events::update_event(&db.conn, event_id, input).unwrap();
+  common::import(&mut db, &[row("John Smith", "Amy Lee, Jane Doe")], ImportMode::Append);
+  let ban = BanInput {
+    name: "Jane Doe".into(),
+    reason: None,
+  };
+  banned::add_banned(&mut db.conn, ban, None).unwrap();
+  let quota = MemberQuota {
+    member_host: "John Smith".into(),
+    guest_quota: None,
+    present_quota: Some(1),
+  };
+  quotas::set_member_quota(&db.conn, event_id, quota, None).unwrap();
+  let check_in = |db: &mut common::TestDb, name: &str, overrides| {
+    let guest = guest_id(&db.conn, event_id, name);
+    checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::In, Some("door"), false, overrides)
+      .unwrap()
+      .status
+  };
+  assert_eq!(check_in(&mut db, "Amy Lee", Overrides::default()), ToggleStatus::CheckedIn);
+
+  // Jane's ban and John's quota are waived, but the venue is full, so she is
+  // not let in and nothing was waived.
+  let waive = Overrides {
+    banned: true,
+    quota: true,
+    ..Overrides::default()
+  };
+  assert_eq!(check_in(&mut db, "Jane Doe", waive), ToggleStatus::AtCapacity);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  let logged = |db: &common::TestDb| -> Vec<String> {
+    let filter = AuditFilter {
+      event_id: Some(event_id),
+      guest_id: Some(jane),
+      ..AuditFilter::default()
+    };
+    audit::query_audit_log(&db.conn, filter)
+      .unwrap()
+      .into_iter()
+      .map(|entry| entry.action)
+      .collect()
+  };
+  assert!(logged(&db).is_empty(), "{:?}", logged(&db));
+
+  let admit = Overrides {
+    capacity: true,
+    ..waive
+  };
+  assert_eq!(check_in(&mut db, "Jane Doe", admit), ToggleStatus::CheckedIn);
+  let mut actions = logged(&db);
+  actions.sort_unstable();
+  assert_eq!(actions, ["banned_override", "capacity_override", "check_in", "quota_override"]);
+}
+
+#[test]
+fn member_quotas_limit_check_ins() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe, Bob Roe, Carl Poe")], ImportMode::Append);
+  let quota = MemberQuota {
+    member_host: "john smith".into(),
+    guest_quota: Some(2),
+    present_quota: Some(1),
+  };
+  quotas::set_member_quota(&db.conn, event_id, quota, None).unwrap();
+  let check_in = |db: &mut common::TestDb, name: &str, overrides| {
+    let guest = guest_id(&db.conn, event_id, name);
+    checkins::toggle_checkin(&mut db.conn, event_id, guest, CheckAction::In, Some("door"), false, overrides)
+      .unwrap()
+      .status
+  };
+
+  // Carl is third on John's list, past the guest quota of two.
+  assert_eq!(check_in(&mut db, "Carl Poe", Overrides::default()), ToggleStatus::OverGuestQuota);
+  assert_eq!(check_in(&mut db, "Jane Doe", Overrides::default()), ToggleStatus::CheckedIn);
+  assert_eq!(check_in(&mut db, "Bob Roe", Overrides::default()), ToggleStatus::MemberQuotaFull);
+
+  let admit = Overrides {
+    quota: true,
+    ..Overrides::default()
+  };
+  assert_eq!(check_in(&mut db, "Bob Roe", admit), ToggleStatus::CheckedIn);
+  let filter = AuditFilter {
+    event_id: Some(event_id),
+    ..AuditFilter::default()
+  };
+  let overrides = audit::query_audit_log(&db.conn, filter)
+    .unwrap()
+    .into_iter()
+    .filter(|entry| entry.action == "quota_override")
+    .count();
+  assert_eq!(overrides, 1);
+}
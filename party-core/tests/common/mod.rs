@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+
+use party_core::{
+  checkins::Overrides,
+  db,
+  events::{self, Event, EventInput},
+  import::{self, CsvRow, ImportMode, ImportSummary},
+};
+use rusqlite::{params, Connection};
+use tempfile::TempDir;
+
+/// A fresh database holding one event: Saturday 10 April 2026, doors at
+/// 8 PM, in Chicago (UTC-5 that night).
+pub struct TestDb {
+  pub conn: Connection,
+  pub event: Event,
+  pub dir: TempDir,
+}
+
+pub fn open() -> TestDb {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let path = dir.path().join("party.db");
+  let conn = db::open(path.to_str().expect("utf-8 path")).expect("open db");
+  let event = events::create_event(
+    &conn,
+    EventInput {
+      name: "Spring Formal".into(),
+      event_date: Some("2026-04-10".into()),
+      venue: None,
+      doors_open: Some("20:00".into()),
+      doors_close: None,
+      timezone: Some("America/Chicago".into()),
+      ..EventInput::default()
+    },
+  )
+  .expect("create event");
+  TestDb { conn, event, dir }
+}
+
+/// Imports `rows` into the test event with no operator or overrides.
+pub fn import(db: &mut TestDb, rows: &[CsvRow], mode: ImportMode) -> ImportSummary {
+  import::import_rows(&mut db.conn, db.event.id, rows, mode, None, Overrides::default()).expect("import")
+}
+
+pub fn row(member: &str, guests: &str) -> CsvRow {
+  CsvRow {
+    member_name: Some(member.into()),
+    guest_names: Some(guests.into()),
+    ..CsvRow::default()
+  }
+}
+
+pub fn guest_id(conn: &Connection, event_id: i64, name: &str) -> i64 {
+  conn
+    .query_row(
+      "SELECT id FROM guests WHERE event_id = ?1 AND display_name = ?2",
+      params![event_id, name],
+      |row| row.get(0),
+    )
+    .unwrap_or_else(|_| panic!("guest {} not found", name))
+}
+
+pub fn guest_names(conn: &Connection, event_id: i64) -> Vec<String> {
+  let mut stmt = conn
+    .prepare("SELECT display_name FROM guests WHERE event_id = ?1 ORDER BY display_name")
+    .unwrap();
+  let names = stmt
+    .query_map(params![event_id], |row| row.get(0))
+    .unwrap()
+    .collect::<rusqlite::Result<_>>()
+    .unwrap();
+  names
+}
+
+/// `(in_ts, out_ts)` for each of the guest's check-ins, oldest first.
+pub fn checkins(conn: &Connection, guest_id: i64) -> Vec<(String, Option<String>)> {
+  let mut stmt = conn
+    .prepare("SELECT in_ts, out_ts FROM checkins WHERE guest_id = ?1 ORDER BY id")
+    .unwrap();
+  let rows = stmt
+    .query_map(params![guest_id], |row| Ok((row.get(0)?, row.get(1)?)))
+    .unwrap()
+    .collect::<rusqlite::Result<_>>()
+    .unwrap();
+  rows
+}
+
+/// The member the guest is listed under.
+pub fn host(conn: &Connection, guest_id: i64) -> Option<String> {
+  conn
+    .query_row("SELECT member_host FROM guests WHERE id = ?1", params![guest_id], |row| row.get(0))
+    .unwrap()
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{checkins, guest_id, row};
+use party_core::{
+  checkins::{self as door, CheckAction, Overrides},
+  events::{self, EventInput},
+  import::ImportMode,
+  Error,
+};
+use rusqlite::params;
+
+/// Jane Doe on the test event, checked in at `in_ts` and still inside.
+fn checked_in_at(db: &mut common::TestDb, in_ts: &str) -> i64 {
+  common::import(db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, db.event.id, "Jane Doe");
+  door::toggle_checkin(&mut db.conn, db.event.id, jane, CheckAction::In, None, false, Overrides::default()).unwrap();
+  db.conn
+    .execute("UPDATE checkins SET in_ts = ?1 WHERE guest_id = ?2", params![in_ts, jane])
+    .unwrap();
+  jane
+}
+
+fn details(db: &common::TestDb, name: &str, timezone: &str) -> EventInput {
+  EventInput {
+    name: name.into(),
+    event_date: db.event.event_date.clone(),
+    doors_open: db.event.doors_open.clone(),
+    timezone: Some(timezone.into()),
+    ..EventInput::default()
+  }
+}
+
+#[test]
+fn updates_keep_the_timezone() {
+  let db = common::open();
+  let event_id = db.event.id;
+
+  let input = details(&db, "Spring Formal", "America/New_York");
+  let err = events::update_event(&db.conn, event_id, input).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+  assert_eq!(events::fetch_event(&db.conn, event_id).unwrap().timezone, "America/Chicago");
+
+  // Repeating the current timezone, or leaving it out, is fine.
+  let input = details(&db, "Spring Ball", "America/Chicago");
+  assert_eq!(events::update_event(&db.conn, event_id, input).unwrap().name, "Spring Ball");
+  let input = EventInput {
+    timezone: None,
+    ..details(&db, "Spring Formal", "")
+  };
+  let event = events::update_event(&db.conn, event_id, input).unwrap();
+  assert_eq!((event.name.as_str(), event.timezone.as_str()), ("Spring Formal", "America/Chicago"));
+}
+
+#[test]
+fn timezone_changes_keep_the_instant_by_default() {
+  let mut db = common::open();
+  // 10:05 PM in Chicago (CDT, UTC-5).
+  let jane = checked_in_at(&mut db, "2026-04-11T03:05:00Z");
+
+  let event = events::set_event_timezone(&mut db.conn, db.event.id, " America/New_York ", false).unwrap();
+  assert_eq!(event.timezone, "America/New_York");
+  assert_eq!(checkins(&db.conn, jane), [("2026-04-11T03:05:00Z".to_string(), None)]);
+}
+
+#[test]
+fn timezone_changes_can_keep_the_wall_clock() {
+  let mut db = common::open();
+  let jane = checked_in_at(&mut db, "2026-04-11T03:05:00Z");
+
+  // Still 10:05 PM, now in New York (EDT, UTC-4).
+  events::set_event_timezone(&mut db.conn, db.event.id, "America/New_York", true).unwrap();
+  assert_eq!(checkins(&db.conn, jane), [("2026-04-11T02:05:00Z".to_string(), None)]);
+}
+
+#[test]
+fn wall_clock_times_skipped_by_dst_move_to_the_next_hour() {
+  let mut db = common::open();
+  events::set_event_timezone(&mut db.conn, db.event.id, "America/Phoenix", false).unwrap();
+  // 2:30 AM on 8 March 2026 in Phoenix (MST all year, UTC-7). Chicago skips
+  // from 2 AM to 3 AM that night, so 2:30 does not exist there.
+  let jane = checked_in_at(&mut db, "2026-03-08T09:30:00Z");
+
+  events::set_event_timezone(&mut db.conn, db.event.id, "America/Chicago", true).unwrap();
+  // 3:30 AM CDT.
+  assert_eq!(checkins(&db.conn, jane), [("2026-03-08T08:30:00Z".to_string(), None)]);
+}
+
+#[test]
+fn details_and_timezone_change_together_or_not_at_all() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let jane = checked_in_at(&mut db, "2026-04-11T03:05:00Z");
+
+  let input = details(&db, "  ", "America/New_York");
+  let err = events::update_event_with_timezone(&mut db.conn, event_id, input, true).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+  assert_eq!(events::fetch_event(&db.conn, event_id).unwrap().timezone, "America/Chicago");
+  assert_eq!(checkins(&db.conn, jane), [("2026-04-11T03:05:00Z".to_string(), None)]);
+
+  let input = details(&db, "Spring Ball", "America/New_York");
+  let event = events::update_event_with_timezone(&mut db.conn, event_id, input, true).unwrap();
+  assert_eq!((event.name.as_str(), event.timezone.as_str()), ("Spring Ball", "America/New_York"));
+  assert_eq!(checkins(&db.conn, jane), [("2026-04-11T02:05:00Z".to_string(), None)]);
+}
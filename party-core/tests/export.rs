@@ -0,0 +1,64 @@
+mod common;
+
+use common::row;
+use party_core::{
+  checkins::Overrides,
+  export,
+  import::{self, CsvRow, ImportMode},
+};
+
+#[test]
+fn export_shows_local_times() {
+  let mut db = common::open();
+  let rows = [
+    CsvRow {
+      check_in_time: Some("11:30 PM".into()),
+      check_out_time: Some("12:10 AM".into()),
+      ..row("John Smith", "Jane Doe")
+    },
+    row("Amy Lee", "Carl Poe"),
+  ];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  let csv = String::from_utf8(export::render_csv(&db.conn, db.event.id).unwrap()).unwrap();
+  assert_eq!(
+    csv,
+    "Member Name,Guest Name,Check In Y/N,Check In Time,Check Out Y/N,Check Out Time,Walk-In Y/N\n\
+     Amy Lee,Carl Poe,N,,N,,N\n\
+     John Smith,Jane Doe,Y,04/10/2026 11:30:00 PM,Y,04/11/2026 12:10:00 AM,N\n"
+  );
+}
+
+#[test]
+fn export_round_trips_through_import() {
+  let mut db = common::open();
+  let rows = [CsvRow {
+    check_in_time: Some("9:15 PM".into()),
+    ..row("John Smith", "Jane Doe")
+  }];
+  common::import(&mut db, &rows, ImportMode::Append);
+  let first = export::render_csv(&db.conn, db.event.id).unwrap();
+
+  let reread = import::read_csv(first.as_slice()).unwrap();
+  assert_eq!(reread.len(), 1);
+  // Jane is still inside, so the replace has to be confirmed.
+  let confirmed = Overrides {
+    checked_in: true,
+    ..Overrides::default()
+  };
+  import::import_rows(&mut db.conn, db.event.id, &reread, ImportMode::Replace, None, confirmed).unwrap();
+
+  assert_eq!(export::render_csv(&db.conn, db.event.id).unwrap(), first);
+}
+
+#[test]
+fn export_writes_a_named_file() {
+  let db = common::open();
+
+  let path = export::export_csv(&db.conn, db.event.id, db.dir.path().to_str()).unwrap();
+
+  let name = std::path::Path::new(&path).file_name().unwrap().to_str().unwrap();
+  assert!(name.starts_with("party-sign-in-spring-formal-"), "{}", name);
+  assert!(name.ends_with(".csv"));
+  assert!(std::fs::metadata(&path).unwrap().len() > 0);
+}
@@ -0,0 +1,253 @@
+mod common;
+
+use common::{checkins, guest_id, guest_names, host, row};
+use party_core::{
+  checkins::{self as door, CheckAction, Overrides, RedoStatus, ToggleStatus, UndoStatus},
+  events::{self, EventInput},
+  export,
+  guests::{self, GuestEdit, WalkInInput},
+  import::ImportMode,
+  stats, Error,
+};
+
+fn walk_in(name: &str, host: Option<&str>) -> WalkInInput {
+  WalkInInput {
+    display_name: name.into(),
+    member_host: host.map(Into::into),
+  }
+}
+
+#[test]
+fn walk_ins_are_added_checked_in_and_reported() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+
+  let result = guests::add_guest(
+    &mut db.conn,
+    event_id,
+    walk_in("  pat   o'neil ", Some("John Smith")),
+    Some("door"),
+    Overrides::default(),
+  )
+  .unwrap();
+  assert_eq!(result.status, ToggleStatus::CheckedIn);
+  let guest_id = result.guest_id.unwrap();
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe", "pat o'neil"]);
+
+  let summary = stats::stats_summary(&db.conn, event_id).unwrap();
+  assert_eq!((summary.total_guests, summary.walk_ins, summary.currently_present), (2, 1, 1));
+  let present = &summary.present_guests[0];
+  assert_eq!((present.id, present.is_walk_in), (guest_id, true));
+  assert_eq!(present.operator.as_deref(), Some("door"));
+
+  let added_by: Option<String> = db
+    .conn
+    .query_row("SELECT added_by FROM guests WHERE id = ?1", [guest_id], |row| row.get(0))
+    .unwrap();
+  assert_eq!(added_by.as_deref(), Some("door"));
+
+  let csv = String::from_utf8(export::render_csv(&db.conn, event_id).unwrap()).unwrap();
+  let lines: Vec<&str> = csv.lines().collect();
+  assert!(lines[0].ends_with(",Walk-In Y/N"));
+  assert!(lines[1].starts_with("John Smith,Jane Doe,N,") && lines[1].ends_with(",N"));
+  assert!(lines[2].starts_with("John Smith,pat o'neil,Y,") && lines[2].ends_with(",Y"));
+
+  // Undo takes back the check-in but keeps the guest on the list.
+  let undo = door::undo_last(&mut db.conn, event_id, Some("door")).unwrap();
+  assert_eq!(undo.status, UndoStatus::RevertedCheckIn);
+  assert_eq!(guest_names(&db.conn, event_id).len(), 2);
+}
+
+#[test]
+fn refused_walk_ins_are_not_added() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let input = EventInput {
+    name: db.event.name.clone(),
+    capacity: Some(1),
+    ..EventInput::default()
+  };
+  events::update_event(&db.conn, event_id, input).unwrap();
+
+  let add = |db: &mut common::TestDb, name: &str, overrides| {
+    guests::add_guest(&mut db.conn, event_id, walk_in(name, None), None, overrides)
+  };
+  assert_eq!(add(&mut db, "Amy Lee", Overrides::default()).unwrap().status, ToggleStatus::CheckedIn);
+
+  let refused = add(&mut db, "Bob Roe", Overrides::default()).unwrap();
+  assert_eq!(refused.status, ToggleStatus::AtCapacity);
+  assert_eq!(refused.guest_id, None);
+  assert_eq!(guest_names(&db.conn, event_id), ["Amy Lee"]);
+
+  let overrides = Overrides {
+    capacity: true,
+    ..Overrides::default()
+  };
+  assert!(add(&mut db, "Bob Roe", overrides).unwrap().guest_id.is_some());
+
+  let err = add(&mut db, "amy lee", Overrides::default()).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+  let err = add(&mut db, "   ", Overrides::default()).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+}
+
+fn toggle(db: &mut common::TestDb, guest_id: i64, action: CheckAction) {
+  door::toggle_checkin(&mut db.conn, db.event.id, guest_id, action, None, false, Overrides::default()).unwrap();
+}
+
+fn undo(db: &mut common::TestDb) -> UndoStatus {
+  door::undo_last(&mut db.conn, db.event.id, None).unwrap().status
+}
+
+fn redo(db: &mut common::TestDb) -> RedoStatus {
+  door::redo_last(&mut db.conn, db.event.id, None).unwrap().status
+}
+
+#[test]
+fn edits_are_checked_and_undoable() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe, Bob Roe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+
+  let edit = |name: &str, host: Option<&str>| GuestEdit {
+    display_name: name.into(),
+    member_host: host.map(Into::into),
+  };
+  guests::edit_guest(&mut db.conn, event_id, jane, edit(" JANE   DOWE ", Some("Amy Lee")), Some("door")).unwrap();
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Jane Dowe"]);
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("Amy Lee"));
+
+  let err = guests::edit_guest(&mut db.conn, event_id, jane, edit("bob roe", Some("john smith")), None).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+  let err = guests::edit_guest(&mut db.conn, event_id, jane, edit("  ", None), None).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+
+  assert_eq!(undo(&mut db), UndoStatus::RevertedEdit);
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Jane Doe"]);
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("John Smith"));
+  assert_eq!(redo(&mut db), RedoStatus::ReappliedEdit);
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Jane Dowe"]);
+
+  let history = door::list_undo_history(&db.conn, event_id, Some(5)).unwrap();
+  assert_eq!((history[0].action.as_str(), history[0].display_name.as_str()), ("edit_guest", "Jane Dowe"));
+}
+
+#[test]
+fn deleted_guests_come_back_with_their_checkins() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  toggle(&mut db, jane, CheckAction::In);
+  toggle(&mut db, jane, CheckAction::Out);
+  toggle(&mut db, jane, CheckAction::In);
+  let visits = checkins(&db.conn, jane);
+
+  guests::delete_guest(&mut db.conn, event_id, jane, Some("door")).unwrap();
+  assert!(guest_names(&db.conn, event_id).is_empty());
+  assert!(checkins(&db.conn, jane).is_empty());
+  let history = door::list_undo_history(&db.conn, event_id, Some(5)).unwrap();
+  assert_eq!((history[0].action.as_str(), history[0].display_name.as_str()), ("delete_guest", "Jane Doe"));
+
+  assert_eq!(undo(&mut db), UndoStatus::RevertedDelete);
+  assert_eq!(guest_id(&db.conn, event_id, "Jane Doe"), jane);
+  assert_eq!(checkins(&db.conn, jane), visits);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().currently_present, 1);
+
+  assert_eq!(redo(&mut db), RedoStatus::ReappliedDelete);
+  assert!(guest_names(&db.conn, event_id).is_empty());
+
+  // Earlier check-ins stay undoable once the delete itself is undone.
+  assert_eq!(undo(&mut db), UndoStatus::RevertedDelete);
+  assert_eq!(undo(&mut db), UndoStatus::RevertedCheckIn);
+  assert_eq!(checkins(&db.conn, jane).len(), 1);
+}
+
+#[test]
+fn undoing_a_delete_leaves_later_guests_alone() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Amy Lee, Jane Doe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  guests::delete_guest(&mut db.conn, event_id, jane, None).unwrap();
+
+  // Jane had the highest id; Pat must not be given it.
+  let pat = guests::add_guest(&mut db.conn, event_id, walk_in("Pat Roe", None), None, Overrides::default())
+    .unwrap()
+    .guest_id
+    .unwrap();
+  assert_ne!(pat, jane);
+
+  assert_eq!(undo(&mut db), UndoStatus::RevertedCheckIn);
+  assert_eq!(undo(&mut db), UndoStatus::RevertedDelete);
+  assert_eq!(guest_names(&db.conn, event_id), ["Amy Lee", "Jane Doe", "Pat Roe"]);
+  assert_eq!(guest_id(&db.conn, event_id, "Jane Doe"), jane);
+  assert_eq!(guest_id(&db.conn, event_id, "Pat Roe"), pat);
+
+  assert_eq!(redo(&mut db), RedoStatus::ReappliedDelete);
+  assert_eq!(guest_names(&db.conn, event_id), ["Amy Lee", "Pat Roe"]);
+}
+
+#[test]
+fn undo_refuses_to_overwrite_a_different_guest() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  guests::delete_guest(&mut db.conn, event_id, jane, None).unwrap();
+  // Only a hand-edited database could put someone else under Jane's id.
+  db.conn
+    .execute(
+      "INSERT INTO guests (id, event_id, display_name, created_at) VALUES (?1, ?2, 'Pat Roe', '2000-01-01 00:00:00')",
+      rusqlite::params![jane, event_id],
+    )
+    .unwrap();
+
+  let err = door::undo_last(&mut db.conn, event_id, None).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+  assert_eq!(guest_names(&db.conn, event_id), ["Pat Roe"]);
+}
+
+#[test]
+fn merges_move_checkins_and_close_duplicate_visits() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(
+    &mut db,
+    &[row("", "Jane Doe"), row("John Smith", "Jayne Doe")],
+    ImportMode::Append,
+  );
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  let jayne = guest_id(&db.conn, event_id, "Jayne Doe");
+  toggle(&mut db, jane, CheckAction::In);
+  toggle(&mut db, jayne, CheckAction::In);
+  toggle(&mut db, jayne, CheckAction::Out);
+  toggle(&mut db, jayne, CheckAction::In);
+  let (survivor_visits, duplicate_visits) = (checkins(&db.conn, jane), checkins(&db.conn, jayne));
+
+  let err = guests::merge_guests(&mut db.conn, event_id, jane, jane, None).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+
+  guests::merge_guests(&mut db.conn, event_id, jane, jayne, Some("door")).unwrap();
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe"]);
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("John Smith"));
+  // Jane's own open visit is kept; Jayne's closed one moves over and the
+  // second open visit is dropped.
+  let merged = checkins(&db.conn, jane);
+  assert_eq!(merged.len(), 2);
+  assert_eq!(merged[0], survivor_visits[0]);
+  assert_eq!(merged[1], duplicate_visits[0]);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().currently_present, 1);
+
+  assert_eq!(undo(&mut db), UndoStatus::RevertedMerge);
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe", "Jayne Doe"]);
+  assert_eq!(host(&db.conn, jane).unwrap_or_default(), "");
+  assert_eq!(checkins(&db.conn, jane), survivor_visits);
+  assert_eq!(checkins(&db.conn, jayne), duplicate_visits);
+
+  assert_eq!(redo(&mut db), RedoStatus::ReappliedMerge);
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe"]);
+  assert_eq!(checkins(&db.conn, jane), merged);
+}
@@ -0,0 +1,505 @@
+mod common;
+
+use common::{checkins, guest_id, guest_names, host, row};
+use party_core::{
+  banned::{self, BanInput},
+  batches,
+  checkins::{self as door, CheckAction, Overrides, ToggleStatus, UndoStatus},
+  guests::{self, WalkInInput},
+  import::{self, CsvRow, GuestOutcome, ImportMode},
+  name_list::AmbiguityKind,
+  profiles::{self, ColumnMapping, ProfileInput},
+  quotas::{self, MemberQuota},
+  search, stats, Error,
+};
+
+#[test]
+fn append_splits_names_and_skips_duplicates() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+
+  let summary = import::import_rows(
+    &mut db.conn,
+    event_id,
+    &[row("John Smith", "JANE DOE and Bob Roe"), row("Amy Lee", "Carl Poe & ")],
+    ImportMode::Append,
+    None,
+    Overrides::default(),
+  )
+  .unwrap();
+  assert_eq!(summary.total_rows, 2);
+  assert_eq!(summary.inserted, 3);
+
+  let again = import::import_rows(
+    &mut db.conn,
+    event_id,
+    &[row("john smith", "jane doe"), row("Amy Lee", "Dee Poe")],
+    ImportMode::Append,
+    None,
+    Overrides::default(),
+  )
+  .unwrap();
+  assert_eq!(again.inserted, 1);
+  assert_eq!(
+    guest_names(&db.conn, event_id),
+    ["Bob Roe", "Carl Poe", "Dee Poe", "Jane Doe"]
+  );
+}
+
+#[test]
+fn replace_drops_the_previous_list() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+  let summary = common::import(&mut db, &[row("Amy Lee", "Carl Poe")], ImportMode::Replace);
+
+  assert_eq!(guest_names(&db.conn, event_id), ["Carl Poe"]);
+  let replaced = summary.replaced.unwrap();
+  assert!(replaced.archive_id.is_some());
+  assert_eq!((replaced.guests, replaced.checkins, replaced.checked_in), (1, 0, 0));
+}
+
+#[test]
+fn replace_guards_checked_in_guests_and_rolls_back_to_the_archive() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe & Bob Roe")], ImportMode::Append);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  let bob = guest_id(&db.conn, event_id, "Bob Roe");
+  door::toggle_checkin(&mut db.conn, event_id, jane, CheckAction::In, None, false, Overrides::default()).unwrap();
+  door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::In, None, false, Overrides::default()).unwrap();
+  door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::Out, None, false, Overrides::default()).unwrap();
+
+  // The preview shows who would be cleared; the import itself refuses.
+  let rows = [row("Amy Lee", "Carl Poe")];
+  let preview = import::preview_import(&mut db.conn, event_id, &rows, ImportMode::Replace, Overrides::default()).unwrap();
+  let replaced = preview.summary.replaced.unwrap();
+  assert_eq!(replaced.archive_id, None);
+  assert_eq!((replaced.guests, replaced.checkins, replaced.checked_in), (2, 2, 1));
+  let refused = import::import_rows(&mut db.conn, event_id, &rows, ImportMode::Replace, None, Overrides::default());
+  assert!(matches!(refused, Err(Error::GuestsCheckedIn(1))));
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Jane Doe"]);
+
+  let confirmed = Overrides {
+    checked_in: true,
+    ..Overrides::default()
+  };
+  let summary = import::import_rows(&mut db.conn, event_id, &rows, ImportMode::Replace, Some("door"), confirmed).unwrap();
+  assert_eq!(summary.replaced.unwrap().checked_in, 1);
+  assert_eq!(guest_names(&db.conn, event_id), ["Carl Poe"]);
+
+  let rolled = batches::rollback_batch(&mut db.conn, event_id, summary.batch_id.unwrap(), None, false).unwrap();
+  assert_eq!((rolled.guests_removed, rolled.guests_restored), (1, 2));
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Jane Doe"]);
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  assert_eq!(checkins(&db.conn, jane).len(), 1);
+  assert_eq!(checkins(&db.conn, jane)[0].1, None);
+  let bob = guest_id(&db.conn, event_id, "Bob Roe");
+  assert!(checkins(&db.conn, bob)[0].1.is_some());
+}
+
+#[test]
+fn sync_reconciles_the_list_and_keeps_history() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe & Bob Roe"), row("Amy Lee", "Carl Poe")], ImportMode::Append);
+  let bob = guest_id(&db.conn, event_id, "Bob Roe");
+  door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::In, None, false, Overrides::default()).unwrap();
+  let walk_in = WalkInInput {
+    display_name: "Zed Walker".into(),
+    member_host: None,
+  };
+  guests::add_guest(&mut db.conn, event_id, walk_in, None, Overrides::default()).unwrap();
+
+  // Jane moves to Amy, Bob and Carl drop off, Dee is new.
+  let rows = [row("John Smith", ""), row("Amy Lee", "jane doe, Dee Roe")];
+  let preview = import::preview_import(&mut db.conn, event_id, &rows, ImportMode::Sync, Overrides::default()).unwrap();
+  let removed: Vec<_> = preview.removed.iter().map(|guest| guest.display_name.as_str()).collect();
+  assert_eq!(removed, ["Bob Roe", "Carl Poe"]);
+  assert_eq!(preview.rows[1].guests[0].outcome, GuestOutcome::Updated);
+  assert_eq!(guest_names(&db.conn, event_id).len(), 4);
+
+  let summary = common::import(&mut db, &rows, ImportMode::Sync);
+  let synced = summary.synced.unwrap();
+  assert_eq!((synced.added, synced.updated, synced.removed, synced.unchanged), (1, 1, 2, 0));
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("Amy Lee"));
+  // Only the guests it moved or removed are archived, without check-ins.
+  let archived: (i64, String) = db
+    .conn
+    .query_row(
+      "SELECT checkins, snapshot FROM import_archives WHERE batch_id = ?1",
+      [summary.batch_id.unwrap()],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .unwrap();
+  let snapshot: serde_json::Value = serde_json::from_str(&archived.1).unwrap();
+  let guests = snapshot["guests"].as_array().unwrap();
+  let mut ids: Vec<i64> = guests.iter().map(|guest| guest["id"].as_i64().unwrap()).collect();
+  ids.sort_unstable();
+  let mut touched = vec![jane, bob, guest_id(&db.conn, event_id, "Carl Poe")];
+  touched.sort_unstable();
+  assert_eq!((archived.0, ids), (0, touched));
+  assert!(snapshot.get("checkins").is_none());
+
+  // Bob keeps his visit and can still leave, but cannot come back in.
+  let hits = search::search_guests(&db.conn, event_id, "bob", None).unwrap();
+  assert!(hits[0].is_removed && hits[0].is_checked_in);
+  let out = door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::Out, None, false, Overrides::default()).unwrap();
+  assert_eq!(out.status, ToggleStatus::CheckedOut);
+  let back = door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::In, None, false, Overrides::default()).unwrap();
+  assert_eq!(back.status, ToggleStatus::Removed);
+  assert_eq!(checkins(&db.conn, bob).len(), 1);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().total_guests, 3);
+
+  // Back on the sheet, Carl is restored rather than added twice.
+  let again = common::import(&mut db, &[row("Amy Lee", "Jane Doe, Dee Roe, Carl Poe")], ImportMode::Sync);
+  let synced = again.synced.unwrap();
+  assert_eq!((synced.added, synced.updated, synced.removed, synced.unchanged), (1, 0, 0, 2));
+  assert_eq!(guest_names(&db.conn, event_id), ["Bob Roe", "Carl Poe", "Dee Roe", "Jane Doe", "Zed Walker"]);
+
+  // Rolling the first sync back drops Dee and puts Jane and Bob back, over
+  // the second sync, so it needs confirming.
+  let first = batches::list_batches(&db.conn, event_id).unwrap()[1].id;
+  let refused = batches::rollback_batch(&mut db.conn, event_id, first, None, false);
+  assert!(matches!(refused, Err(Error::RollbackLosesChanges { door_checkins: 0, later_imports: 1 })));
+  let rolled = batches::rollback_batch(&mut db.conn, event_id, first, None, true).unwrap();
+  assert_eq!((rolled.guests_removed, rolled.guests_restored), (1, 2));
+  assert_eq!(rolled.later_imports_overridden, 1);
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("John Smith"));
+  let back = door::toggle_checkin(&mut db.conn, event_id, bob, CheckAction::In, None, false, Overrides::default()).unwrap();
+  assert_eq!(back.status, ToggleStatus::CheckedIn);
+}
+
+#[test]
+fn guests_a_sync_removed_can_be_added_again() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe, Bob Roe, Carl Poe")], ImportMode::Append);
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Sync);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().total_guests, 1);
+
+  let walk_in = WalkInInput {
+    display_name: "Bob Roe".into(),
+    member_host: Some("John Smith".into()),
+  };
+  let added = guests::add_guest(&mut db.conn, event_id, walk_in, None, Overrides::default()).unwrap();
+  assert_eq!(added.status, ToggleStatus::CheckedIn);
+  let summary = common::import(&mut db, &[row("John Smith", "Carl Poe")], ImportMode::Append);
+  assert_eq!(summary.inserted, 1);
+  assert_eq!(stats::stats_summary(&db.conn, event_id).unwrap().total_guests, 3);
+}
+
+#[test]
+fn sync_moves_are_checked_like_new_guests() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let no_host = || CsvRow {
+    guest_names: Some("Kim Park".into()),
+    ..CsvRow::default()
+  };
+  let rows = [row("John Smith", "Jane Doe, Bob Roe"), row("Amy Lee", "Carl Poe"), no_host()];
+  common::import(&mut db, &rows, ImportMode::Append);
+  let quota = MemberQuota {
+    member_host: "Amy Lee".into(),
+    guest_quota: Some(1),
+    present_quota: None,
+  };
+  quotas::set_member_quota(&db.conn, event_id, quota, None).unwrap();
+  common::import(&mut db, &[row("John Smith", "Jane Doe"), row("Amy Lee", "Carl Poe"), no_host()], ImportMode::Sync);
+  let input = BanInput {
+    name: "Bob Roe".into(),
+    reason: None,
+  };
+  banned::add_banned(&mut db.conn, input, None).unwrap();
+
+  // Jane would take Amy past her quota, so she stays with John; Bob comes
+  // back on the list flagged; Kim keeps having no host.
+  let rows = [row("Amy Lee", "Carl Poe, Jane Doe"), row("John Smith", "Bob Roe"), no_host()];
+  let summary = common::import(&mut db, &rows, ImportMode::Sync);
+  assert_eq!(summary.over_quota.len(), 1);
+  assert_eq!(summary.over_quota[0].guest_name, "Jane Doe");
+  let jane = guest_id(&db.conn, event_id, "Jane Doe");
+  assert_eq!(host(&db.conn, jane).as_deref(), Some("John Smith"));
+  let banned: Vec<_> = summary.banned.iter().map(|ban| ban.guest_name.as_str()).collect();
+  assert_eq!(banned, ["Bob Roe"]);
+  let synced = summary.synced.unwrap();
+  assert_eq!((synced.added, synced.updated, synced.removed), (1, 0, 0));
+  let kim = guest_id(&db.conn, event_id, "Kim Park");
+  assert_eq!(host(&db.conn, kim), None);
+}
+
+#[test]
+fn history_times_land_on_the_event_night() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+
+  let rows = [
+    CsvRow {
+      check_in_time: Some("11:30 PM".into()),
+      check_out_time: Some("12:10 AM".into()),
+      ..row("John Smith", "Jane Doe")
+    },
+    CsvRow {
+      check_in: Some("Y".into()),
+      check_in_time: Some("9:15 PM".into()),
+      ..row("Amy Lee", "Carl Poe")
+    },
+  ];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  assert_eq!(
+    checkins(&db.conn, guest_id(&db.conn, event_id, "Jane Doe")),
+    [("2026-04-11T04:30:00Z".to_string(), Some("2026-04-11T05:10:00Z".to_string()))]
+  );
+  assert_eq!(
+    checkins(&db.conn, guest_id(&db.conn, event_id, "Carl Poe")),
+    [("2026-04-11T02:15:00Z".to_string(), None)]
+  );
+}
+
+#[test]
+fn read_csv_matches_headers_loosely() {
+  let data = "member name,Guest Names,Check In Y/N\nJohn Smith, Jane Doe ,Y\n,,\n";
+  let rows = import::read_csv(data.as_bytes()).unwrap();
+
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[0].member_name.as_deref(), Some("John Smith"));
+  assert_eq!(rows[0].guest_names.as_deref(), Some("Jane Doe"));
+  assert_eq!(rows[0].check_in.as_deref(), Some("Y"));
+  assert_eq!(rows[0].source_row, Some(2));
+  assert_eq!(rows[1].guest_names, None);
+}
+
+#[test]
+fn guest_quota_reports_overflow_rows() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let quota = MemberQuota {
+    member_host: "John Smith".into(),
+    guest_quota: Some(2),
+    present_quota: None,
+  };
+  quotas::set_member_quota(&db.conn, event_id, quota, Some("chair")).unwrap();
+  let rows = [
+    CsvRow {
+      source_row: Some(2),
+      ..row("John Smith", "Jane Doe, Bob Roe")
+    },
+    CsvRow {
+      source_row: Some(3),
+      ..row("JOHN SMITH", "Carl Poe")
+    },
+    row("Amy Lee", "Dee Poe, Eve Poe, Fay Poe"),
+  ];
+
+  let summary = common::import(&mut db, &rows, ImportMode::Append);
+  assert_eq!(summary.inserted, 5);
+  assert_eq!(summary.over_quota.len(), 1);
+  assert_eq!(summary.over_quota[0].guest_name, "Carl Poe");
+  assert_eq!(summary.over_quota[0].source_row, Some(3));
+  assert!(!guest_names(&db.conn, event_id).contains(&"Carl Poe".to_string()));
+
+  let admit = Overrides {
+    quota: true,
+    ..Overrides::default()
+  };
+  let summary =
+    import::import_rows(&mut db.conn, event_id, &rows, ImportMode::Append, Some("chair"), admit).unwrap();
+  assert_eq!(summary.inserted, 1);
+  assert!(guest_names(&db.conn, event_id).contains(&"Carl Poe".to_string()));
+}
+
+#[test]
+fn preview_reports_each_row_and_writes_nothing() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[CsvRow { source_row: Some(2), ..row("John Smith", "Jane Doe") }], ImportMode::Append);
+  let audit_rows = |db: &common::TestDb| -> i64 {
+    db.conn.query_row("SELECT COUNT(*) FROM audit_log", [], |row| row.get(0)).unwrap()
+  };
+  let audited = audit_rows(&db);
+
+  let rows = [
+    CsvRow {
+      source_row: Some(2),
+      check_in_time: Some("9:15 PM".into()),
+      check_out_time: Some("half past ten".into()),
+      ..row(" John   Smith ", "Jane Doe and Carl Poe & carl poe")
+    },
+    CsvRow {
+      source_row: Some(3),
+      ..row("Amy Lee", "(tbd)")
+    },
+  ];
+  let preview = import::preview_import(&mut db.conn, event_id, &rows, ImportMode::Append, Overrides::default()).unwrap();
+
+  assert_eq!((preview.summary.inserted, preview.summary.total_rows), (1, 2));
+  let first = &preview.rows[0];
+  assert_eq!(first.member_host.as_deref(), Some("John Smith"));
+  let outcomes: Vec<(&str, GuestOutcome)> = first.guests.iter().map(|g| (g.name.as_str(), g.outcome)).collect();
+  assert_eq!(
+    outcomes,
+    [
+      ("Jane Doe", GuestOutcome::Duplicate),
+      ("Carl Poe", GuestOutcome::Added),
+      ("carl poe", GuestOutcome::Duplicate),
+    ]
+  );
+  let existing = first.guests[0].duplicate_of.as_ref().unwrap();
+  assert_eq!((existing.display_name.as_str(), existing.source_row), ("Jane Doe", Some(2)));
+  // The second "carl poe" matches the guest the same row just added.
+  assert_eq!(first.guests[2].duplicate_of.as_ref().unwrap().display_name, "Carl Poe");
+
+  let history = first.history.as_ref().unwrap();
+  assert_eq!((history.in_ts.as_str(), history.out_ts.as_deref()), ("2026-04-11T02:15:00Z", None));
+  assert_eq!(first.unparsed_check_in_time, None);
+  assert_eq!(first.unparsed_check_out_time.as_deref(), Some("half past ten"));
+  assert!(first.ambiguities.is_empty());
+  assert!(preview.rows[1].guests.is_empty());
+  assert_eq!(preview.rows[1].ambiguities[0].kind, AmbiguityKind::Note);
+
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe"]);
+  assert_eq!(audit_rows(&db), audited);
+
+  // A replace is previewed against the emptied list.
+  let preview = import::preview_import(&mut db.conn, event_id, &rows, ImportMode::Replace, Overrides::default()).unwrap();
+  assert_eq!(preview.rows[0].guests[0].outcome, GuestOutcome::Added);
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe"]);
+}
+
+#[test]
+fn read_sheet_sniffs_delimiters_and_encodings() {
+  let names = |bytes: &[u8]| -> Vec<(Option<String>, Option<String>)> {
+    import::read_sheet(bytes, None)
+      .unwrap()
+      .into_iter()
+      .map(|row| (row.member_name, row.guest_names))
+      .collect()
+  };
+  let expected = [(Some("John Smith".to_string()), Some("José Díaz, Amy Lee".to_string()))];
+
+  assert_eq!(names("\u{feff}Member Name\tGuest Names\nJohn Smith\tJosé Díaz, Amy Lee\n".as_bytes()), expected);
+  assert_eq!(names("sep=;\r\nMember Name;Guest Names\r\nJohn Smith;\"José Díaz, Amy Lee\"\r\n".as_bytes()), expected);
+  assert_eq!(names("Member Name|Guest Names\nJohn Smith|José Díaz, Amy Lee\n".as_bytes()), expected);
+
+  let utf16: Vec<u8> = [0xFF, 0xFE]
+    .into_iter()
+    .chain("Member Name\tGuest Names\r\nJohn Smith\tJosé Díaz, Amy Lee\r\n".encode_utf16().flat_map(u16::to_le_bytes))
+    .collect();
+  assert_eq!(names(&utf16), expected);
+
+  let mut cp1252 = b"Member Name,Guest Names\r\nJohn Smith,\"Jos\xe9 D\xedaz, Amy Lee\"\r\n".to_vec();
+  assert_eq!(names(&cp1252), expected);
+  cp1252.extend_from_slice(b"Pat O\x92Neil,Kim Roe\r\n");
+  assert_eq!(names(&cp1252)[1].0.as_deref(), Some("Pat O\u{2019}Neil"));
+
+  let err = import::read_sheet(b"Name,Email\nJohn,j@x.org\n", None).unwrap_err();
+  assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+}
+
+#[test]
+fn profiles_map_other_sheet_layouts() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  let form = ProfileInput {
+    name: " Google   Form ".into(),
+    columns: ColumnMapping {
+      member_name: Some("Your name".into()),
+      guest_names: Some("Who are you bringing?".into()),
+      check_in_time: Some(" Arrived ".into()),
+      ..ColumnMapping::default()
+    },
+  };
+  let saved = profiles::save_profile(&mut db.conn, form, Some("door")).unwrap();
+  assert_eq!(saved.name, "Google Form");
+  assert_eq!(saved.columns.check_in_time.as_deref(), Some("Arrived"));
+
+  let path = db.dir.path().join("form.csv");
+  std::fs::write(
+    &path,
+    "Timestamp,Your name,Who are you bringing?,arrived\n4/1/2026 10:00:00,John Smith,Jane Doe & Carl Poe,9:15 PM\n",
+  )
+  .unwrap();
+  let path = path.to_str().unwrap();
+  let file = import::read_import_file(&db.conn, path, Some("google form")).unwrap();
+  common::import(&mut db, &file.rows, ImportMode::Append);
+  assert_eq!(guest_names(&db.conn, event_id), ["Carl Poe", "Jane Doe"]);
+  assert_eq!(checkins(&db.conn, guest_id(&db.conn, event_id, "Jane Doe")).len(), 1);
+
+  // Without the profile the file has no guest names column.
+  assert!(matches!(import::read_import_file(&db.conn, path, None), Err(Error::Invalid(_))));
+
+  // Saving under the same name replaces the mapping; a header the file lacks
+  // is reported rather than silently skipped.
+  let chapter = ProfileInput {
+    name: "GOOGLE FORM".into(),
+    columns: ColumnMapping {
+      guest_names: Some("Guests".into()),
+      ..ColumnMapping::default()
+    },
+  };
+  profiles::save_profile(&mut db.conn, chapter, None).unwrap();
+  assert_eq!(profiles::list_profiles(&db.conn).unwrap().len(), 1);
+  let err = import::read_import_file(&db.conn, path, Some("Google Form")).unwrap_err();
+  assert!(matches!(err, Error::Invalid(ref message) if message.contains("Guests")), "{:?}", err);
+
+  let missing = ProfileInput {
+    name: "Empty".into(),
+    columns: ColumnMapping::default(),
+  };
+  assert!(matches!(profiles::save_profile(&mut db.conn, missing, None), Err(Error::Invalid(_))));
+
+  profiles::delete_profile(&mut db.conn, "google form", None).unwrap();
+  assert!(profiles::list_profiles(&db.conn).unwrap().is_empty());
+  assert!(matches!(
+    import::read_import_file(&db.conn, path, Some("Google Form")),
+    Err(Error::ProfileNotFound(_))
+  ));
+}
+
+#[test]
+fn batches_record_provenance_and_roll_back() {
+  let mut db = common::open();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("John Smith", "Jane Doe")], ImportMode::Append);
+
+  let path = db.dir.path().join("week two.csv");
+  std::fs::write(&path, "Member Name,Guest Names,Check In Y/N\nAmy Lee,Carl Poe & Dee Roe,Y\nJohn Smith,Jane Doe,\n").unwrap();
+  let file = import::read_import_file(&db.conn, path.to_str().unwrap(), None).unwrap();
+  let summary =
+    import::import_file(&mut db.conn, event_id, &file, ImportMode::Append, Some("door"), Overrides::default()).unwrap();
+  let second = summary.batch_id.unwrap();
+  assert_eq!(guest_names(&db.conn, event_id), ["Carl Poe", "Dee Roe", "Jane Doe"]);
+
+  let listed = batches::list_batches(&db.conn, event_id).unwrap();
+  assert_eq!(listed.len(), 2);
+  let batch = &listed[0];
+  assert_eq!(batch.id, second);
+  assert_eq!(batch.file_name.as_deref(), Some("week two.csv"));
+  assert_eq!(batch.file_hash.as_ref().map(String::len), Some(64));
+  assert_eq!((batch.mode.as_str(), batch.operator.as_deref()), ("append", Some("door")));
+  assert_eq!((batch.total_rows, batch.inserted, batch.guests), (2, 2, 2));
+  assert_eq!(listed[1].file_name, None);
+
+  // A door check-out on one of the batch's guests goes with it, once
+  // confirmed.
+  let carl = guest_id(&db.conn, event_id, "Carl Poe");
+  door::toggle_checkin(&mut db.conn, event_id, carl, CheckAction::Out, None, false, Overrides::default()).unwrap();
+  assert_eq!(batches::list_batches(&db.conn, event_id).unwrap()[0].door_checkins, 1);
+  let refused = batches::rollback_batch(&mut db.conn, event_id, second, Some("door"), false);
+  assert!(matches!(refused, Err(Error::RollbackLosesChanges { door_checkins: 1, later_imports: 0 })));
+  assert_eq!(checkins(&db.conn, carl).len(), 1);
+
+  let rolled = batches::rollback_batch(&mut db.conn, event_id, second, Some("door"), true).unwrap();
+  assert_eq!((rolled.guests_removed, rolled.checkins_removed, rolled.door_checkins_removed), (2, 2, 1));
+  assert_eq!(guest_names(&db.conn, event_id), ["Jane Doe"]);
+  assert_eq!(door::undo_last(&mut db.conn, event_id, None).unwrap().status, UndoStatus::Empty);
+
+  let listed = batches::list_batches(&db.conn, event_id).unwrap();
+  assert!(listed[0].rolled_back_at.is_some());
+  assert_eq!(listed[0].guests, 0);
+  assert!(matches!(batches::rollback_batch(&mut db.conn, event_id, second, None, true), Err(Error::Invalid(_))));
+  assert!(matches!(batches::rollback_batch(&mut db.conn, event_id, 999, None, true), Err(Error::BatchNotFound(999))));
+}
@@ -0,0 +1,73 @@
+use party_core::db;
+use rusqlite::Connection;
+
+/// A database as the first release left it: no events, and check-in times
+/// stored as bare wall-clock times.
+fn legacy_db(path: &str) {
+  let conn = Connection::open(path).unwrap();
+  conn
+    .execute_batch(include_str!("../migrations/0001_initial.sql"))
+    .unwrap();
+  conn
+    .execute_batch(
+      "INSERT INTO guests (id, display_name, member_host, created_at)
+         VALUES (1, 'Jane Doe', 'John Smith', '2026-04-11 01:30:00'),
+                (2, 'Carl Poe', 'Amy Lee', '2026-04-11 02:00:00');
+       INSERT INTO checkins (guest_id, in_ts, out_ts, in_by, out_by)
+         VALUES (1, '11:50:00 PM', '12:20:00 AM', 'door', 'door'),
+                (2, '10:05:00 PM', NULL, 'door', NULL);",
+    )
+    .unwrap();
+}
+
+#[test]
+fn legacy_time_only_checkins_become_instants_on_the_event_night() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("legacy.db");
+  let path = path.to_str().unwrap();
+  legacy_db(path);
+
+  let conn = db::open(path).expect("legacy database opens");
+  // The first guest was added at 8:30 PM Chicago time, already the 11th in UTC.
+  let (event_id, event_date): (i64, String) = conn
+    .query_row("SELECT id, event_date FROM events WHERE name = 'Imported event'", [], |row| {
+      Ok((row.get(0)?, row.get(1)?))
+    })
+    .unwrap();
+  assert_eq!(event_date, "2026-04-10");
+  let mut stmt = conn
+    .prepare("SELECT event_id, in_ts, out_ts FROM checkins ORDER BY guest_id")
+    .unwrap();
+  let checkins: Vec<(i64, String, Option<String>)> = stmt
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+    .unwrap()
+    .collect::<rusqlite::Result<_>>()
+    .unwrap();
+
+  // Chicago is on CDT (UTC-5) that night; the check-out is after midnight.
+  assert_eq!(
+    checkins,
+    [
+      (event_id, "2026-04-11T04:50:00Z".to_string(), Some("2026-04-11T05:20:00Z".to_string())),
+      (event_id, "2026-04-11T03:05:00Z".to_string(), None),
+    ]
+  );
+}
+
+#[test]
+fn concurrent_opens_migrate_once() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("shared.db");
+  let path = path.to_str().unwrap().to_string();
+  legacy_db(&path);
+
+  let opens: Vec<_> = (0..4)
+    .map(|_| {
+      let path = path.clone();
+      std::thread::spawn(move || db::open(&path).map(|_| ()))
+    })
+    .collect();
+  for open in opens {
+    open.join().unwrap().expect("database opens");
+  }
+}
@@ -0,0 +1,81 @@
+use party_core::name_list::{parse_name_list, AmbiguityKind};
+
+/// Guest columns as members type them, and the names each should become.
+const CORPUS: &[(&str, &[&str])] = &[
+  // Separators.
+  ("Jane Doe", &["Jane Doe"]),
+  ("Jane Doe, Bob Roe", &["Jane Doe", "Bob Roe"]),
+  ("Amy; Ben", &["Amy", "Ben"]),
+  ("Amy Lee\nBen Poe\r\nCal Roe", &["Amy Lee", "Ben Poe", "Cal Roe"]),
+  ("Jane Doe & Bob Roe", &["Jane Doe", "Bob Roe"]),
+  ("Jane Doe AND Bob Roe", &["Jane Doe", "Bob Roe"]),
+  ("Amy + Ben", &["Amy", "Ben"]),
+  ("Amy Lee,, ; ", &["Amy Lee"]),
+  ("", &[]),
+  // "and" inside a name is not a separator.
+  ("Brandon Anderson, Sandy Rand", &["Brandon Anderson", "Sandy Rand"]),
+  // Shared surnames.
+  ("John and Jane Smith", &["John Smith", "Jane Smith"]),
+  ("John & Jane Smith", &["John Smith", "Jane Smith"]),
+  ("Ann, Bob and Cy Poe", &["Ann", "Bob Poe", "Cy Poe"]),
+  ("John and Jane Smith Jr.", &["John Smith", "Jane Smith Jr."]),
+  ("John Smith and Jane", &["John Smith", "Jane"]),
+  ("Amy and Ben", &["Amy", "Ben"]),
+  ("JOHN AND JANE SMITH", &["John Smith", "Jane Smith"]),
+  // Titles.
+  ("Dr. and Mrs. Lee", &["Dr. Lee", "Mrs. Lee"]),
+  ("Mr and Mrs Patel", &["Mr Patel", "Mrs Patel"]),
+  ("Mr. and Mrs. John Smith", &["Mr. Smith", "Mrs. John Smith"]),
+  // Extra guests.
+  ("Sarah +1", &["Sarah", "Sarah's guest"]),
+  ("Sarah Jones + 2", &["Sarah Jones", "Sarah Jones's guest", "Sarah Jones's guest 2"]),
+  ("Mike (+1)", &["Mike", "Mike's guest"]),
+  ("+2", &["Guest", "Guest 2"]),
+  ("Amy +10", &["Amy", "Amy's guest", "Amy's guest 2", "Amy's guest 3", "Amy's guest 4", "Amy's guest 5",
+    "Amy's guest 6", "Amy's guest 7", "Amy's guest 8", "Amy's guest 9", "Amy's guest 10"]),
+  // Too many to be guests: a phone number or the like, left out as a note.
+  ("Mike +100000", &["Mike"]),
+  ("Mike (+11)", &["Mike"]),
+  ("Jane Doe +15551234567", &["Jane Doe"]),
+  ("Jane Doe +99999999999999999999999", &["Jane Doe"]),
+  // Notes.
+  ("Mike (roommate)", &["Mike"]),
+  ("Mike [late, after 11] and Tom Roe", &["Mike Roe", "Tom Roe"]),
+  ("(tbd)", &[]),
+  ("Mike (roommate, Amy Lee", &["Mike (roommate", "Amy Lee"]),
+];
+
+#[test]
+fn corpus_splits_as_expected() {
+  for (input, expected) in CORPUS {
+    assert_eq!(parse_name_list(input).names, *expected, "splitting {:?}", input);
+  }
+}
+
+#[test]
+fn guesses_are_reported() {
+  use AmbiguityKind::*;
+  let cases: &[(&str, &[AmbiguityKind])] = &[
+    ("Jane Doe, Bob Roe", &[]),
+    ("Dr. and Mrs. Lee", &[]),
+    ("John and Jane Smith", &[SharedSurname]),
+    ("Mr. and Mrs. John Smith", &[SharedSurname]),
+    ("Sarah +1", &[PlusGuests]),
+    ("Mike (roommate)", &[Note]),
+    ("Mike (+1)", &[PlusGuests]),
+    ("Mike +100000", &[Note]),
+    ("Mike (+11)", &[Note]),
+    ("Sarah (work) +1; Amy and Ben Poe", &[PlusGuests, Note, SharedSurname]),
+  ];
+  for (input, expected) in cases {
+    let kinds: Vec<_> = parse_name_list(input).ambiguities.iter().map(|found| found.kind).collect();
+    assert_eq!(kinds, *expected, "guesses for {:?}", input);
+  }
+
+  let list = parse_name_list("Ann and Bob Poe, Mike (roommate)");
+  let shared = &list.ambiguities[0];
+  assert_eq!(shared.text, "Ann and Bob Poe");
+  assert_eq!(shared.names, ["Ann Poe", "Bob Poe"]);
+  let note = &list.ambiguities[1];
+  assert_eq!((note.text.as_str(), note.names.as_slice()), ("Mike (roommate)", &["Mike".to_string()][..]));
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use common::row;
+use party_core::{import::ImportMode, names, search};
+
+fn seeded() -> common::TestDb {
+  let mut db = common::open();
+  let rows = [
+    row("Amy Lee", "John Smith, Jane Smythe"),
+    row("Dan Roe", "Joan Smit, Carl Poe"),
+  ];
+  common::import(&mut db, &rows, ImportMode::Append);
+  db
+}
+
+#[test]
+fn direct_matches_score_one() {
+  let db = seeded();
+
+  let results = search::search_guests(&db.conn, db.event.id, "jo smi", None).unwrap();
+  let names: Vec<_> = results.iter().map(|r| r.display_name.as_str()).collect();
+  assert_eq!(names.len(), 2);
+  assert!(names.contains(&"John Smith") && names.contains(&"Joan Smit"));
+  assert!(results.iter().all(|r| r.score == Some(1.0)));
+
+  let default = search::search_guests(&db.conn, db.event.id, "", None).unwrap();
+  assert!(default.iter().all(|r| r.score.is_none()));
+}
+
+#[test]
+fn misspellings_are_ranked_by_similarity() {
+  let db = seeded();
+
+  let results = search::search_guests(&db.conn, db.event.id, "Jonh Smtih", None).unwrap();
+  assert_eq!(results[0].display_name, "John Smith");
+  let scores: Vec<f64> = results.iter().map(|r| r.score.unwrap()).collect();
+  assert!(scores[0] < 1.0 && scores[0] >= 0.6, "{:?}", scores);
+  assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", scores);
+  assert!(results.iter().all(|r| r.display_name != "Carl Poe"));
+}
+
+#[test]
+fn transpositions_count_as_one_edit() {
+  assert_eq!(names::edit_distance("jonh", "john"), 1);
+  assert_eq!(names::edit_distance("smtih", "smith"), 1);
+  assert_eq!(names::edit_distance("kitten", "sitting"), 3);
+  assert_eq!(names::edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn sound_keys_group_common_spellings() {
+  use party_core::phonetic::sound_key;
+
+  let same = [
+    ("Katelyn", "Caitlin"),
+    ("Shawn", "Sean"),
+    ("Stephen", "Steven"),
+    ("Christine", "Kristen"),
+    ("Phillip", "Filip"),
+    ("Smyth", "Smith"),
+    ("Geoff", "Jeff"),
+  ];
+  for (a, b) in same {
+    assert_eq!(sound_key(a), sound_key(b), "{} / {}", a, b);
+  }
+  assert_ne!(sound_key("Katelyn"), sound_key("Kristen"));
+  assert_eq!(sound_key("Caitlin  O'Neil-Smith"), "KTLN ANL SM0");
+}
+
+#[test]
+fn sound_alikes_rank_below_typed_matches() {
+  let mut db = common::open();
+  let rows = [
+    row("Shawn Miller", "Katelyn Shaw, Caitlin Ross"),
+    row("Sean Miller", "Katie Shaw"),
+  ];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  let results = search::search_guests(&db.conn, db.event.id, "caitlin", None).unwrap();
+  let names: Vec<_> = results.iter().map(|r| r.display_name.as_str()).collect();
+  assert_eq!(names, ["Caitlin Ross", "Katelyn Shaw"]);
+  assert!(results[1].score.unwrap() < 1.0);
+
+  let members = search::search_members(&db.conn, db.event.id, "sean", None).unwrap();
+  let hosts: Vec<_> = members.iter().map(|m| m.member_host.as_str()).collect();
+  assert_eq!(hosts, ["Sean Miller", "Shawn Miller"]);
+}
+
+#[test]
+fn accents_apostrophes_and_hyphens_match_either_way() {
+  let mut db = common::open();
+  let rows = [
+    row("Zoë O’Brien", "José Núñez, Patrick O'Neil, Ana Smith-Jones"),
+    row("Oleg", "Иван Петров"),
+  ];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  let find = |q: &str| -> Vec<String> {
+    search::search_guests(&db.conn, db.event.id, q, None)
+      .unwrap()
+      .into_iter()
+      .filter(|r| r.score == Some(1.0))
+      .map(|r| r.display_name)
+      .collect()
+  };
+  for q in ["jose", "José", "JOSE NUNEZ", "núñ"] {
+    assert_eq!(find(q), ["José Núñez"], "{}", q);
+  }
+  for q in ["oneil", "O'Neil", "O’Neil", "o'ne"] {
+    assert_eq!(find(q), ["Patrick O'Neil"], "{}", q);
+  }
+  for q in ["smith", "jones", "Smith-Jones", "smithjones", "ana jon"] {
+    assert_eq!(find(q), ["Ana Smith-Jones"], "{}", q);
+  }
+  assert_eq!(find("иван"), ["Иван Петров"]);
+  assert_eq!(find("ПЕТР"), ["Иван Петров"]);
+
+  let members = search::search_members(&db.conn, db.event.id, "zoe obrien", None).unwrap();
+  assert_eq!(members[0].member_host, "Zoë O’Brien");
+}
+
+#[test]
+fn folding_is_shared_by_index_and_query() {
+  assert_eq!(names::fold("Zoë O’Brien-Núñez"), "zoe obrien-nunez");
+  assert_eq!(names::clean_token("D'Angelo"), "dangelo");
+  assert_eq!(names::index_text("Ana Smith-Jones"), "ana smith jones smithjones");
+  assert_eq!(names::name_key("Renée  O'Hara"), names::name_key("renee ohara"));
+}
+
+#[test]
+fn qualifiers_are_split_from_search_text() {
+  use search::{parse_query, StatusFilter};
+
+  let parsed = parse_query("host:smith in: jo");
+  assert_eq!(parsed.text, "jo");
+  assert_eq!(parsed.host, "smith");
+  assert_eq!(parsed.status, Some(StatusFilter::In));
+
+  let parsed = parse_query("HOST: \"van der berg\" never:amy 10:30");
+  assert_eq!(parsed.host, "van der berg");
+  assert_eq!(parsed.text, "amy 10:30");
+  assert_eq!(parsed.status, Some(StatusFilter::Never));
+
+  assert_eq!(parse_query("out: in:").status, Some(StatusFilter::In));
+  assert_eq!(parse_query("jo smi").text, "jo smi");
+}
+
+#[test]
+fn unified_search_ranks_guests_and_members_together() {
+  use party_core::checkins::{self, CheckAction, Overrides};
+  use search::SearchHit;
+
+  let mut db = seeded();
+  let event_id = db.event.id;
+  let toggle = |db: &mut common::TestDb, name: &str, action| {
+    let guest = common::guest_id(&db.conn, event_id, name);
+    checkins::toggle_checkin(&mut db.conn, event_id, guest, action, None, false, Overrides::default()).unwrap();
+  };
+  toggle(&mut db, "John Smith", CheckAction::In);
+  toggle(&mut db, "Joan Smit", CheckAction::In);
+  toggle(&mut db, "Joan Smit", CheckAction::Out);
+
+  let labels = |q: &str| -> Vec<String> {
+    search::search(&db.conn, event_id, q, None)
+      .unwrap()
+      .into_iter()
+      .map(|hit| match hit {
+        SearchHit::Guest(guest) => guest.display_name,
+        SearchHit::Member(member) => format!("[{}]", member.member_host),
+      })
+      .collect()
+  };
+
+  assert_eq!(labels("lee"), ["[Amy Lee]"]);
+  assert_eq!(labels("host:amy"), ["[Amy Lee]", "Jane Smythe", "John Smith"]);
+  assert_eq!(labels("host:dan jo"), ["[Dan Roe]", "Joan Smit"]);
+  assert_eq!(labels("in:"), ["John Smith"]);
+  assert_eq!(labels("out:"), ["Joan Smit"]);
+  assert_eq!(labels("never: host:amy"), ["[Amy Lee]", "Jane Smythe"]);
+  assert_eq!(labels("out: host:amy"), Vec::<String>::new());
+  assert_eq!(labels("host:x' OR 1=1 --"), Vec::<String>::new());
+
+  // Typed prefix matches come before the sound-alike.
+  let jo = labels("jo");
+  assert_eq!(jo.len(), 3);
+  assert_eq!(jo[2], "Jane Smythe");
+  // The exact member outranks a guest who is only a near miss.
+  assert_eq!(labels("dan"), ["[Dan Roe]", "Jane Smythe"]);
+}
+
+#[test]
+fn members_go_in_by_how_well_they_match() {
+  use search::SearchHit;
+
+  let mut db = common::open();
+  let rows = [row("Carl Smyth", "Al Poe"), row("Dan Smythe", "Bo Roe"), row("", "Ann Smith, Cy Smit, Em Smeeth")];
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  // Both members only sound like the search, so Carl comes first from the
+  // index, but Dan is the closer spelling.
+  let labels: Vec<String> = search::search(&db.conn, db.event.id, "smithe", None)
+    .unwrap()
+    .into_iter()
+    .map(|hit| match hit {
+      SearchHit::Guest(guest) => guest.display_name,
+      SearchHit::Member(member) => format!("[{}]", member.member_host),
+    })
+    .collect();
+  assert_eq!(labels, ["[Dan Smythe]", "Ann Smith", "[Carl Smyth]", "Cy Smit", "Em Smeeth"]);
+}
+
+#[test]
+fn member_index_follows_guest_changes() {
+  let mut db = seeded();
+  let event_id = db.event.id;
+  common::import(&mut db, &[row("amy LEE", "Kim Park")], ImportMode::Append);
+  let hosts = |db: &common::TestDb, q: &str| -> Vec<(String, i64)> {
+    search::search_members(&db.conn, event_id, q, None)
+      .unwrap()
+      .into_iter()
+      .map(|m| (m.member_host, m.total_guests))
+      .collect()
+  };
+
+  // Spellings that differ only in case are one member.
+  assert_eq!(hosts(&db, "lee"), [("Amy Lee".to_string(), 3)]);
+
+  db.conn
+    .execute("UPDATE guests SET member_host = 'Dana Ross' WHERE display_name = 'Kim Park'", [])
+    .unwrap();
+  assert_eq!(hosts(&db, "dan"), [("Dan Roe".to_string(), 2), ("Dana Ross".to_string(), 1)]);
+
+  db.conn
+    .execute("DELETE FROM guests WHERE member_host = 'Dan Roe'", [])
+    .unwrap();
+  assert_eq!(hosts(&db, "dan"), [("Dana Ross".to_string(), 1)]);
+
+  common::import(&mut db, &[row("Zed", "Al")], ImportMode::Replace);
+  assert_eq!(hosts(&db, ""), [("Zed".to_string(), 1)]);
+}
+
+#[test]
+fn member_search_ranks_prefix_matches_and_binds_input() {
+  let mut db = common::open();
+  let rows: Vec<_> = (0..300)
+    .map(|i| row(&format!("Member {:04}", i), &format!("Guest {i}a, Guest {i}b")))
+    .chain([row("Sean Carter", "Ann"), row("Shawn Miles", "Bo")])
+    .collect();
+  common::import(&mut db, &rows, ImportMode::Append);
+
+  let hosts = |q: &str| -> Vec<String> {
+    search::search_members(&db.conn, db.event.id, q, Some(5))
+      .unwrap()
+      .into_iter()
+      .map(|m| m.member_host)
+      .collect()
+  };
+  assert_eq!(hosts("member 0042")[0], "Member 0042");
+  // "Shawn" starts with the typed word; "Sean" only sounds like it.
+  assert_eq!(hosts("shaw"), ["Shawn Miles", "Sean Carter"]);
+  assert!(hosts("x' OR 1=1 --").is_empty());
+  assert_eq!(hosts("\"member\"").len(), 5);
+}
@@ -5,10 +5,8 @@ import {
   useMemo,
   useState,
 } from "react";
-import Papa from "papaparse";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
-import { readTextFile } from "@tauri-apps/plugin-fs";
 import { appDataDir, join } from "@tauri-apps/api/path";
 import clsx from "clsx";
 
@@ -18,6 +16,10 @@ type RawGuest = {
   member_host: string | null;
   is_checked_in: boolean;
   has_history: boolean;
+  is_banned: boolean;
+  banned_reason: string | null;
+  is_removed: boolean;
+  score: number | null;
 };
 
 type Guest = {
@@ -26,25 +28,250 @@ type Guest = {
   memberHost: string | null;
   isCheckedIn: boolean;
   hasHistory: boolean;
+  isBanned: boolean;
+  bannedReason: string | null;
+  isRemoved: boolean;
+  score: number | null;
+};
+
+type PartyEvent = {
+  id: number;
+  name: string;
+  eventDate: string | null;
+  venue: string | null;
+  doorsOpen: string | null;
+  doorsClose: string | null;
+  timezone: string;
+  capacity: number | null;
+  capacityWarningPercent: number;
+  guestQuota: number | null;
+  presentQuota: number | null;
+};
+
+type EventDraft = {
+  id: number | null;
+  name: string;
+  eventDate: string;
+  venue: string;
+  doorsOpen: string;
+  doorsClose: string;
+  timezone: string;
+  capacity: string;
+  capacityWarningPercent: string;
+  guestQuota: string;
+  presentQuota: string;
 };
 
 type MemberSearchResult = {
   memberHost: string;
   totalGuests: number;
   presentGuests: number;
+  score: number | null;
 };
 
+type RawSearchHit = ({ kind: "guest" } & RawGuest) | ({ kind: "member" } & MemberSearchResult);
+
+type SearchHit = { kind: "guest"; guest: Guest } | { kind: "member"; member: MemberSearchResult };
+
+type QuotaOverflow = {
+  source_row: number | null;
+  member_name: string;
+  guest_name: string;
+  quota: number;
+};
+
+type BannedMatch = {
+  source_row: number | null;
+  guest_name: string;
+  banned_name: string;
+  reason: string | null;
+};
+
+type ImportMode = "replace" | "append" | "sync";
+
 type ImportSummary = {
+  batch_id: number | null;
   inserted: number;
   total_rows: number;
+  over_quota: QuotaOverflow[];
+  banned: BannedMatch[];
+  replaced: ReplacedList | null;
+  synced: SyncCounts | null;
+};
+
+type SyncCounts = {
+  added: number;
+  updated: number;
+  removed: number;
+  unchanged: number;
+};
+
+type ReplacedList = {
+  archive_id: number | null;
+  guests: number;
+  checkins: number;
+  checked_in: number;
+};
+
+type ColumnMapping = {
+  memberName: string | null;
+  guestNames: string | null;
+  checkIn: string | null;
+  checkInTime: string | null;
+  checkOut: string | null;
+  checkOutTime: string | null;
+  walkIn: string | null;
+};
+
+type ImportProfile = {
+  id: number;
+  name: string;
+  columns: ColumnMapping;
+  createdAt: string;
+  updatedAt: string;
+};
+
+type ProfileDraft = {
+  name: string;
+  headers: string[];
+  columns: ColumnMapping;
+};
+
+type ExistingGuest = { guest_id: number; display_name: string; member_host: string | null; source_row: number | null };
+
+type ImportPreview = {
+  summary: ImportSummary;
+  rows: RowPreview[];
+  removed: ExistingGuest[];
+};
+
+type RowPreview = {
+  source_row: number | null;
+  member_host: string | null;
+  guests: {
+    name: string;
+    outcome: "added" | "duplicate" | "over_quota" | "unchanged" | "updated" | "restored";
+    duplicate_of: ExistingGuest | null;
+    banned_name: string | null;
+  }[];
+  history: { in_ts: string; out_ts: string | null } | null;
+  unparsed_check_in_time: string | null;
+  unparsed_check_out_time: string | null;
+  ambiguities: { kind: "shared_surname" | "plus_guests" | "note"; text: string; names: string[] }[];
+};
+
+type PendingImport = {
+  mode: ImportMode;
+  profile: string | null;
+  preview: ImportPreview;
+};
+
+type ImportBatch = {
+  id: number;
+  eventId: number;
+  fileName: string | null;
+  fileHash: string | null;
+  mode: string;
+  operator: string | null;
+  createdAt: string;
+  totalRows: number;
+  inserted: number;
+  guests: number;
+  doorCheckins: number;
+  laterImports: number;
+  rolledBackAt: string | null;
+  rolledBackBy: string | null;
+};
+
+type RollbackSummary = {
+  guestsRemoved: number;
+  checkinsRemoved: number;
+  doorCheckinsRemoved: number;
+  laterImportsOverridden: number;
+  guestsRestored: number;
+};
+
+type BannedGuest = {
+  id: number;
+  name: string;
+  reason: string | null;
+  addedBy: string | null;
+  createdAt: string;
+};
+
+type MemberQuota = {
+  memberHost: string;
+  guestQuota: number | null;
+  presentQuota: number | null;
 };
 
 type ToggleResult = {
-  status: "checked_in" | "checked_out" | "already_in" | "not_checked_in" | "never_checked_in";
+  status:
+    | "checked_in"
+    | "checked_out"
+    | "already_in"
+    | "not_checked_in"
+    | "never_checked_in"
+    | "at_capacity"
+    | "over_guest_quota"
+    | "member_quota_full"
+    | "banned"
+    | "removed";
+};
+
+type WalkInResult = {
+  status: ToggleResult["status"];
+  guestId: number | null;
+};
+
+type Overrides = {
+  capacity?: boolean;
+  quota?: boolean;
+  banned?: boolean;
+  checkedIn?: boolean;
 };
 
 type UndoResult = {
-  status: "reverted_check_in" | "reverted_check_out" | "empty";
+  status:
+    | "reverted_check_in"
+    | "reverted_check_out"
+    | "reverted_edit"
+    | "reverted_delete"
+    | "reverted_merge"
+    | "empty";
+};
+
+type RedoResult = {
+  status:
+    | "reapplied_check_in"
+    | "reapplied_check_out"
+    | "reapplied_edit"
+    | "reapplied_delete"
+    | "reapplied_merge"
+    | "empty";
+};
+
+type AuditEntry = {
+  id: number;
+  eventId: number | null;
+  guestId: number | null;
+  guestName: string | null;
+  action: string;
+  operator: string | null;
+  createdAt: string;
+  beforeState: Record<string, unknown> | null;
+  afterState: Record<string, unknown> | null;
+};
+
+type UndoHistoryEntry = {
+  id: number;
+  action: "check_in" | "check_out" | "forced_check_out" | "edit_guest" | "delete_guest" | "merge_guests";
+  guestId: number;
+  displayName: string;
+  operator: string | null;
+  createdAt: string;
+  undoneAt: string | null;
+  undoneBy: string | null;
 };
 
 type ToastTone = "success" | "info" | "error";
@@ -54,6 +281,10 @@ type StatsSummary = {
   totalCheckIns: number;
   totalCheckOuts: number;
   currentlyPresent: number;
+  walkIns: number;
+  capacity: number | null;
+  capacityWarningAt: number | null;
+  nearCapacity: boolean;
   presentGuests: PresentGuest[];
   topHosts: HostSummary[];
 };
@@ -70,6 +301,7 @@ type PresentGuest = {
   memberHost: string | null;
   inTs: string | null;
   operator: string | null;
+  isWalkIn: boolean;
 };
 
 type HostSummary = {
@@ -80,31 +312,142 @@ type HostSummary = {
 
 const DEFAULT_LIMIT = 25;
 
+const HISTORY_LABELS: Record<UndoHistoryEntry["action"], string> = {
+  check_in: "Checked in",
+  check_out: "Checked out",
+  forced_check_out: "Force checked out",
+  edit_guest: "Edited",
+  delete_guest: "Deleted",
+  merge_guests: "Merged duplicate into",
+};
+
+const isRedoShortcut = (event: KeyboardEvent<HTMLInputElement>) =>
+  (event.metaKey || event.ctrlKey) &&
+  ((event.shiftKey && event.key.toLowerCase() === "z") || event.key.toLowerCase() === "y");
+
+const MAPPED_FIELDS: { key: keyof ColumnMapping; label: string }[] = [
+  { key: "guestNames", label: "Guest names" },
+  { key: "memberName", label: "Member name" },
+  { key: "checkIn", label: "Checked in Y/N" },
+  { key: "checkInTime", label: "Check-in time" },
+  { key: "checkOut", label: "Checked out Y/N" },
+  { key: "checkOutTime", label: "Check-out time" },
+  { key: "walkIn", label: "Walk-in Y/N" },
+];
+
+const isModShortcut = (event: KeyboardEvent<HTMLInputElement>, key: string) =>
+  (event.metaKey || event.ctrlKey) && !event.shiftKey && event.key.toLowerCase() === key;
+
+const todayIso = () => {
+  const now = new Date();
+  const pad = (value: number) => String(value).padStart(2, "0");
+  return `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}`;
+};
+
+const localTimezone = () => Intl.DateTimeFormat().resolvedOptions().timeZone;
+
+const formatClock = (value: string, timeZone?: string) => {
+  const date = new Date(value);
+  if (Number.isNaN(date.getTime())) return value;
+  return date.toLocaleTimeString([], { hour: "numeric", minute: "2-digit", timeZone });
+};
+
+const emptyEventDraft = (): EventDraft => ({
+  id: null,
+  name: "",
+  eventDate: todayIso(),
+  venue: "",
+  doorsOpen: "",
+  doorsClose: "",
+  timezone: localTimezone(),
+  capacity: "",
+  capacityWarningPercent: "90",
+  guestQuota: "",
+  presentQuota: "",
+});
+
+const eventDraftFrom = (event: PartyEvent): EventDraft => ({
+  id: event.id,
+  name: event.name,
+  eventDate: event.eventDate ?? "",
+  venue: event.venue ?? "",
+  doorsOpen: event.doorsOpen ?? "",
+  doorsClose: event.doorsClose ?? "",
+  timezone: event.timezone,
+  capacity: event.capacity?.toString() ?? "",
+  capacityWarningPercent: event.capacityWarningPercent.toString(),
+  guestQuota: event.guestQuota?.toString() ?? "",
+  presentQuota: event.presentQuota?.toString() ?? "",
+});
+
+const optionalNumber = (value: string) => (value.trim() ? Number(value) : null);
+
+// A search for one brother's guests; quoted so multi-word names stay together.
+const hostQuery = (memberHost: string) => `host:"${memberHost.replace(/"/g, "")}" `;
+
+// Why the import preview flags a guest column for a second look.
+const AMBIGUITY_LABELS: Record<RowPreview["ambiguities"][number]["kind"], string> = {
+  shared_surname: "surname shared between names",
+  plus_guests: "unnamed extra guests",
+  note: "note left out of the name",
+};
+
+// Refusals an admin may wave through, with the override each one needs.
+const OVERRIDABLE: Partial<Record<ToggleResult["status"], { override: keyof Overrides; reason: string }>> = {
+  at_capacity: { override: "capacity", reason: "The venue is at capacity" },
+  over_guest_quota: { override: "quota", reason: "This guest is past their member's guest quota" },
+  member_quota_full: { override: "quota", reason: "Their member already has the maximum guests inside" },
+  banned: { override: "banned", reason: "This guest is on the banned list" },
+};
+
 function App() {
   const [dbPath, setDbPath] = useState<string | null>(null);
+  const [events, setEvents] = useState<PartyEvent[]>([]);
+  const [eventId, setEventId] = useState<number | null>(() => {
+    if (typeof window === "undefined") return null;
+    const stored = Number(window.localStorage.getItem("party-event"));
+    return Number.isFinite(stored) && stored > 0 ? stored : null;
+  });
+  const [eventDraft, setEventDraft] = useState<EventDraft | null>(null);
+  const [eventBusy, setEventBusy] = useState(false);
   const [query, setQuery] = useState("");
-  const [guestResults, setGuestResults] = useState<Guest[]>([]);
-  const [memberResults, setMemberResults] = useState<MemberSearchResult[]>([]);
-  const [memberGuests, setMemberGuests] = useState<Guest[]>([]);
-  const [memberGuestsLoading, setMemberGuestsLoading] = useState(false);
+  const [searchHits, setSearchHits] = useState<SearchHit[]>([]);
   const [selectedIndex, setSelectedIndex] = useState(0);
   const [isSearching, setIsSearching] = useState(false);
   const [toast, setToast] = useState<Toast | null>(null);
   const [pendingImportPath, setPendingImportPath] = useState<string | null>(null);
   const [showImportModePicker, setShowImportModePicker] = useState(false);
   const [importBusy, setImportBusy] = useState(false);
+  const [pendingImport, setPendingImport] = useState<PendingImport | null>(null);
+  const [importProfiles, setImportProfiles] = useState<ImportProfile[]>([]);
+  const [importProfile, setImportProfile] = useState("");
+  const [profileDraft, setProfileDraft] = useState<ProfileDraft | null>(null);
   const [operatorId, setOperatorId] = useState(() => {
     if (typeof window === "undefined") return "";
     return window.localStorage.getItem("party-operator") ?? "";
   });
   const [stats, setStats] = useState<StatsSummary | null>(null);
+  const [history, setHistory] = useState<UndoHistoryEntry[]>([]);
+  const [auditEntries, setAuditEntries] = useState<AuditEntry[]>([]);
+  const [auditGuest, setAuditGuest] = useState("");
+  const [auditOperator, setAuditOperator] = useState("");
+  const [bans, setBans] = useState<BannedGuest[]>([]);
+  const [batches, setBatches] = useState<ImportBatch[]>([]);
+  const [banName, setBanName] = useState("");
+  const [banReason, setBanReason] = useState("");
   const [viewMode, setViewMode] = useState<"search" | "dashboard">("search");
-  const [searchMode, setSearchMode] = useState<"guest" | "member">("guest");
+  const [mergeSource, setMergeSource] = useState<Guest | null>(null);
 
   useEffect(() => {
     window.localStorage.setItem("party-operator", operatorId);
   }, [operatorId]);
 
+  useEffect(() => {
+    if (eventId != null) {
+      window.localStorage.setItem("party-event", String(eventId));
+    }
+  }, [eventId]);
+
   const showToast = useCallback(
     (message: string, tone: ToastTone) => {
       setToast({ id: Date.now(), message, tone });
@@ -115,18 +458,74 @@ function App() {
     []
   );
 
-  const refreshStats = useCallback(
-    async (path?: string) => {
-      const target = path ?? dbPath;
-      if (!target) return;
+  const refreshStats = useCallback(async () => {
+    if (!dbPath || eventId == null) return;
+    try {
+      const [payload, recent, banned, imports] = await Promise.all([
+        invoke<StatsSummary>("stats_summary", { dbPath, eventId }),
+        invoke<UndoHistoryEntry[]>("list_undo_history", { dbPath, eventId, limit: 15 }),
+        invoke<BannedGuest[]>("list_banned", { dbPath }),
+        invoke<ImportBatch[]>("list_import_batches", { dbPath, eventId }),
+      ]);
+      setStats(payload);
+      setHistory(recent);
+      setBans(banned);
+      setBatches(imports);
+    } catch (error) {
+      console.error(error);
+    }
+  }, [dbPath, eventId]);
+
+  const searchAuditLog = useCallback(async () => {
+    if (!dbPath || eventId == null) return;
+    try {
+      const entries = await invoke<AuditEntry[]>("query_audit_log", {
+        dbPath,
+        filter: {
+          eventId,
+          guestName: auditGuest.trim() || null,
+          operator: auditOperator.trim() || null,
+          limit: 100,
+        },
+      });
+      setAuditEntries(entries);
+    } catch (error) {
+      console.error(error);
+      showToast("Audit log lookup failed", "error");
+    }
+  }, [auditGuest, auditOperator, dbPath, eventId, showToast]);
+
+  const addBan = useCallback(async () => {
+    if (!dbPath || banName.trim().length === 0) return;
+    try {
+      const ban = await invoke<BannedGuest>("add_banned", {
+        dbPath,
+        ban: { name: banName, reason: banReason.trim() || null },
+        operator: operatorId || null,
+      });
+      showToast(`Added ${ban.name} to the banned list`, "success");
+      setBanName("");
+      setBanReason("");
+      await refreshStats();
+    } catch (error) {
+      console.error(error);
+      showToast(String(error), "error");
+    }
+  }, [banName, banReason, dbPath, operatorId, refreshStats, showToast]);
+
+  const removeBan = useCallback(
+    async (ban: BannedGuest) => {
+      if (!dbPath || !window.confirm(`Lift the ban on ${ban.name}?`)) return;
       try {
-        const payload = await invoke<StatsSummary>("stats_summary", { dbPath: target });
-        setStats(payload);
+        await invoke("remove_banned", { dbPath, banId: ban.id, operator: operatorId || null });
+        showToast(`Lifted the ban on ${ban.name}`, "info");
+        await refreshStats();
       } catch (error) {
         console.error(error);
+        showToast(String(error), "error");
       }
     },
-    [dbPath]
+    [dbPath, operatorId, refreshStats, showToast]
   );
 
   const mapRawGuest = useCallback(
@@ -136,24 +535,32 @@ function App() {
       memberHost: guest.member_host,
       isCheckedIn: guest.is_checked_in,
       hasHistory: guest.has_history,
+      isBanned: guest.is_banned,
+      bannedReason: guest.banned_reason,
+      isRemoved: guest.is_removed,
+      score: guest.score,
     }),
     []
   );
 
   const runGuestSearch = useCallback(
-    async (db: string, text: string) => {
+    async (db: string, event: number, text: string) => {
       setIsSearching(true);
       try {
-        const payload = await invoke<RawGuest[]>("search_guests", {
+        const payload = await invoke<RawSearchHit[]>("search", {
           dbPath: db,
+          eventId: event,
           q: text,
           limit: DEFAULT_LIMIT,
         });
-        const mapped = payload.map(mapRawGuest);
-        setGuestResults(mapped);
-        if (searchMode === "guest") {
-          setSelectedIndex(0);
-        }
+        setSearchHits(
+          payload.map((hit): SearchHit =>
+            hit.kind === "guest"
+              ? { kind: "guest", guest: mapRawGuest(hit) }
+              : { kind: "member", member: hit }
+          )
+        );
+        setSelectedIndex(0);
       } catch (error) {
         console.error(error);
         showToast("Search failed", "error");
@@ -161,76 +568,31 @@ function App() {
         setIsSearching(false);
       }
     },
-    [mapRawGuest, searchMode, showToast]
+    [mapRawGuest, showToast]
   );
 
-  const runMemberSearch = useCallback(
-    async (
-      db: string,
-      text: string,
-      options?: {
-        retainMemberHost?: string;
-      }
-    ) => {
-      setIsSearching(true);
-      try {
-        const payload = await invoke<MemberSearchResult[]>("search_members", {
-          dbPath: db,
-          q: text,
-          limit: DEFAULT_LIMIT,
-        });
-        setMemberResults(payload);
-        if (searchMode === "member") {
-          if (options?.retainMemberHost) {
-            const retainedIndex = payload.findIndex(
-              (member) => member.memberHost === options.retainMemberHost
-            );
-            setSelectedIndex(retainedIndex >= 0 ? retainedIndex : 0);
-          } else {
-            setSelectedIndex(0);
-          }
-        }
-      } catch (error) {
-        console.error(error);
-        showToast("Member search failed", "error");
-      } finally {
-        setIsSearching(false);
-      }
-    },
-    [searchMode, showToast]
-  );
-
-  const fetchMemberGuests = useCallback(
-    async (memberHost: string) => {
-      if (!dbPath || memberHost.trim().length === 0) {
-        setMemberGuests([]);
-        return;
-      }
-      setMemberGuestsLoading(true);
-      try {
-        const payload = await invoke<RawGuest[]>("guests_for_member", {
-          dbPath,
-          memberHost,
-        });
-        setMemberGuests(payload.map(mapRawGuest));
-      } catch (error) {
-        console.error(error);
-      } finally {
-        setMemberGuestsLoading(false);
-      }
-    },
-    [dbPath, mapRawGuest]
-  );
+  const loadEvents = useCallback(async (db: string) => {
+    let payload = await invoke<PartyEvent[]>("list_events", { dbPath: db });
+    if (payload.length === 0) {
+      const created = await invoke<PartyEvent>("create_event", {
+        dbPath: db,
+        event: { name: "Party", eventDate: todayIso(), timezone: localTimezone() },
+      });
+      payload = [created];
+    }
+    setEvents(payload);
+    setEventId((current) =>
+      current != null && payload.some((event) => event.id === current) ? current : payload[0].id
+    );
+  }, []);
 
   const bootstrap = useCallback(async () => {
     const base = await appDataDir();
     const db = await join(base, "app.db");
     await invoke("init_db", { dbPath: db });
+    await loadEvents(db);
     setDbPath(db);
-    await runGuestSearch(db, "");
-    await runMemberSearch(db, "");
-    await refreshStats(db);
-  }, [refreshStats, runGuestSearch, runMemberSearch]);
+  }, [loadEvents]);
 
   useEffect(() => {
     bootstrap().catch((error) => {
@@ -240,56 +602,92 @@ function App() {
   }, [bootstrap, showToast]);
 
   useEffect(() => {
-    setSelectedIndex(0);
-  }, [searchMode]);
-
-  useEffect(() => {
-    if (viewMode === "dashboard") {
-      void refreshStats();
-    }
+    void refreshStats();
   }, [refreshStats, viewMode]);
 
   useEffect(() => {
-    if (!dbPath) return;
+    if (!dbPath || eventId == null) return;
     const handle = setTimeout(() => {
-      const task = searchMode === "guest"
-        ? runGuestSearch(dbPath, query)
-        : runMemberSearch(dbPath, query);
-      task.catch((error) => {
+      runGuestSearch(dbPath, eventId, query).catch((error) => {
         console.error(error);
       });
     }, query.length > 1 ? 80 : 0);
 
     return () => clearTimeout(handle);
-  }, [dbPath, query, searchMode, runGuestSearch, runMemberSearch]);
+  }, [dbPath, eventId, query, runGuestSearch]);
 
-  const selectedGuest = useMemo(() => {
-    if (searchMode !== "guest" || !guestResults.length) return null;
-    return guestResults[Math.max(0, Math.min(selectedIndex, guestResults.length - 1))];
-  }, [guestResults, searchMode, selectedIndex]);
+  const selectedEvent = useMemo(
+    () => events.find((event) => event.id === eventId) ?? null,
+    [eventId, events]
+  );
 
-  const selectedMember = useMemo(() => {
-    if (searchMode !== "member" || !memberResults.length) return null;
-    return memberResults[Math.max(0, Math.min(selectedIndex, memberResults.length - 1))];
-  }, [memberResults, searchMode, selectedIndex]);
+  const switchEvent = useCallback((nextId: number) => {
+    setEventId(nextId);
+    setQuery("");
+    setSelectedIndex(0);
+  }, []);
 
-  useEffect(() => {
-    if (searchMode !== "member" || !selectedMember?.memberHost) {
-      setMemberGuests([]);
-      setMemberGuestsLoading(false);
-      return;
+  const saveEvent = useCallback(async () => {
+    if (!dbPath || !eventDraft) return;
+    setEventBusy(true);
+    try {
+      const event = {
+        name: eventDraft.name,
+        eventDate: eventDraft.eventDate || null,
+        venue: eventDraft.venue || null,
+        doorsOpen: eventDraft.doorsOpen || null,
+        doorsClose: eventDraft.doorsClose || null,
+        timezone: eventDraft.timezone || null,
+        capacity: optionalNumber(eventDraft.capacity),
+        capacityWarningPercent: optionalNumber(eventDraft.capacityWarningPercent),
+        guestQuota: optionalNumber(eventDraft.guestQuota),
+        presentQuota: optionalNumber(eventDraft.presentQuota),
+      };
+      const current = events.find((existing) => existing.id === eventDraft.id);
+      let keepWallClock = false;
+      if (current && event.timezone && event.timezone !== current.timezone) {
+        // Check-ins are stored as instants; only the operator knows whether
+        // the old zone was wrong and times should keep reading the same.
+        keepWallClock = window.confirm(
+          `Change the timezone from ${current.timezone} to ${event.timezone}.\n\n` +
+            "OK: the old timezone was wrong; keep check-in times reading the same on the clock.\n" +
+            "Cancel: keep the moments check-ins happened; their times display in the new timezone."
+        );
+      }
+      const saved = eventDraft.id == null
+        ? await invoke<PartyEvent>("create_event", { dbPath, event })
+        : await invoke<PartyEvent>("update_event", { dbPath, eventId: eventDraft.id, event, keepWallClock });
+      await loadEvents(dbPath);
+      switchEvent(saved.id);
+      setEventDraft(null);
+      showToast(`${eventDraft.id == null ? "Created" : "Updated"} ${saved.name}`, "success");
+    } catch (error) {
+      console.error(error);
+      showToast(String(error), "error");
+    } finally {
+      setEventBusy(false);
     }
-    void fetchMemberGuests(selectedMember.memberHost);
-  }, [fetchMemberGuests, searchMode, selectedMember]);
+  }, [dbPath, eventDraft, events, loadEvents, showToast, switchEvent]);
+
+  const selectedHit = useMemo(() => {
+    if (!searchHits.length) return null;
+    return searchHits[Math.max(0, Math.min(selectedIndex, searchHits.length - 1))];
+  }, [searchHits, selectedIndex]);
+
+  const selectedGuest = selectedHit?.kind === "guest" ? selectedHit.guest : null;
 
   const handleKeyDown = (event: KeyboardEvent<HTMLInputElement>) => {
-    const listLength = searchMode === "guest" ? guestResults.length : memberResults.length;
+    const listLength = searchHits.length;
 
     if (!listLength) {
       if (event.key === "Enter") {
         event.preventDefault();
+        void addWalkIn();
       }
-      if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "z") {
+      if (isRedoShortcut(event)) {
+        event.preventDefault();
+        void redoLast();
+      } else if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "z") {
         event.preventDefault();
         void undoLast();
       }
@@ -304,23 +702,38 @@ function App() {
       setSelectedIndex((index) => Math.max(index - 1, 0));
     } else if (event.key === "Enter") {
       event.preventDefault();
-      if (searchMode === "member") {
-        if (selectedMember) {
-          setSearchMode("guest");
-          setQuery(selectedMember.memberHost);
-        }
+      if (selectedHit?.kind === "member") {
+        setQuery(hostQuery(selectedHit.member.memberHost));
         return;
       }
 
       if (!selectedGuest) return;
 
-      if (event.shiftKey) {
+      if (mergeSource) {
+        void mergeInto(selectedGuest);
+      } else if (event.shiftKey) {
         void toggleGuest(selectedGuest, "out", true);
       } else if (selectedGuest.isCheckedIn) {
         void toggleGuest(selectedGuest, "out");
       } else {
         void toggleGuest(selectedGuest, "in");
       }
+    } else if (event.key === "Escape" && mergeSource) {
+      event.preventDefault();
+      setMergeSource(null);
+    } else if (selectedGuest && isModShortcut(event, "e")) {
+      event.preventDefault();
+      void editGuest(selectedGuest);
+    } else if (selectedGuest && (event.metaKey || event.ctrlKey) && event.key === "Backspace") {
+      event.preventDefault();
+      void deleteGuest(selectedGuest);
+    } else if (selectedGuest && isModShortcut(event, "m")) {
+      event.preventDefault();
+      setMergeSource(selectedGuest);
+      showToast(`Pick the guest to keep and press Enter to merge ${selectedGuest.displayName} into them`, "info");
+    } else if (isRedoShortcut(event)) {
+      event.preventDefault();
+      void redoLast();
     } else if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "z") {
       event.preventDefault();
       void undoLast();
@@ -328,31 +741,27 @@ function App() {
   };
 
   const toggleGuest = useCallback(
-    async (guest: Guest, action: "in" | "out", force = false) => {
-      if (!dbPath) return;
-      const retainedMemberHost =
-        searchMode === "member" ? selectedMember?.memberHost : undefined;
+    async (guest: Guest, action: "in" | "out", force = false, overrides: Overrides = {}): Promise<void> => {
+      if (!dbPath || eventId == null) return;
       try {
         const payload = await invoke<ToggleResult>("toggle_checkin", {
           dbPath,
+          eventId,
           guestId: guest.id,
           action,
           operator: operatorId || null,
           force,
+          overrides,
         });
 
         switch (payload.status) {
           case "checked_in":
             showToast(`Checked in ${guest.displayName}`, "success");
-            if (searchMode === "guest") {
-              setQuery("");
-            }
+            setQuery("");
             break;
           case "checked_out":
             showToast(`Checked out ${guest.displayName}`, "info");
-            if (searchMode === "guest") {
-              setQuery("");
-            }
+            setQuery("");
             break;
           case "already_in":
             showToast(`${guest.displayName} already checked in`, "info");
@@ -363,38 +772,213 @@ function App() {
           case "never_checked_in":
             showToast(`${guest.displayName} has never been checked in`, "info");
             break;
+          case "removed":
+            showToast(`${guest.displayName} was taken off the list by a sync import`, "error");
+            break;
+          case "at_capacity":
+          case "over_guest_quota":
+          case "member_quota_full":
+          case "banned": {
+            const { override, reason } = OVERRIDABLE[payload.status]!;
+            if (window.confirm(`${reason}. Admit ${guest.displayName} anyway?`)) {
+              return await toggleGuest(guest, action, force, { ...overrides, [override]: true });
+            }
+            showToast(`${reason}: ${guest.displayName} was not checked in`, "error");
+            break;
+          }
         }
       } catch (error) {
         console.error(error);
         showToast("Check-in failed", "error");
       } finally {
         if (dbPath) {
-          if (searchMode === "guest") {
-            await runGuestSearch(dbPath, action === "in" ? "" : query);
-          } else {
-            await runGuestSearch(dbPath, "");
-            await runMemberSearch(
-              dbPath,
-              query,
-              retainedMemberHost
-                ? { retainMemberHost: retainedMemberHost }
-                : undefined
-            );
-            if (retainedMemberHost) {
-              await fetchMemberGuests(retainedMemberHost);
-            }
-          }
+          await runGuestSearch(dbPath, eventId, action === "in" ? "" : query);
           await refreshStats();
         }
       }
     },
-    [dbPath, fetchMemberGuests, operatorId, query, refreshStats, runGuestSearch, runMemberSearch, searchMode, selectedMember, showToast]
+    [dbPath, eventId, operatorId, query, refreshStats, runGuestSearch, showToast]
+  );
+
+  const addWalkIn = useCallback(
+    async (overrides: Overrides = {}, guest?: { displayName: string; memberHost: string | null }): Promise<void> => {
+      if (!dbPath || eventId == null) return;
+      let walkIn = guest;
+      if (!walkIn) {
+        const parsedName = query.replace(/\b\w+:("[^"]*"|\S*)/g, "").trim();
+        const displayName = window.prompt("Walk-in guest name", parsedName)?.trim();
+        if (!displayName) return;
+        const memberHost = window.prompt("Brother bringing them (leave blank for none)")?.trim();
+        walkIn = { displayName, memberHost: memberHost || null };
+      }
+      try {
+        const payload = await invoke<WalkInResult>("add_guest", {
+          dbPath,
+          eventId,
+          guest: walkIn,
+          operator: operatorId || null,
+          overrides,
+        });
+        const refusal = OVERRIDABLE[payload.status];
+        if (payload.status === "checked_in") {
+          showToast(`Added and checked in ${walkIn.displayName}`, "success");
+          setQuery("");
+        } else if (refusal) {
+          if (window.confirm(`${refusal.reason}. Admit ${walkIn.displayName} anyway?`)) {
+            return await addWalkIn({ ...overrides, [refusal.override]: true }, walkIn);
+          }
+          showToast(`${refusal.reason}: ${walkIn.displayName} was not added`, "error");
+        }
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      } finally {
+        await runGuestSearch(dbPath, eventId, "");
+        await refreshStats();
+      }
+    },
+    [dbPath, eventId, operatorId, query, refreshStats, runGuestSearch, showToast]
+  );
+
+  const rollbackBatch = useCallback(
+    async (batch: ImportBatch) => {
+      if (!dbPath || eventId == null) return;
+      const label = batch.fileName ?? `import ${batch.id}`;
+      if (
+        !window.confirm(
+          `Roll back ${label}? Its ${batch.guests.toLocaleString()} guests and all of their check-ins will be removed.` +
+            (batch.mode === "replace" ? " The list it replaced will be restored." : "") +
+            (batch.mode === "sync" ? " Guests it moved or removed go back as they were." : "") +
+            (batch.doorCheckins > 0
+              ? `\n\n${batch.doorCheckins.toLocaleString()} check-in(s) recorded at the door will be lost.`
+              : "") +
+            (batch.laterImports > 0
+              ? `\n\n${batch.laterImports.toLocaleString()} later import(s) changed the list since; their changes will be overridden.`
+              : "")
+        )
+      ) {
+        return;
+      }
+      try {
+        const summary = await invoke<RollbackSummary>("rollback_import_batch", {
+          dbPath,
+          eventId,
+          batchId: batch.id,
+          operator: operatorId || null,
+          confirm: batch.doorCheckins > 0 || batch.laterImports > 0,
+        });
+        showToast(
+          `Removed ${summary.guestsRemoved} guests and ${summary.checkinsRemoved} check-ins from ${label}` +
+            (summary.doorCheckinsRemoved > 0 ? ` (${summary.doorCheckinsRemoved} made at the door)` : "") +
+            (summary.guestsRestored > 0 ? `, restored ${summary.guestsRestored} replaced guests` : "") +
+            (summary.laterImportsOverridden > 0
+              ? `, overrode ${summary.laterImportsOverridden} later imports`
+              : ""),
+          "info"
+        );
+        await runGuestSearch(dbPath, eventId, "");
+        await refreshStats();
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      }
+    },
+    [dbPath, eventId, operatorId, refreshStats, runGuestSearch, showToast]
+  );
+
+  const refreshAfterChange = useCallback(async () => {
+    if (!dbPath || eventId == null) return;
+    await runGuestSearch(dbPath, eventId, query);
+    await refreshStats();
+  }, [dbPath, eventId, query, refreshStats, runGuestSearch]);
+
+  const editGuest = useCallback(
+    async (guest: Guest): Promise<void> => {
+      if (!dbPath || eventId == null) return;
+      const displayName = window.prompt("Guest name", guest.displayName)?.trim();
+      if (!displayName) return;
+      const memberHost = window.prompt("Brother bringing them (leave blank for none)", guest.memberHost ?? "");
+      if (memberHost == null) return;
+      try {
+        await invoke("edit_guest", {
+          dbPath,
+          eventId,
+          guestId: guest.id,
+          edit: { displayName, memberHost: memberHost.trim() || null },
+          operator: operatorId || null,
+        });
+        showToast(`Updated ${displayName}`, "success");
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      } finally {
+        await refreshAfterChange();
+      }
+    },
+    [dbPath, eventId, operatorId, refreshAfterChange, showToast]
+  );
+
+  const deleteGuest = useCallback(
+    async (guest: Guest): Promise<void> => {
+      if (!dbPath || eventId == null) return;
+      if (!window.confirm(`Delete ${guest.displayName} and their check-ins? You can undo this.`)) return;
+      try {
+        await invoke("delete_guest", {
+          dbPath,
+          eventId,
+          guestId: guest.id,
+          operator: operatorId || null,
+        });
+        showToast(`Deleted ${guest.displayName}`, "info");
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      } finally {
+        await refreshAfterChange();
+      }
+    },
+    [dbPath, eventId, operatorId, refreshAfterChange, showToast]
+  );
+
+  const mergeInto = useCallback(
+    async (survivor: Guest): Promise<void> => {
+      if (!dbPath || eventId == null || !mergeSource) return;
+      const duplicate = mergeSource;
+      setMergeSource(null);
+      if (duplicate.id === survivor.id) {
+        showToast("Pick a different guest to merge into", "error");
+        return;
+      }
+      if (!window.confirm(`Merge ${duplicate.displayName} into ${survivor.displayName}? Their check-ins move over.`)) {
+        return;
+      }
+      try {
+        await invoke("merge_guests", {
+          dbPath,
+          eventId,
+          survivorId: survivor.id,
+          duplicateId: duplicate.id,
+          operator: operatorId || null,
+        });
+        showToast(`Merged ${duplicate.displayName} into ${survivor.displayName}`, "success");
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      } finally {
+        await refreshAfterChange();
+      }
+    },
+    [dbPath, eventId, mergeSource, operatorId, refreshAfterChange, showToast]
   );
 
   const undoLast = useCallback(async () => {
-    if (!dbPath) return;
+    if (!dbPath || eventId == null) return;
     try {
-      const payload = await invoke<UndoResult>("undo_last", { dbPath });
+      const payload = await invoke<UndoResult>("undo_last", {
+        dbPath,
+        eventId,
+        operator: operatorId || null,
+      });
       switch (payload.status) {
         case "reverted_check_in":
           showToast("Reverted last check-in", "info");
@@ -402,115 +986,315 @@ function App() {
         case "reverted_check_out":
           showToast("Reverted last check-out", "info");
           break;
+        case "reverted_edit":
+          showToast("Reverted last guest edit", "info");
+          break;
+        case "reverted_delete":
+          showToast("Restored deleted guest", "info");
+          break;
+        case "reverted_merge":
+          showToast("Reverted last guest merge", "info");
+          break;
         case "empty":
           showToast("Nothing to undo", "info");
           break;
       }
     } catch (error) {
       console.error(error);
-      showToast("Undo failed", "error");
-    } finally {
-      if (dbPath) {
-        await runGuestSearch(dbPath, query);
-        await refreshStats();
-      }
+      showToast("Undo failed", "error");
+    } finally {
+      if (dbPath) {
+        await runGuestSearch(dbPath, eventId, query);
+        await refreshStats();
+      }
+    }
+  }, [dbPath, eventId, operatorId, query, refreshStats, runGuestSearch, showToast]);
+
+  const redoLast = useCallback(async () => {
+    if (!dbPath || eventId == null) return;
+    try {
+      const payload = await invoke<RedoResult>("redo_last", {
+        dbPath,
+        eventId,
+        operator: operatorId || null,
+      });
+      switch (payload.status) {
+        case "reapplied_check_in":
+          showToast("Redid check-in", "info");
+          break;
+        case "reapplied_check_out":
+          showToast("Redid check-out", "info");
+          break;
+        case "reapplied_edit":
+          showToast("Redid guest edit", "info");
+          break;
+        case "reapplied_delete":
+          showToast("Deleted guest again", "info");
+          break;
+        case "reapplied_merge":
+          showToast("Redid guest merge", "info");
+          break;
+        case "empty":
+          showToast("Nothing to redo", "info");
+          break;
+      }
+    } catch (error) {
+      console.error(error);
+      showToast("Redo failed", "error");
+    } finally {
+      await runGuestSearch(dbPath, eventId, query);
+      await refreshStats();
+    }
+  }, [dbPath, eventId, operatorId, query, refreshStats, runGuestSearch, showToast]);
+
+  const beginImport = useCallback(async () => {
+    try {
+      const selection = await open({
+        filters: [{ name: "Guest list", extensions: ["csv", "tsv", "txt"] }],
+        multiple: false,
+      });
+      if (!selection || Array.isArray(selection)) {
+        return;
+      }
+      if (dbPath) {
+        setImportProfiles(await invoke<ImportProfile[]>("list_import_profiles", { dbPath }));
+      }
+      setPendingImportPath(selection);
+      setShowImportModePicker(true);
+    } catch (error) {
+      console.error(error);
+      showToast("Unable to open CSV", "error");
+    }
+  }, [dbPath, showToast]);
+
+  const closeImport = useCallback(() => {
+    setShowImportModePicker(false);
+    setPendingImportPath(null);
+    setPendingImport(null);
+    setProfileDraft(null);
+  }, []);
+
+  // Starts a profile from the chosen file's header row, prefilled from the
+  // selected profile when there is one.
+  const beginProfile = useCallback(async () => {
+    if (!pendingImportPath) return;
+    try {
+      const headers = await invoke<string[]>("read_file_headers", { path: pendingImportPath });
+      const current = importProfiles.find((profile) => profile.name === importProfile);
+      setProfileDraft({
+        name: current?.name ?? "",
+        headers,
+        columns: current?.columns ?? {
+          memberName: null,
+          guestNames: null,
+          checkIn: null,
+          checkInTime: null,
+          checkOut: null,
+          checkOutTime: null,
+          walkIn: null,
+        },
+      });
+    } catch (error) {
+      console.error(error);
+      showToast(String(error), "error");
+    }
+  }, [importProfile, importProfiles, pendingImportPath, showToast]);
+
+  const saveProfile = useCallback(async () => {
+    if (!dbPath || !profileDraft) return;
+    try {
+      const saved = await invoke<ImportProfile>("save_import_profile", {
+        dbPath,
+        profile: { name: profileDraft.name, columns: profileDraft.columns },
+        operator: operatorId || null,
+      });
+      setImportProfiles(await invoke<ImportProfile[]>("list_import_profiles", { dbPath }));
+      setImportProfile(saved.name);
+      setProfileDraft(null);
+      showToast(`Saved profile ${saved.name}`, "success");
+    } catch (error) {
+      console.error(error);
+      showToast(String(error), "error");
     }
-  }, [dbPath, query, refreshStats, runGuestSearch, showToast]);
+  }, [dbPath, operatorId, profileDraft, showToast]);
 
-  const beginImport = useCallback(async () => {
+  const deleteProfile = useCallback(async () => {
+    if (!dbPath || !importProfile) return;
+    if (!window.confirm(`Delete the import profile ${importProfile}?`)) return;
     try {
-      const selection = await open({
-        filters: [{ name: "CSV", extensions: ["csv"] }],
-        multiple: false,
-      });
-      if (!selection || Array.isArray(selection)) {
-        return;
-      }
-      setPendingImportPath(selection);
-      setShowImportModePicker(true);
+      await invoke("delete_import_profile", { dbPath, name: importProfile, operator: operatorId || null });
+      setImportProfiles(await invoke<ImportProfile[]>("list_import_profiles", { dbPath }));
+      setImportProfile("");
     } catch (error) {
       console.error(error);
-      showToast("Unable to open CSV", "error");
+      showToast(String(error), "error");
     }
-  }, [showToast]);
+  }, [dbPath, importProfile, operatorId, showToast]);
 
-  const importCsv = useCallback(
-    async (mode: "replace" | "append") => {
-      if (!dbPath || !pendingImportPath) return;
+  // Asks the backend what importing the file would do; nothing is written
+  // until the preview is confirmed.
+  const previewCsv = useCallback(
+    async (mode: ImportMode) => {
+      if (!dbPath || eventId == null || !pendingImportPath) return;
       setImportBusy(true);
       try {
-        const raw = await readTextFile(pendingImportPath);
-        const parsed = Papa.parse<Record<string, string>>(raw, {
-          header: true,
-          skipEmptyLines: true,
+        const profile = importProfile || null;
+        const preview = await invoke<ImportPreview>("preview_file", {
+          dbPath,
+          eventId,
+          path: pendingImportPath,
+          profile,
+          mode,
         });
+        setPendingImport({ mode, profile, preview });
+        setShowImportModePicker(false);
+      } catch (error) {
+        console.error(error);
+        showToast(String(error), "error");
+      } finally {
+        setImportBusy(false);
+      }
+    },
+    [dbPath, eventId, importProfile, pendingImportPath, showToast]
+  );
 
-        if (parsed.errors.length) {
-          console.warn(parsed.errors);
-        }
-
-        const rows = parsed.data.map((row, index) => {
-          const pull = (keys: string[]) => {
-            for (const key of keys) {
-              const value = row[key];
-              if (value == null) continue;
-              const normalized = typeof value === "string" ? value.trim() : String(value);
-              if (normalized.length === 0) continue;
-              return normalized;
-            }
-            return null;
-          };
-
-          return {
-            memberName: pull(["Member Name", "member_name"]),
-            guestNames: pull(["Guest Names", "guest_names"]),
-            checkIn: pull(["Check In Y/N", "check_in_y/n", "check_in_y_n"]),
-            checkInTime: pull(["Check In Time", "check_in_time"]),
-            checkOut: pull(["Check Out Y/N", "check_out_y/n", "check_out_y_n"]),
-            checkOutTime: pull(["Check Out Time", "check_out_time"]),
-            sourceRow: index + 2,
-          };
-        });
+  const importCsv = useCallback(async () => {
+    if (!dbPath || eventId == null || !pendingImport || !pendingImportPath) return;
+    const { mode, profile, preview } = pendingImport;
+    const file = { path: pendingImportPath, profile };
+    const checkedIn = preview.summary.replaced?.checked_in ?? 0;
+    if (
+      checkedIn > 0 &&
+      !window.confirm(
+        `${checkedIn} guest(s) are checked in right now. Replace the list anyway? ` +
+          "The current list and its check-ins are archived, and rolling back this import restores them."
+      )
+    ) {
+      return;
+    }
+    setImportBusy(true);
+    try {
+      const payload = await invoke<ImportSummary>("import_file", {
+        dbPath,
+        eventId,
+        ...file,
+        mode,
+        operator: operatorId || null,
+        overrides: { checkedIn: checkedIn > 0 },
+      });
+      let inserted = payload.inserted;
+      const overQuota = payload.over_quota;
 
-        const payload = await invoke<ImportSummary>("import_rows", {
+      if (
+        overQuota.length > 0 &&
+        window.confirm(
+          `${overQuota.length} guest(s) are past their member's quota:\n` +
+            overQuota
+              .slice(0, 10)
+              .map((over) => `• ${over.guest_name} (${over.member_name}, quota ${over.quota})`)
+              .join("\n") +
+            "\n\nAdd them anyway?"
+        )
+      ) {
+        // The first pass already applied `mode`; appending again adds only
+        // the guests it held back.
+        const admitted = await invoke<ImportSummary>("import_file", {
           dbPath,
-          rows,
-          mode,
+          eventId,
+          ...file,
+          mode: "append",
+          operator: operatorId || null,
+          overrides: { quota: true },
         });
+        inserted += admitted.inserted;
+      } else if (overQuota.length > 0) {
+        showToast(`${overQuota.length} guest(s) skipped for member quotas`, "info");
+      }
+      if (payload.banned.length > 0) {
+        window.alert(
+          `${payload.banned.length} imported guest(s) match the banned list and will be refused at the door:\n` +
+            payload.banned
+              .map((match) => `• ${match.guest_name} → ${match.banned_name}${match.reason ? ` (${match.reason})` : ""}`)
+              .join("\n")
+        );
+      }
 
-        showToast(
-          `Imported ${payload.total_rows} rows → ${payload.inserted} guests`,
-          "success"
+      showToast(
+        `Imported ${payload.total_rows} rows → ${inserted} guests` +
+          (payload.replaced
+            ? ` (replaced ${payload.replaced.guests} guests and ${payload.replaced.checkins} check-ins, archived)`
+            : "") +
+          (payload.synced
+            ? ` (${payload.synced.updated} updated, ${payload.synced.removed} removed, ${payload.synced.unchanged} unchanged)`
+            : ""),
+        "success"
+      );
+      setQuery("");
+      await runGuestSearch(dbPath, eventId, "");
+      await refreshStats();
+    } catch (error) {
+      console.error(error);
+      showToast("Import failed", "error");
+    } finally {
+      setImportBusy(false);
+      closeImport();
+    }
+  }, [closeImport, dbPath, eventId, operatorId, pendingImport, pendingImportPath, refreshStats, runGuestSearch, showToast]);
+
+  const editMemberQuota = useCallback(
+    async (memberHost: string) => {
+      if (!dbPath || eventId == null) return;
+      try {
+        const quotas = await invoke<MemberQuota[]>("list_member_quotas", { dbPath, eventId });
+        const current = quotas.find((quota) => quota.memberHost.toLowerCase() === memberHost.toLowerCase());
+        const guests = window.prompt(
+          `Guests ${memberHost} may list (blank for the event default)`,
+          current?.guestQuota?.toString() ?? ""
         );
-        setQuery("");
-        await runGuestSearch(dbPath, "");
-        await runMemberSearch(dbPath, "");
-        await refreshStats();
+        if (guests == null) return;
+        const present = window.prompt(
+          `Guests of ${memberHost} allowed inside at once (blank for the event default)`,
+          current?.presentQuota?.toString() ?? ""
+        );
+        if (present == null) return;
+        await invoke("set_member_quota", {
+          dbPath,
+          eventId,
+          quota: { memberHost, guestQuota: optionalNumber(guests), presentQuota: optionalNumber(present) },
+          operator: operatorId || null,
+        });
+        showToast(`Updated quota for ${memberHost}`, "success");
       } catch (error) {
         console.error(error);
-        showToast("Import failed", "error");
-      } finally {
-        setImportBusy(false);
-        setShowImportModePicker(false);
-        setPendingImportPath(null);
+        showToast(String(error), "error");
       }
     },
-    [dbPath, pendingImportPath, refreshStats, runGuestSearch, runMemberSearch, showToast]
+    [dbPath, eventId, operatorId, showToast]
   );
 
   const exportCsv = useCallback(async () => {
-    if (!dbPath) return;
+    if (!dbPath || eventId == null) return;
     try {
-      const path = await invoke<string>("export_csv", { dbPath });
+      const path = await invoke<string>("export_csv", { dbPath, eventId });
       showToast(`Exported to ${path}`, "success");
       await refreshStats();
     } catch (error) {
       console.error(error);
       showToast("Export failed", "error");
     }
-  }, [dbPath, refreshStats, showToast]);
+  }, [dbPath, eventId, refreshStats, showToast]);
 
   const statusBadge = useCallback((guest: Guest) => {
+    if (guest.isBanned) {
+      return (
+        <span
+          title={guest.bannedReason ?? undefined}
+          className="rounded-full bg-rose-500/20 px-2 py-1 text-xs font-semibold text-rose-200"
+        >
+          Do not admit
+        </span>
+      );
+    }
     if (guest.isCheckedIn) {
       return (
         <span className="rounded-full bg-emerald-500/10 px-2 py-1 text-xs text-emerald-300">
@@ -518,6 +1302,13 @@ function App() {
         </span>
       );
     }
+    if (guest.isRemoved) {
+      return (
+        <span className="rounded-full bg-slate-700/60 px-2 py-1 text-xs text-slate-400">
+          Removed
+        </span>
+      );
+    }
     if (guest.hasHistory) {
       return (
         <span className="rounded-full bg-amber-400/10 px-2 py-1 text-xs text-amber-200">
@@ -568,14 +1359,9 @@ function App() {
   const topHosts = stats?.topHosts ?? [];
   const presentGuestsList = stats?.presentGuests ?? [];
   const maxHostTotal = topHosts.length > 0 ? Math.max(...topHosts.map((host) => host.totalGuests)) : 1;
-  const searchPlaceholder =
-    searchMode === "guest" ? "Search guests by name" : "Search brothers by member name";
-  const listItemsCount = searchMode === "guest" ? guestResults.length : memberResults.length;
   const headerSubtitle =
     viewMode === "search"
-      ? searchMode === "guest"
-        ? "Search guests · Enter toggles check-in/out · Shift+Enter forces check-out"
-        : "Search brothers"
+      ? "Search guests and brothers · Enter toggles check-in/out · Shift+Enter forces check-out"
       : "Live attendance dashboard and insights";
 
   return (
@@ -587,6 +1373,33 @@ function App() {
             <p className="text-sm text-slate-400">{headerSubtitle}</p>
           </div>
           <div className="flex flex-wrap items-center justify-end gap-3">
+            <div className="flex items-center gap-2 text-sm text-slate-300">
+              <select
+                value={eventId ?? ""}
+                onChange={(event) => switchEvent(Number(event.target.value))}
+                className="max-w-[14rem] rounded-md border border-slate-700 bg-slate-900 px-2 py-1 text-sm shadow-inner focus:border-emerald-400 focus:outline-none"
+                title={selectedEvent?.venue ?? undefined}
+              >
+                {events.map((event) => (
+                  <option key={event.id} value={event.id}>
+                    {event.eventDate ? `${event.name} · ${event.eventDate}` : event.name}
+                  </option>
+                ))}
+              </select>
+              <button
+                onClick={() => selectedEvent && setEventDraft(eventDraftFrom(selectedEvent))}
+                disabled={!selectedEvent}
+                className="rounded-md border border-slate-600 px-2 py-1 text-sm text-slate-200 hover:border-slate-400 hover:text-white disabled:opacity-50"
+              >
+                Edit
+              </button>
+              <button
+                onClick={() => setEventDraft(emptyEventDraft())}
+                className="rounded-md border border-slate-600 px-2 py-1 text-sm text-slate-200 hover:border-slate-400 hover:text-white"
+              >
+                New event
+              </button>
+            </div>
             <div className="inline-flex rounded-md border border-slate-700 bg-slate-900/60 p-1 text-sm">
               <button
                 className={clsx(
@@ -638,6 +1451,12 @@ function App() {
             >
               Undo
             </button>
+            <button
+              onClick={redoLast}
+              className="rounded-md border border-slate-600 px-3 py-2 text-sm font-medium text-slate-200 hover:border-slate-400 hover:text-white"
+            >
+              Redo
+            </button>
           </div>
         </div>
       </header>
@@ -645,61 +1464,33 @@ function App() {
       <main className="mx-auto flex w-full max-w-6xl flex-1 flex-col gap-6 px-6 py-6">
         {viewMode === "search" ? (
           <>
-            <div className="flex flex-wrap items-center justify-between gap-3">
-              <div className="inline-flex rounded-md border border-slate-700 bg-slate-900/60 p-1 text-sm">
-                <button
-                  className={clsx(
-                    "rounded-md px-3 py-1.5 font-medium",
-                    searchMode === "guest"
-                      ? "bg-emerald-500 text-emerald-950 shadow"
-                      : "text-slate-300 hover:text-white"
-                  )}
-                  onClick={() => setSearchMode("guest")}
-                >
-                  Guests
-                </button>
-                <button
-                  className={clsx(
-                    "rounded-md px-3 py-1.5 font-medium",
-                    searchMode === "member"
-                      ? "bg-slate-100 text-slate-900 shadow"
-                      : "text-slate-300 hover:text-white"
-                  )}
-                  onClick={() => setSearchMode("member")}
-                >
-                  Brothers
-                </button>
-              </div>
-              <div className="text-xs text-slate-400">Results: {listItemsCount}</div>
-            </div>
-
             <div>
               <input
                 autoFocus
                 value={query}
                 onChange={(event) => setQuery(event.target.value)}
-                placeholder={searchPlaceholder}
+                placeholder="Search guests and brothers · host:name in: out: never:"
                 onKeyDown={handleKeyDown}
                 className="h-14 w-full rounded-lg border border-slate-700 bg-slate-900 px-4 text-lg shadow focus:border-emerald-500 focus:outline-none"
               />
               <div className="mt-2 flex items-center justify-between text-xs text-slate-400">
                 <span>
-                  {searchMode === "guest" ? (
-                    selectedGuest ? (
-                      <>
-                        <strong className="text-slate-200">{selectedGuest.displayName}</strong>
-                        {selectedGuest.memberHost ? ` · Host: ${selectedGuest.memberHost}` : ""}
-                      </>
-                    ) : (
-                      "No matches. Press Enter for walk-in"
-                    )
-                  ) : selectedMember ? (
+                  {selectedGuest ? (
+                    <>
+                      <strong className="text-slate-200">{selectedGuest.displayName}</strong>
+                      {selectedGuest.memberHost ? ` · Host: ${selectedGuest.memberHost}` : ""}
+                      {mergeSource
+                        ? ` · Enter merges ${mergeSource.displayName} into this guest, Esc cancels`
+                        : " · ⌘E edit · ⌘⌫ delete · ⌘M merge"}
+                    </>
+                  ) : selectedHit?.kind === "member" ? (
                     <>
-                      <strong className="text-slate-200">{selectedMember.memberHost}</strong>
-                      {` · ${selectedMember.presentGuests.toLocaleString()} present / ${selectedMember.totalGuests.toLocaleString()} total`}
+                      <strong className="text-slate-200">{selectedHit.member.memberHost}</strong>
+                      {` · ${selectedHit.member.presentGuests.toLocaleString()} present / ${selectedHit.member.totalGuests.toLocaleString()} total`}
+                      {" · Enter shows their guests"}
                     </>
                   ) : (
-                    "No brother results. Try a different name."
+                    "No matches. Press Enter for walk-in"
                   )}
                 </span>
                 {isSearching && <span>Searching…</span>}
@@ -708,18 +1499,55 @@ function App() {
 
             <section className="flex flex-1 flex-col overflow-hidden rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
               <div className="border-b border-slate-800 px-4 py-2 text-xs uppercase tracking-wide text-slate-400">
-                Results ({listItemsCount})
+                Results ({searchHits.length})
               </div>
               <div className="flex-1 overflow-y-auto">
-                {searchMode === "guest" ? (
-                  guestResults.length === 0 ? (
-                    <div className="flex h-full flex-col items-center justify-center gap-2 text-sm text-slate-400">
-                      <p>No matches</p>
-                      <p>Check spelling or import a guest list.</p>
-                    </div>
-                  ) : (
-                    <ul className="divide-y divide-slate-800">
-                      {guestResults.map((guest, index) => (
+                {searchHits.length === 0 ? (
+                  <div className="flex h-full flex-col items-center justify-center gap-2 text-sm text-slate-400">
+                    <p>No matches</p>
+                    <p>Check spelling or import a guest list.</p>
+                  </div>
+                ) : (
+                  <ul className="divide-y divide-slate-800">
+                    {searchHits.map((hit, index) => {
+                      if (hit.kind === "member") {
+                        const member = hit.member;
+                        return (
+                          <li
+                            key={`member:${member.memberHost}`}
+                            onMouseEnter={() => setSelectedIndex(index)}
+                            onDoubleClick={() => setQuery(hostQuery(member.memberHost))}
+                            className={clsx(
+                              "flex cursor-pointer items-center justify-between gap-3 px-4 py-3",
+                              index === selectedIndex
+                                ? "bg-slate-100/10"
+                                : "hover:bg-slate-800/50"
+                            )}
+                          >
+                            <div>
+                              <div className="text-base font-medium text-slate-50">
+                                {member.memberHost}
+                              </div>
+                              <div className="text-xs text-slate-400">
+                                Brother · {member.presentGuests.toLocaleString()} present · {member.totalGuests.toLocaleString()} total
+                              </div>
+                            </div>
+                            <div className="flex items-center gap-3">
+                              <button
+                                onClick={() => void editMemberQuota(member.memberHost)}
+                                className="rounded border border-slate-700 px-2 py-0.5 text-xs text-slate-300 hover:border-emerald-400"
+                              >
+                                Quota
+                              </button>
+                              <span className="rounded-full bg-slate-700 px-2 py-1 text-xs font-semibold text-slate-200">
+                                Group
+                              </span>
+                            </div>
+                          </li>
+                        );
+                      }
+                      const guest = hit.guest;
+                      return (
                         <li
                           key={guest.id}
                           onMouseEnter={() => setSelectedIndex(index)}
@@ -739,121 +1567,16 @@ function App() {
                             </div>
                             <div className="text-xs text-slate-400">
                               {guest.memberHost ? `Host: ${guest.memberHost}` : "No host"}
+                              {guest.score != null && guest.score < 1
+                                ? ` · ~${Math.round(guest.score * 100)}% match`
+                                : ""}
                             </div>
                           </div>
                           {statusBadge(guest)}
                         </li>
-                      ))}
-                    </ul>
-                  )
-                ) : memberResults.length === 0 ? (
-                  <div className="flex h-full flex-col items-center justify-center gap-2 text-sm text-slate-400">
-                    <p>No matches</p>
-                    <p>We couldn’t find that brother.</p>
-                  </div>
-                ) : (
-                  <div className="flex h-full flex-col md:flex-row">
-                    <div className="md:w-5/12 md:border-r md:border-slate-800">
-                      <ul className="divide-y divide-slate-800">
-                        {memberResults.map((member, index) => {
-                          const ratio = member.totalGuests > 0
-                            ? Math.min(100, Math.round((member.presentGuests / member.totalGuests) * 100))
-                            : 0;
-                          const active = index === selectedIndex;
-                          return (
-                            <li
-                              key={member.memberHost}
-                              onMouseEnter={() => setSelectedIndex(index)}
-                              onClick={() => setSelectedIndex(index)}
-                              className={clsx(
-                                "flex cursor-pointer items-center justify-between gap-3 px-4 py-3",
-                                active ? "bg-slate-100/10" : "hover:bg-slate-800/50"
-                              )}
-                            >
-                              <div>
-                                <div className="text-base font-medium text-slate-50">
-                                  {member.memberHost}
-                                </div>
-                                <div className="text-xs text-slate-400">
-                                  {member.presentGuests.toLocaleString()} present · {member.totalGuests.toLocaleString()} total
-                                </div>
-                              </div>
-                              <div className="flex w-32 items-center gap-2">
-                                <div className="h-2 flex-1 rounded-full bg-slate-800">
-                                  <div
-                                    className="h-full rounded-full bg-emerald-500"
-                                    style={{ width: `${ratio}%` }}
-                                  />
-                                </div>
-                                <span className="text-xs text-slate-400">{ratio}%</span>
-                              </div>
-                            </li>
-                          );
-                        })}
-                      </ul>
-                    </div>
-                    <div className="mt-4 flex-1 md:mt-0 md:border-l md:border-slate-800">
-                      {selectedMember ? (
-                        <div className="flex h-full flex-col">
-                          <div className="border-b border-slate-800 px-4 py-3">
-                            <div className="text-sm font-semibold text-slate-100">
-                              {selectedMember.memberHost}
-                            </div>
-                            <div className="text-xs text-slate-400">
-                              {selectedMember.presentGuests.toLocaleString()} present · {selectedMember.totalGuests.toLocaleString()} total guests
-                            </div>
-                          </div>
-                          <div className="flex-1 overflow-y-auto">
-                            {memberGuestsLoading ? (
-                              <div className="flex h-full items-center justify-center text-sm text-slate-400">
-                                Loading guests…
-                              </div>
-                            ) : memberGuests.length === 0 ? (
-                              <div className="flex h-full flex-col items-center justify-center gap-2 text-sm text-slate-400">
-                                <p>No guests on file for this brother.</p>
-                              </div>
-                            ) : (
-                              <ul className="divide-y divide-slate-800">
-                                {memberGuests.map((guest) => (
-                                  <li key={guest.id} className="flex items-center justify-between gap-3 px-4 py-3">
-                                    <div>
-                                      <div className="text-sm font-medium text-slate-100">{guest.displayName}</div>
-                                      <div className="text-xs text-slate-500">
-                                        {guest.memberHost ? `Host: ${guest.memberHost}` : "No host"}
-                                      </div>
-                                    </div>
-                                    <div className="flex items-center gap-3">
-                                      {statusBadge(guest)}
-                                      <button
-                                        className={clsx(
-                                          "rounded-md px-3 py-1 text-xs font-semibold",
-                                          guest.isCheckedIn
-                                            ? "bg-slate-700 text-slate-100 hover:bg-slate-600"
-                                            : "bg-emerald-500 text-emerald-950 hover:bg-emerald-400"
-                                        )}
-                                        onClick={() =>
-                                          void toggleGuest(
-                                            guest,
-                                            guest.isCheckedIn ? "out" : "in"
-                                          )
-                                        }
-                                      >
-                                        {guest.isCheckedIn ? "Check out" : "Check in"}
-                                      </button>
-                                    </div>
-                                  </li>
-                                ))}
-                              </ul>
-                            )}
-                          </div>
-                        </div>
-                      ) : (
-                        <div className="flex h-full flex-col items-center justify-center gap-2 text-sm text-slate-400">
-                          <p>Select a brother to view their guests.</p>
-                        </div>
-                      )}
-                    </div>
-                  </div>
+                      );
+                    })}
+                  </ul>
                 )}
               </div>
             </section>
@@ -864,12 +1587,20 @@ function App() {
               <StatCard label="Checked In" value={totalCheckIns} tone="success" />
               <StatCard label="Checked Out" value={totalCheckOuts} tone="info" />
               <StatCard
-                label="Present Now"
+                label={stats.capacity != null ? `Present Now · Capacity ${stats.capacity.toLocaleString()}` : "Present Now"}
                 value={presentNow}
                 tone="warning"
-                description={`Checked in (${totalCheckIns.toLocaleString()}) − Checked out (${totalCheckOuts.toLocaleString()}) = ${presentNow.toLocaleString()}`}
+                description={
+                  stats.nearCapacity
+                    ? `Near capacity: ${presentNow.toLocaleString()} of ${stats.capacity?.toLocaleString()} inside`
+                    : `Checked in (${totalCheckIns.toLocaleString()}) − Checked out (${totalCheckOuts.toLocaleString()}) = ${presentNow.toLocaleString()}`
+                }
+              />
+              <StatCard
+                label="Total Guests"
+                value={totalGuests}
+                description={`${(stats.walkIns ?? 0).toLocaleString()} walk-ins added at the door`}
               />
-              <StatCard label="Total Guests" value={totalGuests} />
             </section>
 
             <section className="rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
@@ -923,9 +1654,188 @@ function App() {
                         <div className="font-medium">{guest.displayName}</div>
                         <div className="text-xs text-slate-400">
                           {guest.memberHost ? `Host: ${guest.memberHost}` : "No host"}
-                          {guest.inTs ? ` · In at ${guest.inTs}` : ""}
+                          {guest.inTs ? ` · In at ${formatClock(guest.inTs, selectedEvent?.timezone)}` : ""}
                           {guest.operator ? ` · ${guest.operator}` : ""}
+                          {guest.isWalkIn ? " · Walk-in" : ""}
+                        </div>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+            </section>
+
+            <section className="rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
+              <div className="border-b border-slate-800 px-4 py-2 text-xs uppercase tracking-wide text-slate-400">
+                Recent Actions
+              </div>
+              <div className="max-h-64 overflow-y-auto">
+                {history.length === 0 ? (
+                  <div className="px-4 py-6 text-sm text-slate-400">Nothing to undo yet.</div>
+                ) : (
+                  <ul className="divide-y divide-slate-800">
+                    {history.map((entry) => (
+                      <li
+                        key={entry.id}
+                        className={clsx(
+                          "px-4 py-3 text-sm",
+                          entry.undoneAt ? "text-slate-500 line-through" : "text-slate-200"
+                        )}
+                      >
+                        <span className="font-medium">{HISTORY_LABELS[entry.action]}</span>{" "}
+                        {entry.displayName}
+                        <span className="text-xs text-slate-400">
+                          {` · ${formatClock(entry.createdAt, selectedEvent?.timezone)}`}
+                          {entry.operator ? ` · ${entry.operator}` : ""}
+                          {entry.undoneAt
+                            ? ` · undone${entry.undoneBy ? ` by ${entry.undoneBy}` : ""}`
+                            : ""}
+                        </span>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+            </section>
+
+            <section className="rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
+              <div className="border-b border-slate-800 px-4 py-2 text-xs uppercase tracking-wide text-slate-400">
+                Do Not Admit
+              </div>
+              <form
+                className="flex gap-2 border-b border-slate-800 px-4 py-3"
+                onSubmit={(event) => {
+                  event.preventDefault();
+                  void addBan();
+                }}
+              >
+                <input
+                  value={banName}
+                  onChange={(event) => setBanName(event.target.value)}
+                  placeholder="Name"
+                  className="w-full rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 text-sm focus:border-emerald-400 focus:outline-none"
+                />
+                <input
+                  value={banReason}
+                  onChange={(event) => setBanReason(event.target.value)}
+                  placeholder="Reason (optional)"
+                  className="w-full rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 text-sm focus:border-emerald-400 focus:outline-none"
+                />
+                <button
+                  type="submit"
+                  disabled={banName.trim().length === 0}
+                  className="rounded-md border border-slate-600 px-3 py-1.5 text-sm font-medium text-slate-200 hover:border-slate-400 hover:text-white disabled:opacity-50"
+                >
+                  Ban
+                </button>
+              </form>
+              <div className="max-h-64 overflow-y-auto">
+                {bans.length === 0 ? (
+                  <div className="px-4 py-6 text-sm text-slate-400">Nobody is banned.</div>
+                ) : (
+                  <ul className="divide-y divide-slate-800">
+                    {bans.map((ban) => (
+                      <li key={ban.id} className="flex items-center justify-between gap-3 px-4 py-3 text-sm text-slate-200">
+                        <div>
+                          <span className="font-medium">{ban.name}</span>
+                          <span className="text-xs text-slate-400">
+                            {ban.reason ? ` · ${ban.reason}` : ""}
+                            {ban.addedBy ? ` · added by ${ban.addedBy}` : ""}
+                          </span>
+                        </div>
+                        <button
+                          onClick={() => void removeBan(ban)}
+                          className="rounded border border-slate-700 px-2 py-0.5 text-xs text-slate-300 hover:border-rose-400"
+                        >
+                          Lift
+                        </button>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+            </section>
+
+            <section className="rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
+              <div className="border-b border-slate-800 px-4 py-2 text-xs uppercase tracking-wide text-slate-400">
+                Imports
+              </div>
+              <div className="max-h-64 overflow-y-auto">
+                {batches.length === 0 ? (
+                  <div className="px-4 py-6 text-sm text-slate-400">No imports yet.</div>
+                ) : (
+                  <ul className="divide-y divide-slate-800">
+                    {batches.map((batch) => (
+                      <li key={batch.id} className="flex items-center justify-between gap-3 px-4 py-3 text-sm text-slate-200">
+                        <div>
+                          <span className="font-medium">{batch.fileName ?? `Import ${batch.id}`}</span>
+                          <span className="text-xs text-slate-400">
+                            {` · ${batch.mode} · ${batch.inserted.toLocaleString()} added`}
+                            {` · ${formatClock(batch.createdAt, selectedEvent?.timezone)}`}
+                            {batch.operator ? ` · ${batch.operator}` : ""}
+                          </span>
                         </div>
+                        {batch.rolledBackAt ? (
+                          <span className="text-xs text-slate-500">Rolled back</span>
+                        ) : (
+                          <button
+                            onClick={() => void rollbackBatch(batch)}
+                            disabled={batch.guests === 0}
+                            className="rounded border border-slate-700 px-2 py-0.5 text-xs text-slate-300 hover:border-rose-400 disabled:opacity-50"
+                          >
+                            Roll back
+                          </button>
+                        )}
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+            </section>
+
+            <section className="rounded-lg border border-slate-800 bg-slate-900/40 shadow-inner">
+              <div className="border-b border-slate-800 px-4 py-2 text-xs uppercase tracking-wide text-slate-400">
+                Audit Log
+              </div>
+              <form
+                className="flex gap-2 border-b border-slate-800 px-4 py-3"
+                onSubmit={(event) => {
+                  event.preventDefault();
+                  void searchAuditLog();
+                }}
+              >
+                <input
+                  value={auditGuest}
+                  onChange={(event) => setAuditGuest(event.target.value)}
+                  placeholder="Guest"
+                  className="w-full rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 text-sm focus:border-emerald-400 focus:outline-none"
+                />
+                <input
+                  value={auditOperator}
+                  onChange={(event) => setAuditOperator(event.target.value)}
+                  placeholder="Operator"
+                  className="w-full rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 text-sm focus:border-emerald-400 focus:outline-none"
+                />
+                <button
+                  type="submit"
+                  className="rounded-md border border-slate-600 px-3 py-1.5 text-sm font-medium text-slate-200 hover:border-slate-400 hover:text-white"
+                >
+                  Search
+                </button>
+              </form>
+              <div className="max-h-64 overflow-y-auto">
+                {auditEntries.length === 0 ? (
+                  <div className="px-4 py-6 text-sm text-slate-400">No matching entries.</div>
+                ) : (
+                  <ul className="divide-y divide-slate-800">
+                    {auditEntries.map((entry) => (
+                      <li key={entry.id} className="px-4 py-3 text-sm text-slate-200">
+                        <span className="font-medium">{entry.action.replace(/_/g, " ")}</span>
+                        {entry.guestName ? ` ${entry.guestName}` : ""}
+                        <span className="text-xs text-slate-400">
+                          {` · ${formatClock(entry.createdAt, selectedEvent?.timezone)}`}
+                          {entry.operator ? ` · ${entry.operator}` : ""}
+                        </span>
                       </li>
                     ))}
                   </ul>
@@ -954,6 +1864,139 @@ function App() {
         </div>
       )}
 
+      {eventDraft && (
+        <div className="fixed inset-0 z-40 flex items-center justify-center bg-slate-950/70 backdrop-blur">
+          <div className="w-full max-w-md rounded-xl border border-slate-700 bg-slate-900 p-6 shadow-xl">
+            <h2 className="text-lg font-semibold text-slate-100">
+              {eventDraft.id == null ? "New event" : "Edit event"}
+            </h2>
+            <div className="mt-4 grid gap-3 text-sm text-slate-300">
+              <label className="grid gap-1">
+                Name
+                <input
+                  autoFocus
+                  value={eventDraft.name}
+                  onChange={(event) => setEventDraft({ ...eventDraft, name: event.target.value })}
+                  className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                />
+              </label>
+              <label className="grid gap-1">
+                Date
+                <input
+                  type="date"
+                  value={eventDraft.eventDate}
+                  onChange={(event) => setEventDraft({ ...eventDraft, eventDate: event.target.value })}
+                  className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                />
+              </label>
+              <label className="grid gap-1">
+                Venue
+                <input
+                  value={eventDraft.venue}
+                  onChange={(event) => setEventDraft({ ...eventDraft, venue: event.target.value })}
+                  className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                />
+              </label>
+              <div className="grid grid-cols-2 gap-3">
+                <label className="grid gap-1">
+                  Doors open
+                  <input
+                    type="time"
+                    value={eventDraft.doorsOpen}
+                    onChange={(event) => setEventDraft({ ...eventDraft, doorsOpen: event.target.value })}
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+                <label className="grid gap-1">
+                  Doors close
+                  <input
+                    type="time"
+                    value={eventDraft.doorsClose}
+                    onChange={(event) => setEventDraft({ ...eventDraft, doorsClose: event.target.value })}
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+              </div>
+              <label className="grid gap-1">
+                Timezone
+                <input
+                  value={eventDraft.timezone}
+                  onChange={(event) => setEventDraft({ ...eventDraft, timezone: event.target.value })}
+                  placeholder="America/Chicago"
+                  className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                />
+              </label>
+              <div className="grid grid-cols-2 gap-3">
+                <label className="grid gap-1">
+                  Capacity
+                  <input
+                    type="number"
+                    min={1}
+                    value={eventDraft.capacity}
+                    onChange={(event) => setEventDraft({ ...eventDraft, capacity: event.target.value })}
+                    placeholder="Unlimited"
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+                <label className="grid gap-1">
+                  Warn at (%)
+                  <input
+                    type="number"
+                    min={1}
+                    max={100}
+                    value={eventDraft.capacityWarningPercent}
+                    onChange={(event) =>
+                      setEventDraft({ ...eventDraft, capacityWarningPercent: event.target.value })
+                    }
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+              </div>
+              <div className="grid grid-cols-2 gap-3">
+                <label className="grid gap-1">
+                  Guests per member
+                  <input
+                    type="number"
+                    min={1}
+                    value={eventDraft.guestQuota}
+                    onChange={(event) => setEventDraft({ ...eventDraft, guestQuota: event.target.value })}
+                    placeholder="Unlimited"
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+                <label className="grid gap-1">
+                  Inside at once
+                  <input
+                    type="number"
+                    min={1}
+                    value={eventDraft.presentQuota}
+                    onChange={(event) => setEventDraft({ ...eventDraft, presentQuota: event.target.value })}
+                    placeholder="Unlimited"
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1.5 focus:border-emerald-400 focus:outline-none"
+                  />
+                </label>
+              </div>
+            </div>
+            <div className="mt-6 flex gap-3">
+              <button
+                disabled={eventBusy || eventDraft.name.trim().length === 0}
+                onClick={() => void saveEvent()}
+                className="flex-1 rounded-md bg-emerald-500 px-4 py-2 text-sm font-semibold text-emerald-950 shadow hover:bg-emerald-400 disabled:cursor-not-allowed disabled:opacity-70"
+              >
+                {eventDraft.id == null ? "Create event" : "Save event"}
+              </button>
+              <button
+                disabled={eventBusy}
+                onClick={() => setEventDraft(null)}
+                className="flex-1 rounded-md border border-slate-700 px-4 py-2 text-sm text-slate-300 hover:border-slate-500 hover:text-white"
+              >
+                Cancel
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {showImportModePicker && pendingImportPath && (
         <div className="fixed inset-0 z-40 flex items-center justify-center bg-slate-950/70 backdrop-blur">
           <div className="w-full max-w-md rounded-xl border border-slate-700 bg-slate-900 p-6 shadow-xl">
@@ -964,20 +2007,101 @@ function App() {
             <p className="mt-3 break-all rounded-md bg-slate-800 px-3 py-2 text-xs text-slate-300">
               {pendingImportPath}
             </p>
+            <label className="mt-4 block text-xs uppercase tracking-wide text-slate-400">
+              Columns
+              <div className="mt-1 flex gap-2">
+                <select
+                  value={importProfile}
+                  onChange={(event) => setImportProfile(event.target.value)}
+                  className="flex-1 rounded-md border border-slate-700 bg-slate-800 px-2 py-2 text-sm normal-case tracking-normal text-slate-100"
+                >
+                  <option value="">App export headers</option>
+                  {importProfiles.map((profile) => (
+                    <option key={profile.id} value={profile.name}>
+                      {profile.name}
+                    </option>
+                  ))}
+                </select>
+                <button
+                  type="button"
+                  onClick={() => void beginProfile()}
+                  className="rounded-md border border-slate-700 px-3 text-sm normal-case tracking-normal text-slate-300 hover:border-slate-500 hover:text-white"
+                >
+                  {importProfile ? "Edit" : "New"}
+                </button>
+                {importProfile && (
+                  <button
+                    type="button"
+                    onClick={() => void deleteProfile()}
+                    className="rounded-md border border-slate-700 px-3 text-sm normal-case tracking-normal text-rose-300 hover:border-rose-500"
+                  >
+                    Delete
+                  </button>
+                )}
+              </div>
+            </label>
+            {profileDraft && (
+              <div className="mt-3 grid gap-2 rounded-md border border-slate-700 p-3 text-sm">
+                <input
+                  value={profileDraft.name}
+                  onChange={(event) => setProfileDraft({ ...profileDraft, name: event.target.value })}
+                  placeholder="Profile name (e.g. Google Form)"
+                  className="rounded-md border border-slate-700 bg-slate-800 px-2 py-1 text-slate-100"
+                />
+                {MAPPED_FIELDS.map((field) => (
+                  <label key={field.key} className="flex items-center justify-between gap-3 text-slate-300">
+                    {field.label}
+                    <select
+                      value={profileDraft.columns[field.key] ?? ""}
+                      onChange={(event) =>
+                        setProfileDraft({
+                          ...profileDraft,
+                          columns: { ...profileDraft.columns, [field.key]: event.target.value || null },
+                        })
+                      }
+                      className="w-48 rounded-md border border-slate-700 bg-slate-800 px-2 py-1 text-slate-100"
+                    >
+                      <option value="">—</option>
+                      {profileDraft.headers.map((header) => (
+                        <option key={header} value={header}>
+                          {header}
+                        </option>
+                      ))}
+                    </select>
+                  </label>
+                ))}
+                <div className="flex gap-2">
+                  <button
+                    type="button"
+                    onClick={() => void saveProfile()}
+                    className="flex-1 rounded-md bg-emerald-500 px-3 py-1 font-semibold text-emerald-950 hover:bg-emerald-400"
+                  >
+                    Save profile
+                  </button>
+                  <button
+                    type="button"
+                    onClick={() => setProfileDraft(null)}
+                    className="rounded-md border border-slate-700 px-3 py-1 text-slate-300 hover:border-slate-500"
+                  >
+                    Cancel
+                  </button>
+                </div>
+              </div>
+            )}
             <div className="mt-6 grid gap-3">
               <button
                 disabled={importBusy}
-                onClick={() => void importCsv("replace")}
+                onClick={() => void previewCsv("replace")}
                 className="rounded-md bg-rose-500 px-4 py-3 text-left text-sm font-semibold text-rose-50 shadow hover:bg-rose-400 disabled:cursor-wait disabled:opacity-70"
               >
                 Replace event
                 <span className="block text-xs font-normal text-rose-50/80">
-                  Clear all guests and check-ins before importing.
+                  Clear this event's guests and check-ins before importing.
                 </span>
               </button>
               <button
                 disabled={importBusy}
-                onClick={() => void importCsv("append")}
+                onClick={() => void previewCsv("append")}
                 className="rounded-md bg-emerald-500 px-4 py-3 text-left text-sm font-semibold text-emerald-950 shadow hover:bg-emerald-400 disabled:cursor-wait disabled:opacity-70"
               >
                 Append only new names
@@ -985,12 +2109,21 @@ function App() {
                   Keep existing guests and add any new ones.
                 </span>
               </button>
+              <button
+                disabled={importBusy}
+                onClick={() => void previewCsv("sync")}
+                className="rounded-md bg-sky-500 px-4 py-3 text-left text-sm font-semibold text-sky-950 shadow hover:bg-sky-400 disabled:cursor-wait disabled:opacity-70"
+              >
+                Sync with the sheet
+                <span className="block text-xs font-normal text-sky-950/80">
+                  Add new names, move guests whose member changed, and mark guests no longer listed as removed.
+                </span>
+              </button>
             </div>
             <button
               onClick={() => {
                 if (!importBusy) {
-                  setShowImportModePicker(false);
-                  setPendingImportPath(null);
+                  closeImport();
                 }
               }}
               className="mt-4 w-full rounded-md border border-slate-700 px-4 py-2 text-sm text-slate-300 hover:border-slate-500 hover:text-white"
@@ -1000,6 +2133,133 @@ function App() {
           </div>
         </div>
       )}
+
+      {pendingImport && (
+        <div className="fixed inset-0 z-40 flex items-center justify-center bg-slate-950/70 backdrop-blur">
+          <div className="flex max-h-[85vh] w-full max-w-3xl flex-col rounded-xl border border-slate-700 bg-slate-900 p-6 shadow-xl">
+            <h2 className="text-lg font-semibold text-slate-100">
+              Preview {pendingImport.mode}
+            </h2>
+            <p className="mt-2 text-sm text-slate-400">
+              {pendingImport.preview.summary.total_rows.toLocaleString()} rows would add{" "}
+              {pendingImport.preview.summary.inserted.toLocaleString()} guests
+              {pendingImport.preview.summary.over_quota.length > 0
+                ? `, ${pendingImport.preview.summary.over_quota.length} over quota`
+                : ""}
+              {pendingImport.preview.summary.banned.length > 0
+                ? `, ${pendingImport.preview.summary.banned.length} on the banned list`
+                : ""}
+              . Nothing has been written yet.
+            </p>
+            {pendingImport.preview.summary.replaced && (
+              <p
+                className={`mt-1 text-sm ${
+                  pendingImport.preview.summary.replaced.checked_in > 0 ? "text-amber-300" : "text-slate-400"
+                }`}
+              >
+                The current {pendingImport.preview.summary.replaced.guests.toLocaleString()} guests and{" "}
+                {pendingImport.preview.summary.replaced.checkins.toLocaleString()} check-ins are archived first
+                {pendingImport.preview.summary.replaced.checked_in > 0
+                  ? `; ${pendingImport.preview.summary.replaced.checked_in} of those guests are checked in right now`
+                  : ""}
+                .
+              </p>
+            )}
+            {pendingImport.preview.summary.synced && (
+              <p className="mt-1 text-sm text-slate-400">
+                Sync: {pendingImport.preview.summary.synced.added} added,{" "}
+                {pendingImport.preview.summary.synced.updated} updated,{" "}
+                {pendingImport.preview.summary.synced.removed} removed,{" "}
+                {pendingImport.preview.summary.synced.unchanged} unchanged. Check-in history is kept.
+              </p>
+            )}
+            <div className="mt-4 flex-1 overflow-y-auto rounded-md border border-slate-800 text-xs">
+              {pendingImport.preview.rows.map((row, index) => (
+                <div key={index} className="border-b border-slate-800 px-3 py-2 last:border-b-0">
+                  <div className="flex justify-between text-slate-400">
+                    <span>Row {row.source_row ?? "?"}</span>
+                    <span>Host: {row.member_host || "—"}</span>
+                  </div>
+                  {row.guests.length === 0 && <div className="text-amber-300">No guest names</div>}
+                  {row.guests.map((guest, guestIndex) => (
+                    <div
+                      key={guestIndex}
+                      className={
+                        ["added", "updated", "restored"].includes(guest.outcome) ? "text-slate-200" : "text-slate-500"
+                      }
+                    >
+                      {guest.name}
+                      {guest.outcome === "duplicate" &&
+                        ` · already listed as ${guest.duplicate_of?.display_name ?? guest.name}` +
+                          (guest.duplicate_of?.source_row != null ? ` (row ${guest.duplicate_of.source_row})` : "")}
+                      {guest.outcome === "over_quota" && " · over member quota"}
+                      {guest.outcome === "unchanged" && " · no change"}
+                      {guest.outcome === "updated" && ` · moved from ${guest.duplicate_of?.member_host || "no member"}`}
+                      {guest.outcome === "restored" && " · back on the list"}
+                      {guest.banned_name && <span className="text-rose-300">{` · banned (${guest.banned_name})`}</span>}
+                    </div>
+                  ))}
+                  {row.history && (
+                    <div className="text-slate-400">
+                      History: in {formatClock(row.history.in_ts, selectedEvent?.timezone)}
+                      {row.history.out_ts ? `, out ${formatClock(row.history.out_ts, selectedEvent?.timezone)}` : ""}
+                    </div>
+                  )}
+                  {row.unparsed_check_in_time && (
+                    <div className="text-amber-300">Unreadable check-in time “{row.unparsed_check_in_time}”</div>
+                  )}
+                  {row.unparsed_check_out_time && (
+                    <div className="text-amber-300">Unreadable check-out time “{row.unparsed_check_out_time}”</div>
+                  )}
+                  {row.ambiguities.map((guess, guessIndex) => (
+                    <div key={`guess-${guessIndex}`} className="text-amber-300">
+                      Check “{guess.text}”: {AMBIGUITY_LABELS[guess.kind]}, read as{" "}
+                      {guess.names.length > 0 ? guess.names.join(", ") : "no names"}
+                    </div>
+                  ))}
+                </div>
+              ))}
+              {pendingImport.preview.removed.length > 0 && (
+                <div className="px-3 py-2">
+                  <div className="text-slate-400">Not on the sheet, marked removed</div>
+                  {pendingImport.preview.removed.map((guest) => (
+                    <div key={guest.guest_id} className="text-amber-300">
+                      {guest.display_name}
+                      {guest.member_host ? ` · ${guest.member_host}` : ""}
+                    </div>
+                  ))}
+                </div>
+              )}
+            </div>
+            <div className="mt-4 flex gap-3">
+              <button
+                disabled={importBusy}
+                onClick={() => void importCsv()}
+                className="flex-1 rounded-md bg-emerald-500 px-4 py-2 text-sm font-semibold text-emerald-950 shadow hover:bg-emerald-400 disabled:cursor-wait disabled:opacity-70"
+              >
+                Import
+              </button>
+              <button
+                disabled={importBusy}
+                onClick={() => {
+                  setPendingImport(null);
+                  setShowImportModePicker(true);
+                }}
+                className="rounded-md border border-slate-700 px-4 py-2 text-sm text-slate-300 hover:border-slate-500 hover:text-white"
+              >
+                Back
+              </button>
+              <button
+                disabled={importBusy}
+                onClick={closeImport}
+                className="rounded-md border border-slate-700 px-4 py-2 text-sm text-slate-300 hover:border-slate-500 hover:text-white"
+              >
+                Cancel
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
     </div>
   );
 }